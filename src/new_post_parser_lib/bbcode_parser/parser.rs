@@ -0,0 +1,289 @@
+use std::collections::{HashMap, HashSet};
+use linked_hash_map::LinkedHashMap;
+use crate::html_parser::node::Node;
+use crate::{BbCodeParser, Element};
+
+// Tags longer than that are not even considered to be tags
+const MAX_TAG_LENGTH: usize = 16;
+
+lazy_static! {
+  static ref SUPPORTED_TAGS: HashSet<&'static str> = {
+    let mut set = HashSet::new();
+
+    set.insert("spoiler");
+    set.insert("code");
+    set.insert("b");
+    set.insert("i");
+    set.insert("u");
+    set.insert("s");
+    set.insert("sup");
+    set.insert("sub");
+    set.insert("math");
+    set.insert("eqn");
+
+    return set;
+  };
+
+  // The contents of these tags are never parsed (no nested tags, quotes or line breaks)
+  static ref RAW_CONTENT_TAGS: HashSet<&'static str> = {
+    let mut set = HashSet::new();
+
+    set.insert("code");
+    set.insert("math");
+    set.insert("eqn");
+
+    return set;
+  };
+}
+
+struct BbCodeTag {
+  tag_name: String,
+  is_closing: bool,
+  // Amount of chars the tag takes including the brackets
+  length: usize
+}
+
+/// What the parser does with a tag found at [start]. [start] and [end] are char offsets.
+struct PlannedTag {
+  start: usize,
+  end: usize,
+  tag_name: String,
+  action: TagAction
+}
+
+enum TagAction {
+  // Opening tag that has a closing tag
+  Open,
+  // Closing tag of the last [TagAction::Open] that was not closed yet
+  Close,
+  // Opening tag of [RAW_CONTENT_TAGS] together with its content and its closing tag
+  RawContent { content_start: usize, content_end: usize },
+  // Opening tag that is never closed, it's kept as text
+  Unclosed
+}
+
+struct OpenElement {
+  tag_name: String,
+  children: Vec<Node>
+}
+
+impl Default for BbCodeParser {
+  fn default() -> Self {
+    return BbCodeParser::new();
+  }
+}
+
+impl BbCodeParser {
+  pub fn new() -> BbCodeParser {
+    return BbCodeParser {};
+  }
+
+  pub fn parse(&self, text: &str) -> Vec<Node> {
+    let chars = text.chars().collect::<Vec<char>>();
+
+    let mut stack: Vec<OpenElement> = vec![OpenElement { tag_name: String::new(), children: Vec::new() }];
+    let mut text_buffer = String::with_capacity(16);
+    let mut planned_tags = plan_tags(&chars).into_iter().peekable();
+    let mut offset = 0;
+
+    while offset < chars.len() {
+      if let Option::Some(planned_tag) = planned_tags.next_if(|planned_tag| planned_tag.start == offset) {
+        flush_text(&mut text_buffer, &mut stack);
+
+        match planned_tag.action {
+          TagAction::Open => {
+            stack.push(OpenElement { tag_name: planned_tag.tag_name, children: Vec::new() });
+          }
+          TagAction::Close => {
+            let open_element = stack.pop().unwrap();
+            let element = create_element(&open_element.tag_name, open_element.children);
+            stack.last_mut().unwrap().children.push(Node::Element(element));
+          }
+          TagAction::RawContent { content_start, content_end } => {
+            let content = chars[content_start..content_end].iter().collect::<String>();
+
+            let element = create_element(&planned_tag.tag_name, vec![Node::Text(escape(&content))]);
+            stack.last_mut().unwrap().children.push(Node::Element(element));
+          }
+          TagAction::Unclosed => {
+            let tag_raw = chars[planned_tag.start..planned_tag.end].iter().collect::<String>();
+            stack.last_mut().unwrap().children.push(Node::Text(escape(&tag_raw)));
+          }
+        }
+
+        offset = planned_tag.end;
+        continue;
+      }
+
+      let ch = chars[offset];
+
+      if ch == '\n' {
+        flush_text(&mut text_buffer, &mut stack);
+
+        let mut line_break = create_element("br", Vec::new());
+        line_break.is_void_element = true;
+
+        stack.last_mut().unwrap().children.push(Node::Element(line_break));
+        offset += 1;
+        continue;
+      }
+
+      text_buffer.push(ch);
+      offset += 1;
+    }
+
+    flush_text(&mut text_buffer, &mut stack);
+    return stack.pop().unwrap().children;
+  }
+}
+
+/// Pairs the opening and the closing tags in one pass. A closing tag closes the last opened tag with the
+/// same name, the tags that were opened after that one are never closed. Tags that are not planned (closing
+/// tags without an opening one, unclosed [RAW_CONTENT_TAGS]) are just text.
+fn plan_tags(chars: &[char]) -> Vec<PlannedTag> {
+  let mut found_tags: Vec<(usize, BbCodeTag)> = Vec::new();
+
+  for (offset, ch) in chars.iter().enumerate() {
+    if *ch == '[' {
+      if let Option::Some(bbcode_tag) = parse_tag(chars, offset) {
+        found_tags.push((offset, bbcode_tag));
+      }
+    }
+  }
+
+  // [Key] name of one of [RAW_CONTENT_TAGS], [Value] ascending starts of its closing tags
+  let mut raw_closing_tag_starts: HashMap<String, Vec<usize>> = HashMap::new();
+
+  for (start, bbcode_tag) in &found_tags {
+    if bbcode_tag.is_closing && RAW_CONTENT_TAGS.contains(bbcode_tag.tag_name.as_str()) {
+      raw_closing_tag_starts.entry(bbcode_tag.tag_name.clone()).or_default().push(*start);
+    }
+  }
+
+  let mut planned_tags: Vec<PlannedTag> = Vec::with_capacity(found_tags.len());
+  // Indexes in [planned_tags] of the opening tags that are not closed yet
+  let mut open_tags: Vec<usize> = Vec::new();
+  // [Key] tag name, [Value] ascending indexes in [open_tags] of the tags with that name
+  let mut open_tag_depths: HashMap<String, Vec<usize>> = HashMap::new();
+  // Tags inside of an already planned tag (e.g. "[b]" of "[code=[b]") are a part of that tag
+  let mut planned_end = 0;
+
+  for (start, bbcode_tag) in found_tags {
+    if start < planned_end {
+      continue;
+    }
+
+    let end = start + bbcode_tag.length;
+
+    if !bbcode_tag.is_closing && RAW_CONTENT_TAGS.contains(bbcode_tag.tag_name.as_str()) {
+      let content_end_maybe = raw_closing_tag_starts.get(&bbcode_tag.tag_name)
+        .and_then(|closing_tag_starts| {
+          let index = closing_tag_starts.partition_point(|closing_tag_start| *closing_tag_start < end);
+          return closing_tag_starts.get(index).copied();
+        });
+
+      if let Option::Some(content_end) = content_end_maybe {
+        planned_end = content_end + tag_length(chars, content_end);
+
+        planned_tags.push(PlannedTag {
+          start,
+          end: planned_end,
+          tag_name: bbcode_tag.tag_name,
+          action: TagAction::RawContent { content_start: end, content_end }
+        });
+      }
+
+      continue;
+    }
+
+    if !bbcode_tag.is_closing {
+      open_tag_depths.entry(bbcode_tag.tag_name.clone()).or_default().push(open_tags.len());
+      open_tags.push(planned_tags.len());
+
+      planned_tags.push(PlannedTag { start, end, tag_name: bbcode_tag.tag_name, action: TagAction::Unclosed });
+      planned_end = end;
+      continue;
+    }
+
+    let depth_maybe = open_tag_depths.get(&bbcode_tag.tag_name).and_then(|depths| depths.last().copied());
+    let depth = match depth_maybe {
+      None => continue,
+      Some(depth) => depth
+    };
+
+    // Everything that was opened after the tag we are closing stays unclosed
+    while open_tags.len() > depth {
+      let planned_tag_index = open_tags.pop().unwrap();
+      open_tag_depths.get_mut(&planned_tags[planned_tag_index].tag_name).unwrap().pop();
+
+      if open_tags.len() == depth {
+        planned_tags[planned_tag_index].action = TagAction::Open;
+      }
+    }
+
+    planned_tags.push(PlannedTag { start, end, tag_name: bbcode_tag.tag_name, action: TagAction::Close });
+    planned_end = end;
+  }
+
+  return planned_tags;
+}
+
+fn parse_tag(chars: &[char], start: usize) -> Option<BbCodeTag> {
+  let mut offset = start + 1;
+  let is_closing = chars.get(offset) == Option::Some(&'/');
+
+  if is_closing {
+    offset += 1;
+  }
+
+  let name_start = offset;
+
+  while offset < chars.len() && chars[offset].is_ascii_alphabetic() {
+    offset += 1;
+  }
+
+  let tag_name = chars[name_start..offset].iter().collect::<String>().to_lowercase();
+  if !SUPPORTED_TAGS.contains(tag_name.as_str()) {
+    return Option::None;
+  }
+
+  // "[code=rust]", the value is not used
+  if !is_closing && chars.get(offset) == Option::Some(&'=') {
+    while offset < chars.len() && chars[offset] != ']' && offset - start < MAX_TAG_LENGTH {
+      offset += 1;
+    }
+  }
+
+  if chars.get(offset) != Option::Some(&']') {
+    return Option::None;
+  }
+
+  return Option::Some(BbCodeTag { tag_name, is_closing, length: offset + 1 - start });
+}
+
+/// Length of the tag that is known to start at [start]
+fn tag_length(chars: &[char], start: usize) -> usize {
+  return parse_tag(chars, start).map(|bbcode_tag| bbcode_tag.length).unwrap_or(0);
+}
+
+fn flush_text(text_buffer: &mut String, stack: &mut [OpenElement]) {
+  if text_buffer.is_empty() {
+    return;
+  }
+
+  stack.last_mut().unwrap().children.push(Node::Text(escape(text_buffer)));
+  text_buffer.clear();
+}
+
+fn create_element(tag_name: &str, children: Vec<Node>) -> Element {
+  return Element {
+    tag_name: String::from(tag_name),
+    attributes: LinkedHashMap::new(),
+    children,
+    is_void_element: false
+  };
+}
+
+fn escape(text: &str) -> String {
+  return html_escape::encode_text(text).to_string();
+}
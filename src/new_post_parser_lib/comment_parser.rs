@@ -1,24 +1,15 @@
 pub mod comment_parser {
   use std::collections::{HashMap, HashSet};
 
-  use crate::rules::anchor::AnchorRuleHandler;
-  use crate::rules::span::SpanHandler;
   use crate::rules::rule_handler::RuleHandler;
-  use crate::rules::line_break::LineBreakRuleHandler;
   use std::fmt;
-  use crate::{empty_set, set_of, TextPart, Attribute};
-  use crate::{PostRaw, PostParserContext, Element, ParsingRule, CommentParser, PostLink, SpannableData, Spannable, ParsedSpannableText};
-  use crate::rules::spoiler::SpoilerHandler;
-  use crate::rules::table_row::TableRowHandler;
-  use crate::rules::bold::BoldHandler;
-  use crate::rules::abbr::AbbrHandler;
+  use crate::{TextPart, Attribute};
+  use crate::{PostRaw, PostParserContext, Element, ParsingRule, CommentParser, PostLink, SpannableData, Spannable, ParsedSpannableText, SiteDescriptor};
   use std::rc::Rc;
-  use crate::rules::style::StyleHandler;
   use linked_hash_map::LinkedHashMap;
   use std::fmt::Debug;
-  use crate::rules::pre::PreHandler;
-  use crate::rules::table_data::TableDataHandler;
-  use crate::rules::table::TableHandler;
+  use crate::site_profiles::site_profile::SiteProfile;
+  use crate::site_profiles::four_chan::FourChanSiteProfile;
 
   const TAG: &str = "CommentParser";
 
@@ -31,8 +22,11 @@ pub mod comment_parser {
         PostLink::Dead { post_no } => {
           write!(f, "Dead(post_no: {})", post_no)
         },
-        PostLink::UrlLink { link } => {
-          write!(f, "UrlLink(link: {})", link)
+        PostLink::UrlLink { link, url_kind } => {
+          write!(f, "UrlLink(link: {}, url_kind: {:?})", link, url_kind)
+        },
+        PostLink::EmailLink { email } => {
+          write!(f, "EmailLink(email: {})", email)
         },
         PostLink::BoardLink { board_code } => {
           write!(f, "BoardLink(board_code: {})", board_code)
@@ -42,11 +36,57 @@ pub mod comment_parser {
         },
         PostLink::ThreadLink { board_code, thread_no, post_no } => {
           write!(f, "ThreadLink(board_code: {}, thread_no: {}, post_no: {})", board_code, thread_no, post_no)
+        },
+        PostLink::CrossSiteThreadLink { post_descriptor } => {
+          write!(
+            f,
+            "CrossSiteThreadLink(site_name: {}, board_code: {}, thread_no: {}, post_no: {})",
+            post_descriptor.site_name(),
+            post_descriptor.board_code(),
+            post_descriptor.thread_no(),
+            post_descriptor.post_no()
+          )
+        },
+        PostLink::CrossSiteBoardLink { board_descriptor } => {
+          write!(
+            f,
+            "CrossSiteBoardLink(site_name: {}, board_code: {})",
+            board_descriptor.site_descriptor.site_name,
+            board_descriptor.board_code
+          )
+        },
+        PostLink::CrossSiteSearchLink { board_descriptor, search_query } => {
+          write!(
+            f,
+            "CrossSiteSearchLink(site_name: {}, board_code: {}, search_query: {})",
+            board_descriptor.site_descriptor.site_name,
+            board_descriptor.board_code,
+            search_query
+          )
         }
       }
     }
   }
 
+  impl PostLink {
+    /// The site a cross-site link points to. [Option::None] for the links to the site of the post that
+    /// contains them and for urls.
+    pub fn cross_site_descriptor(&self) -> Option<&SiteDescriptor> {
+      return match self {
+        PostLink::CrossSiteThreadLink { post_descriptor } => Option::Some(post_descriptor.site_descriptor()),
+        PostLink::CrossSiteBoardLink { board_descriptor } |
+        PostLink::CrossSiteSearchLink { board_descriptor, .. } => Option::Some(&board_descriptor.site_descriptor),
+        PostLink::Quote { .. } |
+        PostLink::Dead { .. } |
+        PostLink::UrlLink { .. } |
+        PostLink::EmailLink { .. } |
+        PostLink::BoardLink { .. } |
+        PostLink::SearchLink { .. } |
+        PostLink::ThreadLink { .. } => Option::None
+      };
+    }
+  }
+
   impl fmt::Display for SpannableData {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
       return match self {
@@ -59,9 +99,36 @@ pub mod comment_parser {
         SpannableData::GreenText => {
           write!(f, "GreenText()")
         }
+        SpannableData::RedText => {
+          write!(f, "RedText()")
+        }
+        SpannableData::OrangeText => {
+          write!(f, "OrangeText()")
+        }
+        SpannableData::Heading { level } => {
+          write!(f, "Heading(level: {})", level)
+        }
         SpannableData::BoldText => {
           write!(f, "BoldText()")
         }
+        SpannableData::ItalicText => {
+          write!(f, "ItalicText()")
+        }
+        SpannableData::UnderlineText => {
+          write!(f, "UnderlineText()")
+        }
+        SpannableData::OverlineText => {
+          write!(f, "OverlineText()")
+        }
+        SpannableData::StrikethroughText => {
+          write!(f, "StrikethroughText()")
+        }
+        SpannableData::SuperscriptText => {
+          write!(f, "SuperscriptText()")
+        }
+        SpannableData::SubscriptText => {
+          write!(f, "SubscriptText()")
+        }
         SpannableData::TextForegroundColorRaw { color_hex: raw_color } => {
           write!(f, "TextForegroundColorRaw(raw_color: {})", raw_color)
         }
@@ -74,8 +141,8 @@ pub mod comment_parser {
         SpannableData::TextBackgroundColorId { color_id } => {
           write!(f, "TextBackgroundColorId(color_id: {})", color_id)
         }
-        SpannableData::FontSize { size } => {
-          write!(f, "FontSize(size: {})", size)
+        SpannableData::FontSize { size, scale } => {
+          write!(f, "FontSize(size: {}, scale: {})", size, scale)
         }
         SpannableData::FontWeight { weight } => {
           write!(f, "FontWeight(weight: {})", weight)
@@ -83,9 +150,33 @@ pub mod comment_parser {
         SpannableData::Monospace => {
           write!(f, "Monospace()")
         }
+        SpannableData::AsciiArt => {
+          write!(f, "AsciiArt()")
+        }
+        SpannableData::Math { is_display_mode } => {
+          write!(f, "Math(is_display_mode: {})", is_display_mode)
+        }
         SpannableData::ThemeJson { theme_name, is_light_theme } => {
           write!(f, "ThemeJson(theme_name: {}, is_light_theme: {})", theme_name, is_light_theme)
         }
+        SpannableData::SearchHighlight => {
+          write!(f, "SearchHighlight()")
+        }
+        SpannableData::Magnet { info_hash, display_name } => {
+          write!(f, "Magnet(info_hash: {}, display_name: {:?})", info_hash, display_name)
+        }
+        SpannableData::Hash { algorithm, value } => {
+          write!(f, "Hash(algorithm: {:?}, value: {})", algorithm, value)
+        }
+        SpannableData::ListItem => {
+          write!(f, "ListItem()")
+        }
+        SpannableData::BlockQuote => {
+          write!(f, "BlockQuote()")
+        }
+        SpannableData::HorizontalRule => {
+          write!(f, "HorizontalRule()")
+        }
       }
     }
   }
@@ -139,21 +230,21 @@ pub mod comment_parser {
   }
 
   impl Attribute {
-    fn with_name(attr_name: &str) -> Attribute {
+    pub fn with_name(attr_name: &str) -> Attribute {
       return Attribute {
         attr_name: attr_name.to_string(),
         attr_value: Option::None
       }
     }
 
-    fn with_name_and_value(attr_name: &str, attr_value: &str) -> Attribute {
+    pub fn with_name_and_value(attr_name: &str, attr_value: &str) -> Attribute {
       return Attribute {
         attr_name: attr_name.to_string(),
         attr_value: Option::Some(attr_value.to_string())
       }
     }
 
-    fn with_class(attr_value: &str) -> Attribute {
+    pub fn with_class(attr_value: &str) -> Attribute {
       return Attribute {
         attr_name: "class".to_string(),
         attr_value: Option::Some(attr_value.to_string())
@@ -171,7 +262,7 @@ pub mod comment_parser {
     }
 
     pub fn high_priority(&self) -> bool {
-      return self.required_attributes.len() > 0;
+      return !self.required_attributes.is_empty();
     }
 
     pub fn applies(&self, element: &Element) -> bool {
@@ -209,12 +300,26 @@ pub mod comment_parser {
 
   impl CommentParser<'_> {
 
-    pub fn new(post_parser_context: &PostParserContext) -> CommentParser {
-      return CommentParser {
+    pub fn new<'a>(post_parser_context: &'a PostParserContext, site_profile: &dyn SiteProfile) -> CommentParser<'a> {
+      let mut comment_parser = CommentParser {
         post_parser_context,
+        comment_format: site_profile.comment_format(),
+        detect_plain_text_quotes: site_profile.detect_plain_text_quotes(),
+        theme_color_mapper: site_profile.theme_color_mapper(),
+        post_link_resolver: site_profile.post_link_resolver(),
         matching_rules: LinkedHashMap::new(),
         replacement_rules: HashMap::new()
+      };
+
+      for rule in site_profile.matching_rules() {
+        comment_parser.add_matching_rule(Rc::new(rule));
       }
+
+      for (pattern, value) in site_profile.replacement_rules() {
+        comment_parser.add_replacement_rule(&pattern, &value);
+      }
+
+      return comment_parser;
     }
 
     fn add_matching_rule(&mut self, rule: Rc<ParsingRule>) {
@@ -225,6 +330,17 @@ pub mod comment_parser {
       self.matching_rules.get_mut(&rule.tag_name).unwrap().push(rule);
     }
 
+    /// The rules are defined by the site profile this parser was created with. Kept for the callers of the
+    /// api from before site profiles, replaces the current rules with the ones of the 4chan profile.
+    #[deprecated(note = "matching rules come from SiteProfile::matching_rules(), use PostParser::add_site_profile()")]
+    pub fn add_default_matching_rules(&mut self) {
+      self.matching_rules.clear();
+
+      for rule in FourChanSiteProfile::new().matching_rules() {
+        self.add_matching_rule(Rc::new(rule));
+      }
+    }
+
     pub fn add_replacement_rule(&mut self, pattern: &str, value: &str) {
       let result = self.replacement_rules.insert(String::from(pattern), String::from(value));
 
@@ -233,26 +349,6 @@ pub mod comment_parser {
       }
     }
 
-    pub fn add_default_matching_rules(&mut self) {
-      // Wildcard rules go first
-      self.add_matching_rule(Rc::new(ParsingRule::new("*", set_of!(Attribute::with_name("style")), Rc::new(StyleHandler::new()))));
-
-      // Then go rules that require specific attributes
-      self.add_matching_rule(Rc::new(ParsingRule::new("span", set_of!(Attribute::with_class("abbr")), Rc::new(AbbrHandler::new()))));
-      self.add_matching_rule(Rc::new(ParsingRule::new("pre", set_of!(Attribute::with_name_and_value("*", "prettyprint")), Rc::new(PreHandler::new()))));
-
-      // Then go general rules for the whole tag
-      self.add_matching_rule(Rc::new(ParsingRule::new("span", empty_set!(), Rc::new(SpanHandler::new()))));
-      self.add_matching_rule(Rc::new(ParsingRule::new("a", empty_set!(), Rc::new(AnchorRuleHandler::new()))));
-      self.add_matching_rule(Rc::new(ParsingRule::new("br", empty_set!(), Rc::new(LineBreakRuleHandler::new()))));
-      self.add_matching_rule(Rc::new(ParsingRule::new("s", empty_set!(), Rc::new(SpoilerHandler::new()))));
-      self.add_matching_rule(Rc::new(ParsingRule::new("tr", empty_set!(), Rc::new(TableRowHandler::new()))));
-      self.add_matching_rule(Rc::new(ParsingRule::new("td", empty_set!(), Rc::new(TableDataHandler::new()))));
-      self.add_matching_rule(Rc::new(ParsingRule::new("b", empty_set!(), Rc::new(BoldHandler::new()))));
-      self.add_matching_rule(Rc::new(ParsingRule::new("strong", empty_set!(), Rc::new(BoldHandler::new()))));
-      self.add_matching_rule(Rc::new(ParsingRule::new("table", empty_set!(), Rc::new(TableHandler::new()))));
-    }
-
     pub fn get_matching_rules(&self, element: &Element) -> Option<Vec<Rc<ParsingRule>>> {
       let mut all_rules: Vec<Rc<ParsingRule>> = Vec::with_capacity(16);
 
@@ -268,17 +364,17 @@ pub mod comment_parser {
             if &required_attribute.attr_name != "*" {
               let element_attr_value_maybe = element.attributes.get(&required_attribute.attr_name);
 
-              let element_attr_value = if let Option::None = element_attr_value_maybe {
-                all_req_attributes_match = false;
-                break;
-              } else {
-                element_attr_value_maybe.unwrap()
+              let element_attr_value = match element_attr_value_maybe {
+                None => {
+                  all_req_attributes_match = false;
+                  break;
+                }
+                Some(element_attr_value) => element_attr_value
               };
 
-              let required_attr_value = if let Option::None = required_attribute.attr_value {
-                continue;
-              } else {
-                required_attribute.attr_value.as_ref().unwrap()
+              let required_attr_value = match &required_attribute.attr_value {
+                None => continue,
+                Some(required_attr_value) => required_attr_value
               };
 
               if element_attr_value != required_attr_value {
@@ -318,11 +414,10 @@ pub mod comment_parser {
         let high_priority = index == 0;
 
         for rule in &rules {
-          if rule.high_priority() == high_priority && rule.applies(element) {
-            if rule.handler.pre_handle(post_raw, self.post_parser_context, element, out_text_parts, out_spannables) {
+          if rule.high_priority() == high_priority && rule.applies(element)
+            && rule.handler.pre_handle(post_raw, self.post_parser_context, element, out_text_parts, out_spannables) {
               return true
             }
-          }
         }
       }
 
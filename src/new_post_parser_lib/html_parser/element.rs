@@ -33,7 +33,7 @@ impl Element {
       Node::Text(text) => output.push_str(text),
       Node::Element(element) => {
         for child in element.children.iter() {
-          self.collect_text_internal(&child, output);
+          self.collect_text_internal(child, output);
         }
       }
     }
@@ -24,6 +24,12 @@ lazy_static! {
   };
 }
 
+impl Default for HtmlParser {
+  fn default() -> Self {
+    return HtmlParser::new();
+  }
+}
+
 impl HtmlParser {
   pub fn new() -> HtmlParser {
     return HtmlParser {};
@@ -45,11 +51,11 @@ impl HtmlParser {
     let mut current_buffer: Vec<u16> = Vec::with_capacity(16);
 
     while local_offset < html.len() {
-      let curr_char = html[local_offset as usize] as u16;
+      let curr_char = html[local_offset];
 
       if curr_char == '<' as u16 {
-        if current_buffer.len() > 0 {
-          let u16_string = String::from_utf16_lossy(&current_buffer.as_slice());
+        if !current_buffer.is_empty() {
+          let u16_string = String::from_utf16_lossy(current_buffer.as_slice());
 
           out_nodes.push(Node::Text(u16_string));
           current_buffer.clear();
@@ -57,7 +63,7 @@ impl HtmlParser {
 
         local_offset += 1;
 
-        let next_char = html[local_offset as usize] as u16;
+        let next_char = html[local_offset];
         if next_char == '/' as u16 {
           let offset = self.skip_tag_end(html, local_offset);
           local_offset = offset;
@@ -76,8 +82,8 @@ impl HtmlParser {
       local_offset += 1;
     }
 
-    if current_buffer.len() > 0 {
-      let u16_string = String::from_utf16_lossy(&current_buffer.as_slice());
+    if !current_buffer.is_empty() {
+      let u16_string = String::from_utf16_lossy(current_buffer.as_slice());
 
       out_nodes.push(Node::Text(u16_string));
       current_buffer.clear();
@@ -91,7 +97,7 @@ impl HtmlParser {
     let mut tag_raw: Vec<u16> = Vec::with_capacity(32);
 
     while local_offset < html.len() {
-      let ch = html[local_offset as usize] as u16;
+      let ch = html[local_offset];
       if ch == '>' as u16 {
         break;
       }
@@ -123,11 +129,11 @@ impl HtmlParser {
     return (updated_element, new_offset);
   }
 
-  fn skip_tag_end(&self, html: &Vec<u16>, start: usize) -> usize {
+  fn skip_tag_end(&self, html: &[u16], start: usize) -> usize {
     let mut local_offset = start;
 
     while local_offset < html.len() {
-      let ch = html[local_offset as usize] as u16;
+      let ch = html[local_offset];
       if ch == '>' as u16 {
         return local_offset + 1;
       }
@@ -139,7 +145,7 @@ impl HtmlParser {
   }
 
   fn create_tag(&self, tag_raw: &String) -> Element {
-    let tag_parts = self.split_into_parts_by_separator(&tag_raw, ' ' as u16);
+    let tag_parts = self.split_into_parts_by_separator(tag_raw, ' ' as u16);
     if tag_parts.is_empty() {
       panic!("tag_parts is empty! tag_raw={}", tag_raw);
     }
@@ -149,7 +155,7 @@ impl HtmlParser {
 
     for tag_part in tag_parts {
       if !tag_part.contains("=") {
-        tag_name_maybe = Option::Some(String::from(tag_part));
+        tag_name_maybe = Option::Some(tag_part);
         continue;
       }
 
@@ -180,14 +186,14 @@ impl HtmlParser {
     let is_void_element = VOID_ELEMENTS.contains(&tag_name.as_str());
 
     return Element {
-      tag_name: tag_name,
+      tag_name,
       attributes,
       children: Vec::with_capacity(4),
       is_void_element
     };
   }
 
-  fn split_into_parts_by_separator(&self, tag_raw: &String, separator: u16) -> Vec<String> {
+  fn split_into_parts_by_separator(&self, tag_raw: &str, separator: u16) -> Vec<String> {
     let mut is_inside_string = false;
     let mut offset: usize = 0;
     let mut tag_parts: Vec<String> = Vec::with_capacity(4);
@@ -195,14 +201,14 @@ impl HtmlParser {
     let tag_bytes = tag_raw.encode_utf16().collect::<Vec<u16>>();
 
     while offset < tag_bytes.len() {
-      let ch = tag_bytes[offset as usize] as u16;
+      let ch = tag_bytes[offset];
 
       if ch == '\"' as u16 {
         is_inside_string = !is_inside_string;
       }
 
       if ch == separator && !is_inside_string {
-        let u16_string = String::from_utf16_lossy(&current_tag_part.as_slice());
+        let u16_string = String::from_utf16_lossy(current_tag_part.as_slice());
         tag_parts.push(u16_string.clone());
         current_tag_part.clear();
 
@@ -214,8 +220,8 @@ impl HtmlParser {
       offset += 1;
     }
 
-    if current_tag_part.len() > 0 {
-      let u16_string = String::from_utf16_lossy(&current_tag_part.as_slice());
+    if !current_tag_part.is_empty() {
+      let u16_string = String::from_utf16_lossy(current_tag_part.as_slice());
       tag_parts.push(u16_string.clone());
       current_tag_part.clear();
     }
@@ -238,7 +244,7 @@ impl HtmlParser {
     for node in nodes {
       match node {
         Node::Text(text) => {
-          iterator(format!("{}", text));
+          iterator(text.to_string());
         }
         Node::Element(element) => {
           iterator(format!("<{}{}>", &element.tag_name, self.debug_format_attributes(&element.attributes)));
@@ -267,7 +273,7 @@ impl HtmlParser {
     for node in nodes {
       match node {
         Node::Text(text) => {
-          iterator(format!("{}", text));
+          iterator(text.to_string());
         }
         Node::Element(element) => {
           iterator(format!("<{}{}>", &element.tag_name, self.debug_format_attributes(&element.attributes)));
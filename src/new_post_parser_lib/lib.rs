@@ -11,8 +11,12 @@ use crate::rules::rule_handler::RuleHandler;
 use core::{fmt};
 use std::rc::Rc;
 use std::fmt::{Debug};
+use regex::Regex;
 
+// The files wrap their content into a module of the same name, the public api is re-exported from here
+#[allow(clippy::module_inception)]
 mod post_parser;
+#[allow(clippy::module_inception)]
 mod comment_parser;
 mod parsing_error;
 
@@ -29,6 +33,19 @@ mod rules {
   pub mod abbr;
   pub mod style;
   pub mod pre;
+  pub mod text_style;
+  pub mod block;
+}
+
+pub mod site_profiles {
+  pub mod site_profile;
+  pub mod four_chan;
+  pub mod dvach;
+  pub mod lynx_chan;
+  pub mod vichan;
+  pub mod foolfuuka;
+  pub mod bbcode;
+  pub mod res_link_resolver;
 }
 
 pub mod html_parser {
@@ -37,19 +54,42 @@ pub mod html_parser {
   pub mod parser;
 }
 
+pub mod bbcode_parser {
+  pub mod parser;
+}
+
+pub mod renderers {
+  pub mod span_events;
+  pub mod site_url_templates;
+  pub mod html_renderer;
+  pub mod markdown_renderer;
+  pub mod ansi_renderer;
+  pub mod preview;
+}
+
 pub mod util {
   pub mod macroses;
   pub mod helpers;
   pub mod color_decoder;
   pub mod style_tag_value_decoder;
   pub mod theme_json_extractor;
+  pub mod search_highlighter;
+  pub mod plain_text_quotes;
+  pub mod url_scanner;
+  pub mod url_classifier;
+  pub mod extension_markup;
+  pub mod hash_detector;
+  pub mod email_detector;
+  pub mod theme_color_mapper;
 }
 
 #[derive(Debug)]
 pub struct TextPart {
   text: String,
   characters_count: usize,
-  bytes_count: usize
+  bytes_count: usize,
+  // Line breaks that put the text after a block on a new line, removed when nothing goes after them
+  is_block_line_break: bool
 }
 
 impl TextPart {
@@ -60,36 +100,57 @@ impl TextPart {
     return TextPart {
       text,
       characters_count,
-      bytes_count
+      bytes_count,
+      is_block_line_break: false
     };
   }
+
+  fn block_line_break() -> TextPart {
+    let mut text_part = TextPart::new(String::from('\n'));
+    text_part.is_block_line_break = true;
+
+    return text_part;
+  }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct SiteDescriptor {
   pub site_name: String
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct BoardDescriptor {
   pub site_descriptor: SiteDescriptor,
   pub board_code: String
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct ThreadDescriptor {
   pub board_descriptor: BoardDescriptor,
   pub thread_no: u64
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct PostDescriptor {
   pub thread_descriptor: ThreadDescriptor,
   pub post_no: u64,
   pub post_sub_no: u64,
 }
 
+impl BoardDescriptor {
+  pub fn new(site_name: &str, board_code: &str) -> BoardDescriptor {
+    return BoardDescriptor {
+      site_descriptor: SiteDescriptor { site_name: site_name.to_string() },
+      board_code: board_code.to_string()
+    };
+  }
+}
+
 impl PostDescriptor {
+  pub fn site_descriptor(&self) -> &SiteDescriptor {
+    return &self.thread_descriptor.board_descriptor.site_descriptor;
+  }
+
   pub fn site_name(&self) -> &String {
     return &self.thread_descriptor.board_descriptor.site_descriptor.site_name;
   }
@@ -134,12 +195,16 @@ pub struct PostRaw {
 }
 
 impl PostRaw {
+  pub fn site_descriptor(&self) -> &SiteDescriptor {
+    return self.post_descriptor.site_descriptor();
+  }
+
   pub fn site_name(&self) -> &String {
-    return &self.post_descriptor.site_name();
+    return self.post_descriptor.site_name();
   }
 
   pub fn board_code(&self) -> &String {
-    return &self.post_descriptor.board_code();
+    return self.post_descriptor.board_code();
   }
 
   pub fn thread_no(&self) -> u64 {
@@ -199,11 +264,40 @@ pub struct Element {
 
 pub struct PostParser<'a> {
   post_parser_context: &'a PostParserContext,
-  pub comment_parser: Box<CommentParser<'a>>,
+  /// [Key] site name of a [SiteProfile], [Value] comment parser that uses the rules of that profile
+  comment_parsers: HashMap<String, CommentParser<'a>>,
+  /// [Key] host of a site ("boards.4chan.org"), [Value] site name of the [SiteProfile] that handles it
+  site_hosts: HashMap<String, String>,
+  url_scanner: util::url_scanner::UrlScanner,
+  url_classifier_registry: util::url_classifier::UrlClassifierRegistry,
+  // Overrides SiteProfile::theme_color_mapper for all sites
+  theme_color_mapper: Option<util::theme_color_mapper::ThemeColorMapper>,
+  min_font_size_scale: f32,
+  max_font_size_scale: f32,
+  search_query: Option<SearchQuery>,
+  // Overrides SiteProfile::detect_plain_text_quotes for all sites
+  detect_plain_text_quotes: bool,
+}
+
+#[derive(Debug, Clone)]
+pub enum SearchQuery {
+  Plain { query: String, ignore_case: bool, ignore_diacritics: bool },
+  Regex { regex: Regex }
+}
+
+/// The markup the comments of a site are written in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommentFormat {
+  Html,
+  BbCode
 }
 
 pub struct CommentParser<'a> {
   post_parser_context: &'a PostParserContext,
+  comment_format: CommentFormat,
+  detect_plain_text_quotes: bool,
+  theme_color_mapper: util::theme_color_mapper::ThemeColorMapper,
+  post_link_resolver: Rc<dyn site_profiles::site_profile::PostLinkResolver>,
   matching_rules: LinkedHashMap<String, Vec<Rc<ParsingRule>>>,
   /// [Key] what pattern in the comment text needs to be replaced with [Value]
   replacement_rules: HashMap<String, String>
@@ -290,29 +384,105 @@ pub enum SpannableData {
   Link(PostLink),
   Spoiler,
   GreenText,
+  RedText,
+  OrangeText,
+  // <h1>-<h6>, [level] is 1..=6. vichan's "==text==" (a red bold line) is level 2, the same as
+  // MediaWiki's "== text =="
+  Heading { level: u8 },
   BoldText,
-  // font-size:22px;font-size:150%;
-  FontSize { size: String },
-  // TODO: FontWeight is not implemented yet
-  // font-weight:600;font-weight:bold
-  FontWeight { weight: String },
+  ItalicText,
+  UnderlineText,
+  OverlineText,
+  StrikethroughText,
+  SuperscriptText,
+  SubscriptText,
+  // font-size:22px;font-size:150%;. [size] is the original value, [scale] is the multiplier of the base
+  // font size (22px is 1.375), clamped by PostParser::set_font_size_scale_range
+  FontSize { size: String, scale: f32 },
+  // font-weight:600;font-weight:bold (700)
+  FontWeight { weight: u16 },
   // color:#fd4d32
   Monospace,
+  // Shift-JIS art, needs a font with the right glyph widths and must never be wrapped
+  AsciiArt,
+  // TeX source of [math] (inline) or [eqn] (display mode) tags
+  Math { is_display_mode: bool },
+  // Always "#RRGGBBAA"
   TextForegroundColorRaw { color_hex: String },
   TextBackgroundColorRaw { color_hex: String },
   TextForegroundColorId { color_id: ChanThemeColorId },
   TextBackgroundColorId { color_id: ChanThemeColorId },
-  ThemeJson { theme_name: String, is_light_theme: bool }
+  ThemeJson { theme_name: String, is_light_theme: bool },
+  SearchHighlight,
+  // Covers the whole "magnet:?xt=urn:btih:..." uri, goes together with the url link of the same uri
+  Magnet { info_hash: String, display_name: Option<String> },
+  // Bare hash in the text ("c12fe1c06bba254a9dc9f519b335aa7c1367a88a")
+  Hash { algorithm: HashAlgorithm, value: String },
+  // <li>, covers the bullet ("• ") or the number ("1. ") too
+  ListItem,
+  BlockQuote,
+  // <hr>, covers the line of box drawing characters that replaces it
+  HorizontalRule
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum PostLink {
   Quote { post_no: u64 },
   Dead { post_no: u64 },
-  UrlLink { link: String },
+  UrlLink { link: String, url_kind: UrlKind },
+  /// Email addresses in the text and "mailto:" links, [email] is normalized ("User@example.com")
+  EmailLink { email: String },
   BoardLink { board_code: String },
   SearchLink { board_code: String, search_query: String },
-  ThreadLink { board_code: String, thread_no: u64, post_no: u64 }
+  ThreadLink { board_code: String, thread_no: u64, post_no: u64 },
+  /// Links to other sites (see SiteProfile::site_hosts). A link to a thread without a post has the
+  /// [PostDescriptor::post_no] equal to the thread_no, the same as [PostLink::ThreadLink].
+  CrossSiteThreadLink { post_descriptor: PostDescriptor },
+  CrossSiteBoardLink { board_descriptor: BoardDescriptor },
+  CrossSiteSearchLink { board_descriptor: BoardDescriptor, search_query: String }
+}
+
+/// What a [PostLink::UrlLink] points to, so that the app can show an embed or a preview without parsing the
+/// url again. See [util::url_classifier::UrlClassifierRegistry].
+#[derive(Debug, PartialEq, Clone)]
+pub enum UrlKind {
+  Generic,
+  YouTube { video_id: String, start_time_seconds: Option<u64> },
+  Streamable { video_id: String },
+  Twitter { tweet_id: u64 },
+  // "artist/track"
+  SoundCloud { track_path: String },
+  Pixiv { artwork_id: u64 },
+  Pastebin { paste_id: String },
+  // Direct links to files, [extension] is lowercase
+  Image { extension: String },
+  Video { extension: String },
+  Audio { extension: String },
+  /// For the classifiers added with [PostParser::add_url_classifier]
+  Custom { kind: String, id: String }
 }
 
-pub struct HtmlParser {}
\ No newline at end of file
+/// Guessed by the length and the alphabet of a [SpannableData::Hash], see [util::hash_detector]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum HashAlgorithm {
+  // 32 hex chars
+  Md5,
+  // 40 hex chars, torrent info hash (may as well be sha1)
+  Btih,
+  // 64 hex chars
+  Sha256,
+  // 128 hex chars
+  Sha512,
+  // "Qm..." (v0) or "bafy..." (v1)
+  IpfsCid
+}
+
+pub struct HtmlParser {}
+
+/// Converts BBCode-like markup ("[spoiler]text[/spoiler]", "[code]...[/code]") into the same
+/// [Node] tree that [HtmlParser] produces so that the rest of the parsing does not care about the format
+/// of the comment. Elements are named after the BBCode tags ("spoiler", "code", "b", ...) and line
+/// breaks become "br" elements. Quotes are left as text, they are detected later (see
+/// SiteProfile::detect_plain_text_quotes). The text is html-escaped because [PostParser] unescapes
+/// every text node. Unknown or unclosed tags are kept as text.
+pub struct BbCodeParser {}
\ No newline at end of file
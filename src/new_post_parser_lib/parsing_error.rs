@@ -9,4 +9,9 @@ impl fmt::Display for ParsingError {
   fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
     write!(f, "{}", self.msg)
   }
-}
\ No newline at end of file
+}
+impl ParsingError {
+  pub fn new(msg: String) -> ParsingError {
+    return ParsingError { msg };
+  }
+}
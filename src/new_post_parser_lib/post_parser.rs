@@ -1,15 +1,28 @@
 pub mod post_parser {
-  use crate::{PostRaw, PostParserContext, PostParser, CommentParser, ParsedPost, ParsedSpannableText, Spannable, SpannableData, PostLink, HtmlParser, TextPart, PostDescriptor};
+  use crate::{PostRaw, PostParserContext, PostParser, CommentParser, ParsedPost, ParsedSpannableText, Spannable, SpannableData, PostLink, HtmlParser, TextPart, Element, PostDescriptor, SearchQuery, SiteDescriptor, BoardDescriptor, BbCodeParser, UrlKind, CommentFormat};
+  use crate::site_profiles::site_profile::{SiteProfile, split_absolute_url};
+  use crate::site_profiles::four_chan::{self, FourChanSiteProfile};
+  use crate::site_profiles::dvach::DvachSiteProfile;
   use crate::html_parser::node::Node;
-  use std::collections::HashSet;
+  use std::collections::{HashSet, HashMap};
   use std::fmt;
-  use regex::Regex;
   use crate::util::helpers::{SumBy, MapJoin};
   use crate::util::theme_json_extractor::detect_and_extract_theme_json;
-
-  lazy_static! {
-    static ref LINK_PATTERN: Regex = Regex::new(r"https?://[-a-zA-Z0-9@:%._\+~#=]{1,256}\.[a-zA-Z0-9()]{1,6}\b(?:[-\w0-9()@:%_\+.~#?&//=!]*)").unwrap();
-  }
+  use crate::util::search_highlighter::detect_search_highlights;
+  use crate::util::plain_text_quotes::find_plain_text_quotes;
+  use crate::util::url_scanner::{UrlScanner, DEFAULT_URL_SCHEMES};
+  use crate::util::url_classifier::{UrlClassifierRegistry, UrlClassifier};
+  use crate::util::extension_markup::strip_extension_markup;
+  use crate::util::hash_detector::detect_magnets_and_hashes;
+  use crate::util::email_detector::{find_emails, parse_mailto};
+  use crate::util::theme_color_mapper::ThemeColorMapper;
+  use crate::util::style_tag_value_decoder::{clamp_font_size_scales, DEFAULT_MIN_FONT_SIZE_SCALE, DEFAULT_MAX_FONT_SIZE_SCALE};
+  use std::rc::Rc;
+  use crate::rules::anchor::handle_post_link;
+
+  const TAG: &str = "PostParser";
+  // Plain-text quotes are not detected inside of these elements (links and code/math blocks)
+  const NO_QUOTE_DETECTION_TAGS: &[&str] = &["a", "pre", "code", "math", "eqn"];
 
   impl PostParserContext {
     pub fn new(
@@ -42,6 +55,21 @@ pub mod post_parser {
     }
   }
 
+  impl ParsedSpannableText {
+    /// (start, len) in unicode characters of every [SpannableData::SearchHighlight] sorted by start. Useful
+    /// for "next/previous match" navigation.
+    pub fn search_highlights(&self) -> Vec<(usize, usize)> {
+      let mut search_highlights = self.spannables
+        .iter()
+        .filter(|spannable| spannable.spannable_data == SpannableData::SearchHighlight)
+        .map(|spannable| (spannable.start, spannable.len))
+        .collect::<Vec<(usize, usize)>>();
+
+      search_highlights.sort_unstable();
+      return search_highlights;
+    }
+  }
+
   impl ParsedPost {
     pub fn new(
       post_raw: &PostRaw,
@@ -62,17 +90,109 @@ pub mod post_parser {
     }
   }
 
-  impl PostParser<'_> {
-    pub fn new(post_parser_context: &PostParserContext) -> PostParser {
-      let mut comment_parser = CommentParser::new(post_parser_context);
-
-      comment_parser.add_default_matching_rules();
-      comment_parser.add_replacement_rule("<wbr>", "");
-
-      return PostParser {
+  impl<'a> PostParser<'a> {
+    /// Only the 4chan and 2ch profiles are registered. The engine profiles (LynxChan, vichan, FoolFuuka
+    /// and BBCode) are used by many sites so they are created for a site name and must be registered with
+    /// [PostParser::add_site_profile], posts of unregistered sites are parsed with the 4chan profile.
+    pub fn new(post_parser_context: &'a PostParserContext) -> PostParser<'a> {
+      let mut post_parser = PostParser {
         post_parser_context,
-        comment_parser: Box::new(comment_parser)
+        comment_parsers: HashMap::new(),
+        site_hosts: HashMap::new(),
+        url_scanner: UrlScanner::new(&DEFAULT_URL_SCHEMES),
+        url_classifier_registry: UrlClassifierRegistry::new(),
+        theme_color_mapper: Option::None,
+        min_font_size_scale: DEFAULT_MIN_FONT_SIZE_SCALE,
+        max_font_size_scale: DEFAULT_MAX_FONT_SIZE_SCALE,
+        search_query: Option::None,
+        detect_plain_text_quotes: false
       };
+
+      post_parser.add_site_profile(&FourChanSiteProfile::new());
+      post_parser.add_site_profile(&DvachSiteProfile::new());
+
+      return post_parser;
+    }
+
+    /// Adds (or replaces) a profile that will be used to parse posts of [SiteProfile::site_name]. A host
+    /// that already belongs to another profile stays with that profile, false is returned when that
+    /// happened to any of the [SiteProfile::site_hosts] (the profile itself is still added).
+    pub fn add_site_profile(&mut self, site_profile: &dyn SiteProfile) -> bool {
+      let site_name = site_profile.site_name();
+
+      let comment_parser = CommentParser::new(self.post_parser_context, site_profile);
+      self.comment_parsers.insert(site_name.to_string(), comment_parser);
+
+      let mut all_hosts_added = true;
+
+      for site_host in site_profile.site_hosts() {
+        if let Option::Some(other_site_name) = self.site_hosts.get(&site_host) {
+          if other_site_name != site_name {
+            all_hosts_added = false;
+            continue;
+          }
+        }
+
+        self.site_hosts.insert(site_host, site_name.to_string());
+      }
+
+      return all_hosts_added;
+    }
+
+    /// Posts of sites without a profile are parsed using the 4chan profile
+    pub fn comment_parser(&self, site_descriptor: &SiteDescriptor) -> &CommentParser<'_> {
+      let comment_parser_maybe = self.comment_parsers.get(&site_descriptor.site_name);
+      if comment_parser_maybe.is_some() {
+        return comment_parser_maybe.unwrap();
+      }
+
+      return self.comment_parsers.get(four_chan::SITE_NAME).unwrap();
+    }
+
+    /// Comment parser of the profile with [site_name], replaces the `comment_parser` field that PostParser
+    /// had before site profiles (its rules are the rules of the 4chan profile now).
+    pub fn comment_parser_mut(&mut self, site_name: &str) -> Option<&mut CommentParser<'a>> {
+      return self.comment_parsers.get_mut(site_name);
+    }
+
+    /// Schemes ("http", "ftp", "magnet" etc) of the urls that are converted into links. "www." links without a
+    /// scheme are always converted.
+    pub fn set_url_schemes(&mut self, url_schemes: &[&str]) {
+      self.url_scanner = UrlScanner::new(url_schemes);
+    }
+
+    /// [url_classifier] is checked before the built-in ones (YouTube, Twitter, direct links to images etc)
+    pub fn add_url_classifier(&mut self, url_classifier: Rc<dyn UrlClassifier>) {
+      self.url_classifier_registry.add_classifier(url_classifier);
+    }
+
+    /// Replaces the mapping of the hard-coded colors and classes to theme color ids of every site profile
+    /// (see SiteProfile::theme_color_mapper), use [ThemeColorMapper::new] to keep all of the colors raw.
+    /// [Option::None] brings back the mappers of the profiles.
+    pub fn set_theme_color_mapper(&mut self, theme_color_mapper: Option<ThemeColorMapper>) {
+      self.theme_color_mapper = theme_color_mapper;
+    }
+
+    /// [SpannableData::FontSize] scales are clamped to [min_scale]..=[max_scale] (0.5..=2.0 by default).
+    /// A NaN or infinite bound is replaced with its default value.
+    pub fn set_font_size_scale_range(&mut self, min_scale: f32, max_scale: f32) {
+      let min_scale = if min_scale.is_finite() { min_scale } else { DEFAULT_MIN_FONT_SIZE_SCALE };
+      let max_scale = if max_scale.is_finite() { max_scale } else { DEFAULT_MAX_FONT_SIZE_SCALE };
+
+      self.min_font_size_scale = min_scale.min(max_scale);
+      self.max_font_size_scale = max_scale.max(min_scale);
+    }
+
+    /// Every match of [search_query] in the parsed text will be marked with [SpannableData::SearchHighlight]
+    pub fn set_search_query(&mut self, search_query: Option<SearchQuery>) {
+      self.search_query = search_query;
+    }
+
+    /// Converts ">>123", ">>>/g/123" and ">>>/g/" written as plain text into links for the posts of all
+    /// sites, not only the ones whose profile does that (see SiteProfile::detect_plain_text_quotes). Meant
+    /// for plain-text sources: archive apis, reply drafts etc.
+    pub fn set_detect_plain_text_quotes(&mut self, detect_plain_text_quotes: bool) {
+      self.detect_plain_text_quotes = detect_plain_text_quotes;
     }
 
     pub fn parse_post(&self, post_raw: &PostRaw) -> ParsedPost {
@@ -94,14 +214,20 @@ pub mod post_parser {
     }
 
     pub fn parse_comment(&self, post_raw: &PostRaw) -> ParsedSpannableText {
-      let comment_raw = self.pre_process_comment(post_raw);
+      let comment_parser = self.comment_parser(post_raw.site_descriptor());
+
+      let comment_raw = self.pre_process_comment(comment_parser, post_raw);
       if comment_raw.is_empty() {
         return ParsedSpannableText::empty();
       }
 
       let html_parser = HtmlParser::new();
 
-      let html_parsing_result = html_parser.parse(comment_raw.as_str());
+      let html_parsing_result = match comment_parser.comment_format {
+        CommentFormat::Html => html_parser.parse(comment_raw.as_str()),
+        CommentFormat::BbCode => Result::Ok(BbCodeParser::new().parse(comment_raw.as_str()))
+      };
+
       if html_parsing_result.is_err() {
         let parser_error_message = format!(
           "Failed to parse comment_raw html, error={:?}",
@@ -111,21 +237,40 @@ pub mod post_parser {
         let post_comment_parsed = ParsedSpannableText::new(
           comment_raw.as_str(),
           Box::new(parser_error_message),
-          Box::new(Vec::new())
+          Box::default()
         );
 
         return post_comment_parsed;
       }
 
+      let mut nodes = html_parsing_result.unwrap();
+      let embeds = strip_extension_markup(&mut nodes, &self.url_classifier_registry);
+
       let mut out_text_parts: Vec<TextPart> = Vec::with_capacity(16);
       let mut out_spannables: Vec<Spannable> = Vec::with_capacity(8);
-      self.process_element(post_raw, &html_parsing_result.unwrap(), &mut out_text_parts, &mut out_spannables);
+      self.process_element(
+        comment_parser,
+        post_raw,
+        &nodes,
+        comment_parser.detect_plain_text_quotes || self.detect_plain_text_quotes,
+        &mut out_text_parts,
+        &mut out_spannables
+      );
+
+      remove_trailing_block_line_breaks(&mut out_text_parts, &mut out_spannables);
+      self.resolve_url_links(&embeds, &mut out_spannables);
+      self.theme_color_mapper(comment_parser).map_colors(&mut out_spannables);
+      clamp_font_size_scales(&mut out_spannables, self.min_font_size_scale, self.max_font_size_scale);
 
       let total_size = out_text_parts.iter().sum_by(&|text_part| text_part.characters_count as i32) as usize;
       let total_text = Box::new(out_text_parts.iter().map_join_cap(total_size, "", &|text_part| text_part.text.as_str()));
 
       detect_and_extract_theme_json(&total_text, &mut out_spannables);
 
+      if let Option::Some(search_query) = &self.search_query {
+        detect_search_highlights(&total_text, search_query, &mut out_spannables);
+      }
+
       return ParsedSpannableText::new(
         comment_raw.as_str(),
         total_text,
@@ -133,7 +278,7 @@ pub mod post_parser {
       );
     }
 
-    fn pre_process_comment(&self, post_raw: &PostRaw) -> String {
+    fn pre_process_comment(&self, comment_parser: &CommentParser, post_raw: &PostRaw) -> String {
       let comment_raw = &post_raw.com;
       if comment_raw.is_empty() {
         return String::from("");
@@ -141,17 +286,23 @@ pub mod post_parser {
 
       let mut result_comment_raw = String::from(comment_raw);
 
-      for (pattern, value) in &self.comment_parser.replacement_rules {
-        result_comment_raw = result_comment_raw.replace(pattern, &value);
+      for (pattern, value) in &comment_parser.replacement_rules {
+        result_comment_raw = result_comment_raw.replace(pattern, value);
       }
 
       return result_comment_raw;
     }
 
+    fn theme_color_mapper<'b>(&'b self, comment_parser: &'b CommentParser) -> &'b ThemeColorMapper {
+      return self.theme_color_mapper.as_ref().unwrap_or(&comment_parser.theme_color_mapper);
+    }
+
     fn process_element(
       &self,
+      comment_parser: &CommentParser,
       post_raw: &PostRaw,
       nodes: &Vec<Node>,
+      detect_quotes: bool,
       out_text_parts: &mut Vec<TextPart>,
       out_spannables: &mut Vec<Spannable>
     ) {
@@ -159,39 +310,281 @@ pub mod post_parser {
         match node {
           Node::Text(text) => {
             let unescaped_text = String::from(html_escape::decode_html_entities(text.as_str()));
-            self.detect_links(out_text_parts, &unescaped_text, out_spannables);
 
-            out_text_parts.push(TextPart::new(unescaped_text));
+            if detect_quotes {
+              self.process_text_with_quotes(post_raw, unescaped_text, out_text_parts, out_spannables);
+            } else {
+              self.push_text_with_links(post_raw, unescaped_text, out_text_parts, out_spannables);
+            }
           },
           Node::Element(element) => {
-            // store the current last indexes of out_text_parts/out_spannables because we may need
-            // them during post process phase to figure out what was added into
-            // out_text_parts/out_spannables
-            let prev_out_text_parts_index = out_text_parts.len().checked_sub(1).unwrap_or(0);
-            let prev_out_spannables_index = out_spannables.len().checked_sub(1).unwrap_or(0);
-
-            if self.comment_parser.pre_process_element(post_raw, &element, out_text_parts, out_spannables) {
+            // store the index of the first text part of the element and the current last index of
+            // out_spannables because we may need them during post process phase to figure out what was
+            // added into out_text_parts/out_spannables
+            let prev_out_text_parts_index = out_text_parts.len();
+            let prev_out_spannables_index = out_spannables.len().saturating_sub(1);
+            let out_spannables_count = out_spannables.len();
+
+            if comment_parser.pre_process_element(post_raw, element, out_text_parts, out_spannables) {
               // Element was fully processed, no need to check the child elements
+              self.resolve_anchor_site_link(
+                post_raw,
+                element,
+                prev_out_text_parts_index,
+                out_spannables_count,
+                out_text_parts,
+                out_spannables
+              );
+
               continue;
             }
 
             if !element.children.is_empty() {
-              self.process_element(post_raw, &element.children, out_text_parts, out_spannables);
+              let class_color_id = self.theme_color_mapper(comment_parser).class_color_id(element);
+              let text_length_before = out_text_parts.iter().sum_by(&|text_part| text_part.characters_count as i32) as usize;
+
+              let detect_child_quotes = detect_quotes
+                && !NO_QUOTE_DETECTION_TAGS.contains(&element.tag_name.as_str());
+
+              self.process_element(
+                comment_parser,
+                post_raw,
+                &element.children,
+                detect_child_quotes,
+                out_text_parts,
+                out_spannables
+              );
 
-              self.comment_parser.post_process_element(
+              comment_parser.post_process_element(
                 post_raw,
-                &element,
+                element,
                 prev_out_text_parts_index,
                 out_text_parts,
                 prev_out_spannables_index,
                 out_spannables
-              )
+              );
+
+              if let Option::Some(color_id) = class_color_id {
+                let text_length_after = out_text_parts.iter().sum_by(&|text_part| text_part.characters_count as i32) as usize;
+
+                let class_spannable = Spannable {
+                  start: text_length_before,
+                  len: text_length_after - text_length_before,
+                  spannable_data: SpannableData::TextForegroundColorId { color_id }
+                };
+
+                if class_spannable.is_valid() {
+                  out_spannables.push(class_spannable);
+                }
+              }
             }
           },
         }
       }
     }
 
+    /// Splits [text] by plain-text quotes (">>123", ">>>/g/123", ">>>/g/"), the quotes are added as links
+    /// and the rest of the text is processed as usual
+    fn process_text_with_quotes(
+      &self,
+      post_raw: &PostRaw,
+      text: String,
+      out_text_parts: &mut Vec<TextPart>,
+      out_spannables: &mut Vec<Spannable>
+    ) {
+      let plain_text_quotes = find_plain_text_quotes(post_raw, self.post_parser_context, &text);
+      if plain_text_quotes.is_empty() {
+        self.push_text_with_links(post_raw, text, out_text_parts, out_spannables);
+        return;
+      }
+
+      let mut offset: usize = 0;
+
+      for plain_text_quote in plain_text_quotes {
+        if plain_text_quote.start > offset {
+          let text_before = String::from(&text[offset..plain_text_quote.start]);
+          self.push_text_with_links(post_raw, text_before, out_text_parts, out_spannables);
+        }
+
+        handle_post_link(
+          post_raw,
+          self.post_parser_context,
+          out_text_parts,
+          out_spannables,
+          plain_text_quote.post_link,
+          String::from(&text[plain_text_quote.start..plain_text_quote.end])
+        );
+
+        offset = plain_text_quote.end;
+      }
+
+      if offset < text.len() {
+        let text_after = String::from(&text[offset..]);
+        self.push_text_with_links(post_raw, text_after, out_text_parts, out_spannables);
+      }
+    }
+
+    /// Adds [text] and the spannables of its links. Urls of the known sites (see [PostParser::resolve_site_url])
+    /// are added with [handle_post_link] so they get the same suffixes (" (OP)", " (DEAD)", " →" etc) as the
+    /// links of the <a> tags.
+    fn push_text_with_links(
+      &self,
+      post_raw: &PostRaw,
+      text: String,
+      out_text_parts: &mut Vec<TextPart>,
+      out_spannables: &mut Vec<Spannable>
+    ) {
+      let mut offset: usize = 0;
+
+      for url_match in self.url_scanner.find_urls(&text) {
+        let post_link = match self.resolve_site_url(post_raw, &url_match.url) {
+          Some(post_link) => post_link,
+          None => continue
+        };
+
+        if url_match.start > offset {
+          let text_before = String::from(&text[offset..url_match.start]);
+          self.detect_links(out_text_parts, &text_before, out_spannables);
+          out_text_parts.push(TextPart::new(text_before));
+        }
+
+        handle_post_link(
+          post_raw,
+          self.post_parser_context,
+          out_text_parts,
+          out_spannables,
+          post_link,
+          String::from(&text[url_match.start..url_match.end])
+        );
+
+        offset = url_match.end;
+      }
+
+      if offset == 0 || offset < text.len() {
+        let text_after = String::from(&text[offset..]);
+        self.detect_links(out_text_parts, &text_after, out_spannables);
+        out_text_parts.push(TextPart::new(text_after));
+      }
+    }
+
+    /// The <a> tag handlers add the links they can't resolve as [PostLink::UrlLink]. When such a link is an
+    /// url of a known site its text is added again with [handle_post_link] to get the suffixes.
+    fn resolve_anchor_site_link(
+      &self,
+      post_raw: &PostRaw,
+      element: &Element,
+      first_text_part_index: usize,
+      first_spannable_index: usize,
+      out_text_parts: &mut Vec<TextPart>,
+      out_spannables: &mut Vec<Spannable>
+    ) {
+      if element.tag_name != "a"
+        || out_text_parts.len() != first_text_part_index + 1
+        || out_spannables.len() != first_spannable_index + 1 {
+        return;
+      }
+
+      let post_link_maybe = match &out_spannables[first_spannable_index].spannable_data {
+        SpannableData::Link(PostLink::UrlLink { link, .. }) => self.resolve_site_url(post_raw, link),
+        _ => Option::None
+      };
+
+      let post_link = match post_link_maybe {
+        Some(post_link) => post_link,
+        None => return
+      };
+
+      out_spannables.pop();
+      let link_text_part = out_text_parts.pop().unwrap();
+
+      handle_post_link(
+        post_raw,
+        self.post_parser_context,
+        out_text_parts,
+        out_spannables,
+        post_link,
+        link_text_part.text
+      );
+    }
+
+    /// Gives [PostLink::UrlLink]s (found by [detect_links] or by the <a> tag handlers) their [UrlKind],
+    /// [embeds] (see [strip_extension_markup]) go first. "mailto:" links become [PostLink::EmailLink]. Urls
+    /// of the known sites are already internal links at this point (see [push_text_with_links]).
+    fn resolve_url_links(
+      &self,
+      embeds: &HashMap<String, UrlKind>,
+      out_spannables: &mut [Spannable]
+    ) {
+      for spannable in out_spannables.iter_mut() {
+        if let SpannableData::Link(PostLink::UrlLink { link, url_kind }) = &spannable.spannable_data {
+          if let Option::Some(email) = parse_mailto(link) {
+            spannable.spannable_data = SpannableData::Link(PostLink::EmailLink { email });
+            continue;
+          }
+
+          // The resolvers of the <a> tags do not classify the urls
+          if *url_kind != UrlKind::Generic {
+            continue;
+          }
+
+          let url_kind = embeds.get(link)
+            .cloned()
+            .unwrap_or_else(|| self.url_classifier_registry.classify(link));
+
+          spannable.spannable_data = SpannableData::Link(PostLink::UrlLink { link: link.clone(), url_kind });
+        }
+      }
+    }
+
+    /// Converts an absolute url of a known site into an internal link. Links to the site of [post_raw]
+    /// become [PostLink::ThreadLink]/[PostLink::Quote]/etc, links to other sites become
+    /// [PostLink::CrossSiteThreadLink]/[PostLink::CrossSiteBoardLink]/[PostLink::CrossSiteSearchLink]. The
+    /// site is found by the host of the url.
+    fn resolve_site_url(&self, post_raw: &PostRaw, link: &str) -> Option<PostLink> {
+      let (host, path) = split_absolute_url(link)?;
+      let site_name = self.site_hosts.get(&host)?;
+      let comment_parser = self.comment_parsers.get(site_name)?;
+
+      if site_name == post_raw.site_name() {
+        let post_link_result = comment_parser.post_link_resolver.link_raw_to_post_link(
+          post_raw,
+          self.post_parser_context,
+          &path
+        );
+
+        return match post_link_result {
+          Ok(PostLink::UrlLink { .. }) | Err(_) => Option::None,
+          Ok(post_link) => Option::Some(post_link)
+        };
+      }
+
+      // A link to another site can't be a quote so it's resolved as if it was in a post that belongs to no
+      // thread
+      let other_site_post_raw = PostRaw::new(site_name, "", 0, 0, 0, "");
+      let post_link_result = comment_parser.post_link_resolver.link_raw_to_post_link(
+        &other_site_post_raw,
+        self.post_parser_context,
+        &path
+      );
+
+      return match post_link_result {
+        Ok(PostLink::ThreadLink { board_code, thread_no, post_no }) => {
+          let post_descriptor = PostDescriptor::new(site_name, &board_code, thread_no, post_no, 0);
+          Option::Some(PostLink::CrossSiteThreadLink { post_descriptor })
+        }
+        Ok(PostLink::BoardLink { board_code }) => {
+          let board_descriptor = BoardDescriptor::new(site_name, &board_code);
+          Option::Some(PostLink::CrossSiteBoardLink { board_descriptor })
+        }
+        Ok(PostLink::SearchLink { board_code, search_query }) => {
+          let board_descriptor = BoardDescriptor::new(site_name, &board_code);
+          Option::Some(PostLink::CrossSiteSearchLink { board_descriptor, search_query })
+        }
+        // Quotes can't point to another site and cross-site links are not something the resolvers return
+        Ok(_) | Err(_) => Option::None
+      };
+    }
+
     fn iterate_element(
       &self,
       nodes: &Vec<Node>,
@@ -201,66 +594,80 @@ pub mod post_parser {
       for node in nodes {
         match node {
           Node::Text(_) => {
-            iterator(&node, post_comment);
+            iterator(node, post_comment);
           },
           Node::Element(element) => {
-            iterator(&node, post_comment);
+            iterator(node, post_comment);
 
             if !element.children.is_empty() {
-              self.iterate_element(&element.children, &post_comment, &iterator);
+              self.iterate_element(&element.children, post_comment, &iterator);
             }
           },
         }
       }
     }
 
-    pub fn detect_links(&self, out_text_parts: &mut Vec<TextPart>, text: &String, out_spannables: &mut Vec<Spannable>) {
-      let mut capture_locations = LINK_PATTERN.capture_locations();
-      let mut offset: usize = 0;
-
+    pub fn detect_links(&self, out_text_parts: &[TextPart], text: &str, out_spannables: &mut Vec<Spannable>) {
       let total_text_chars_count = out_text_parts
         .iter()
         .sum_by(&|string| string.characters_count as i32) as usize;
 
-      let text_bytes = text.as_bytes();
-
-      loop {
-        LINK_PATTERN.captures_read_at(&mut capture_locations, text, offset);
-
-        let capture_maybe = capture_locations.get(0);
-        if capture_maybe.is_none() {
-          break;
-        }
-
-        let (bytes_capture_start, bytes_capture_end) = capture_maybe.unwrap();
-        if bytes_capture_start >= bytes_capture_end || bytes_capture_end <= 0 {
-          break;
-        }
-
-        let actual_link = String::from_utf8_lossy(&text_bytes[bytes_capture_start..bytes_capture_end]);
-
-        // [bytes_capture_start] and [bytes_capture_end] do not account unicode characters being
-        // present in [text]. If there are then they will be incorrect. To fix that we need to count
-        // the amount of unicode characters between [0..bytes_capture_start] and
-        // [bytes_capture_start..bytes_capture_end]. This is slow and uses memory but I have no idea how to do it
-        // correctly.
+      let url_matches = self.url_scanner.find_urls(text);
 
-        let start = String::from_utf8_lossy(&text.as_bytes()[0..bytes_capture_start]).chars().count();
-        let len = String::from_utf8_lossy(&text.as_bytes()[bytes_capture_start..bytes_capture_end]).chars().count();
+      for url_match in &url_matches {
+        // [url_match.start] and [url_match.end] are in bytes but spannables use unicode characters
+        let start = text[..url_match.start].chars().count();
+        let len = text[url_match.start..url_match.end].chars().count();
 
         let link_spannable = Spannable {
           start: total_text_chars_count + start,
           len,
-          spannable_data: SpannableData::Link(PostLink::UrlLink { link: actual_link.to_string() })
+          spannable_data: SpannableData::Link(PostLink::UrlLink {
+            url_kind: self.url_classifier_registry.classify(&url_match.url),
+            link: url_match.url.clone()
+          })
         };
 
         if link_spannable.is_valid() {
           out_spannables.push(link_spannable);
         }
+      }
+
+      for email_match in find_emails(text, &url_matches) {
+        let email_spannable = Spannable {
+          start: total_text_chars_count + text[..email_match.start].chars().count(),
+          len: text[email_match.start..email_match.end].chars().count(),
+          spannable_data: SpannableData::Link(PostLink::EmailLink { email: email_match.email })
+        };
+
+        if email_spannable.is_valid() {
+          out_spannables.push(email_spannable);
+        }
+      }
+
+      detect_magnets_and_hashes(text, &url_matches, total_text_chars_count, out_spannables);
+    }
+
+
+  }
 
-        offset = bytes_capture_end;
+  /// Blocks put the text after them on a new line, the line break is not needed when the post ends with a
+  /// block. Spannables of elements that contain the block are cut at the new end of the text.
+  fn remove_trailing_block_line_breaks(out_text_parts: &mut Vec<TextPart>, out_spannables: &mut Vec<Spannable>) {
+    while let Option::Some(last_text_part) = out_text_parts.last() {
+      if !last_text_part.is_block_line_break && !last_text_part.text.is_empty() {
+        break;
       }
+
+      out_text_parts.pop();
+    }
+
+    let total_size = out_text_parts.iter().sum_by(&|text_part| text_part.characters_count as i32) as usize;
+
+    for spannable in out_spannables.iter_mut() {
+      spannable.len = spannable.len.min(total_size.saturating_sub(spannable.start));
     }
 
+    out_spannables.retain(|spannable| spannable.is_valid());
   }
 }
\ No newline at end of file
@@ -0,0 +1,611 @@
+use std::collections::HashMap;
+use crate::{ParsedPost, ParsedSpannableText, PostDescriptor, Spannable, SpannableData, PostLink, ChanThemeColorId};
+use crate::renderers::span_events::{collect_span_events, SpanEvent};
+use crate::renderers::site_url_templates::SiteUrlTemplates;
+use crate::util::color_decoder::{is_color_hex, parse_css_color};
+
+const CSI: &str = "\x1b[";
+const RESET: &str = "\x1b[0m";
+const OSC_8_START: &str = "\x1b]8;;";
+const ST: &str = "\x1b\\";
+
+const SGR_BOLD: &str = "1";
+const SGR_ITALIC: &str = "3";
+const SGR_UNDERLINE: &str = "4";
+const SGR_REVERSE: &str = "7";
+const SGR_STRIKETHROUGH: &str = "9";
+const SGR_OVERLINE: &str = "53";
+const SGR_RED: &str = "31";
+const SGR_GREEN: &str = "32";
+const SGR_YELLOW: &str = "33";
+const SGR_BLUE: &str = "34";
+const SGR_BLACK: &str = "30";
+const SGR_YELLOW_BACKGROUND: &str = "43";
+
+// The standard 16 terminal colors (xterm defaults) with their foreground SGR codes. Background codes
+// are the same + 10.
+const ANSI_16_COLORS: [(u8, u8, u8, u8); 16] = [
+  (0, 0, 0, 30),
+  (205, 0, 0, 31),
+  (0, 205, 0, 32),
+  (205, 205, 0, 33),
+  (0, 0, 238, 34),
+  (205, 0, 205, 35),
+  (0, 205, 205, 36),
+  (229, 229, 229, 37),
+  (127, 127, 127, 90),
+  (255, 0, 0, 91),
+  (0, 255, 0, 92),
+  (255, 255, 0, 93),
+  (92, 92, 255, 94),
+  (255, 0, 255, 95),
+  (0, 255, 255, 96),
+  (255, 255, 255, 97),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnsiColorMode {
+  // 38;2;r;g;b
+  TrueColor,
+  // 38;5;n
+  Color256,
+  // 30-37, 90-97
+  Color16,
+  // Plain text without any escape sequences
+  NoColor
+}
+
+/// Converts [ParsedSpannableText] into text with ANSI SGR escape sequences (and OSC 8 hyperlinks) for
+/// terminals. Every line (including the ones produced by word wrapping) starts and ends with balanced
+/// escape sequences so it can be printed on its own.
+pub struct AnsiRenderer {
+  site_url_templates: HashMap<String, SiteUrlTemplates>,
+  color_mode: AnsiColorMode,
+  // Max amount of unicode characters (not u8!) per line, None disables word wrapping
+  wrap_width: Option<usize>
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct AnsiStyle {
+  bold: bool,
+  italic: bool,
+  underline: bool,
+  overline: bool,
+  strikethrough: bool,
+  reverse: bool,
+  foreground: Option<String>,
+  background: Option<String>,
+  link: Option<String>
+}
+
+impl AnsiRenderer {
+  pub fn new(color_mode: AnsiColorMode, wrap_width: Option<usize>) -> AnsiRenderer {
+    let mut ansi_renderer = AnsiRenderer {
+      site_url_templates: HashMap::new(),
+      color_mode,
+      wrap_width: wrap_width.filter(|width| *width > 0)
+    };
+
+    ansi_renderer.add_site_url_templates("4chan", SiteUrlTemplates::four_chan());
+    return ansi_renderer;
+  }
+
+  /// Adds (or replaces) url templates that are used to render links of posts from [site_name]
+  pub fn add_site_url_templates(&mut self, site_name: &str, site_url_templates: SiteUrlTemplates) {
+    self.site_url_templates.insert(site_name.to_string(), site_url_templates);
+  }
+
+  pub fn render_post(&self, parsed_post: &ParsedPost) -> String {
+    return self.render(&parsed_post.post_descriptor, &parsed_post.post_comment_parsed);
+  }
+
+  pub fn render(&self, post_descriptor: &PostDescriptor, parsed_spannable_text: &ParsedSpannableText) -> String {
+    let span_events = collect_span_events(
+      &parsed_spannable_text.parsed_text,
+      &parsed_spannable_text.spannables,
+      &|_| self.color_mode != AnsiColorMode::NoColor
+    );
+
+    let mut ansi_writer = AnsiWriter::new(self.wrap_width);
+    let mut open_spannables: Vec<&Spannable> = Vec::with_capacity(8);
+
+    for span_event in span_events {
+      match span_event {
+        SpanEvent::Open(spannable) => {
+          open_spannables.push(spannable);
+        }
+        SpanEvent::Close(_) => {
+          open_spannables.pop();
+        }
+        SpanEvent::Text(text) => {
+          let style = self.compute_style(post_descriptor, &open_spannables);
+          ansi_writer.write(&style, text);
+        }
+      }
+    }
+
+    return ansi_writer.finish();
+  }
+
+  fn compute_style(&self, post_descriptor: &PostDescriptor, open_spannables: &[&Spannable]) -> AnsiStyle {
+    let mut style = AnsiStyle::default();
+
+    // Inner spannables go last so they override the colors of the outer ones
+    for spannable in open_spannables {
+      match &spannable.spannable_data {
+        SpannableData::Link(post_link) => {
+          style.underline = true;
+          style.link = self.format_link_url(post_descriptor, post_link);
+        }
+        SpannableData::Spoiler => {
+          style.reverse = true;
+        }
+        SpannableData::GreenText => {
+          style.foreground = Option::Some(String::from(SGR_GREEN));
+        }
+        SpannableData::RedText => {
+          style.foreground = Option::Some(String::from(SGR_RED));
+        }
+        SpannableData::Heading { .. } => {
+          style.bold = true;
+          style.foreground = Option::Some(String::from(SGR_RED));
+        }
+        SpannableData::OrangeText => {
+          // The closest one of the basic 16 colors
+          style.foreground = Option::Some(String::from(SGR_YELLOW));
+        }
+        SpannableData::BoldText => {
+          style.bold = true;
+        }
+        SpannableData::ItalicText => {
+          style.italic = true;
+        }
+        SpannableData::UnderlineText => {
+          style.underline = true;
+        }
+        SpannableData::OverlineText => {
+          style.overline = true;
+        }
+        SpannableData::StrikethroughText => {
+          style.strikethrough = true;
+        }
+        SpannableData::FontWeight { weight } => {
+          if *weight >= 600 {
+            style.bold = true;
+          }
+        }
+        SpannableData::TextForegroundColorRaw { color_hex } => {
+          if let Option::Some(color) = self.color_sgr(color_hex, false) {
+            style.foreground = Option::Some(color);
+          }
+        }
+        SpannableData::TextBackgroundColorRaw { color_hex } => {
+          if let Option::Some(color) = self.color_sgr(color_hex, true) {
+            style.background = Option::Some(color);
+          }
+        }
+        SpannableData::TextForegroundColorId { color_id } => {
+          if let Option::Some(color) = theme_color_sgr(color_id) {
+            style.foreground = Option::Some(String::from(color));
+          }
+        }
+        SpannableData::SearchHighlight => {
+          style.foreground = Option::Some(String::from(SGR_BLACK));
+          style.background = Option::Some(String::from(SGR_YELLOW_BACKGROUND));
+        }
+        SpannableData::SuperscriptText |
+        SpannableData::SubscriptText |
+        SpannableData::FontSize { .. } |
+        SpannableData::Monospace |
+        SpannableData::AsciiArt |
+        SpannableData::Math { .. } |
+        SpannableData::TextBackgroundColorId { .. } |
+        SpannableData::ThemeJson { .. } |
+        SpannableData::Magnet { .. } |
+        SpannableData::Hash { .. } |
+        SpannableData::ListItem |
+        SpannableData::BlockQuote |
+        SpannableData::HorizontalRule => {
+          // Terminals have no way to show these
+        }
+      }
+    }
+
+    return style;
+  }
+
+  fn format_link_url(&self, post_descriptor: &PostDescriptor, post_link: &PostLink) -> Option<String> {
+    match post_link {
+      PostLink::UrlLink { link, .. } => return Option::Some(link.to_string()),
+      PostLink::EmailLink { email } => return Option::Some(format!("mailto:{}", email)),
+      _ => {}
+    }
+
+    let site_url_templates = self.site_url_templates.get(SiteUrlTemplates::link_site_name(post_descriptor, post_link))?;
+    let url = site_url_templates.format_post_link(post_descriptor, post_link);
+
+    // In-page anchors ("#p123") mean nothing to a terminal
+    if url.starts_with('#') {
+      return Option::None;
+    }
+
+    return Option::Some(url);
+  }
+
+  fn color_sgr(&self, color_hex: &str, is_background: bool) -> Option<String> {
+    let (red, green, blue) = parse_color_hex(color_hex)?;
+
+    let sgr = match self.color_mode {
+      AnsiColorMode::TrueColor => {
+        let prefix = if is_background { "48" } else { "38" };
+        format!("{};2;{};{};{}", prefix, red, green, blue)
+      }
+      AnsiColorMode::Color256 => {
+        let prefix = if is_background { "48" } else { "38" };
+        format!("{};5;{}", prefix, rgb_to_ansi_256(red, green, blue))
+      }
+      AnsiColorMode::Color16 => {
+        let code = rgb_to_ansi_16(red, green, blue);
+        let code = if is_background { code + 10 } else { code };
+        code.to_string()
+      }
+      AnsiColorMode::NoColor => {
+        return Option::None;
+      }
+    };
+
+    return Option::Some(sgr);
+  }
+}
+
+impl AnsiStyle {
+  fn sgr(&self) -> String {
+    let mut params: Vec<&str> = Vec::with_capacity(8);
+
+    if self.bold {
+      params.push(SGR_BOLD);
+    }
+
+    if self.italic {
+      params.push(SGR_ITALIC);
+    }
+
+    if self.underline {
+      params.push(SGR_UNDERLINE);
+    }
+
+    if self.reverse {
+      params.push(SGR_REVERSE);
+    }
+
+    if self.strikethrough {
+      params.push(SGR_STRIKETHROUGH);
+    }
+
+    if self.overline {
+      params.push(SGR_OVERLINE);
+    }
+
+    if let Option::Some(foreground) = &self.foreground {
+      params.push(foreground);
+    }
+
+    if let Option::Some(background) = &self.background {
+      params.push(background);
+    }
+
+    if params.is_empty() {
+      return String::new();
+    }
+
+    return format!("{}{}m", CSI, params.join(";"));
+  }
+}
+
+struct AnsiWriter {
+  output: String,
+  wrap_width: Option<usize>,
+  column: usize,
+  // What is currently applied to the terminal
+  current_style: AnsiStyle,
+  // When wrapping is enabled whitespaces are only written once we know that the line continues after
+  // them, otherwise they are dropped
+  pending_whitespaces: Vec<(AnsiStyle, String)>,
+  pending_whitespaces_width: usize,
+  // The word that is being written when wrapping is enabled. A word may change its style in the middle
+  // ("bbbbbb<i>cc</i>dd") so it's kept as style runs until a whitespace or a line break ends it.
+  pending_word: Vec<(AnsiStyle, String)>,
+  pending_word_width: usize
+}
+
+impl AnsiWriter {
+  fn new(wrap_width: Option<usize>) -> AnsiWriter {
+    return AnsiWriter {
+      output: String::with_capacity(64),
+      wrap_width,
+      column: 0,
+      current_style: AnsiStyle::default(),
+      pending_whitespaces: Vec::new(),
+      pending_whitespaces_width: 0,
+      pending_word: Vec::new(),
+      pending_word_width: 0
+    };
+  }
+
+  fn write(&mut self, style: &AnsiStyle, text: &str) {
+    let text = replace_control_chars(text);
+
+    for (index, line) in text.split('\n').enumerate() {
+      if index > 0 {
+        self.flush_pending_word();
+        self.line_break();
+      }
+
+      for word in split_into_words(line) {
+        self.write_word(style, word);
+      }
+    }
+  }
+
+  fn write_word(&mut self, style: &AnsiStyle, word: &str) {
+    if self.wrap_width.is_none() {
+      self.write_styled(style, word);
+      return;
+    }
+
+    let word_width = word.chars().count();
+    let is_whitespace = word.chars().all(char::is_whitespace);
+
+    if is_whitespace {
+      self.flush_pending_word();
+
+      self.pending_whitespaces.push((style.clone(), word.to_string()));
+      self.pending_whitespaces_width += word_width;
+      return;
+    }
+
+    match self.pending_word.last_mut() {
+      Some((last_style, last_text)) if last_style == style => last_text.push_str(word),
+      _ => self.pending_word.push((style.clone(), word.to_string()))
+    }
+
+    self.pending_word_width += word_width;
+  }
+
+  fn flush_pending_word(&mut self) {
+    if self.pending_word.is_empty() {
+      return;
+    }
+
+    let wrap_width = match self.wrap_width {
+      None => return,
+      Some(wrap_width) => wrap_width
+    };
+
+    let pending_word = std::mem::take(&mut self.pending_word);
+    let word_width = self.pending_word_width;
+    self.pending_word_width = 0;
+
+    if self.column + self.pending_whitespaces_width + word_width <= wrap_width {
+      self.flush_pending_whitespaces();
+
+      for (style, text) in pending_word {
+        self.write_styled(&style, &text);
+      }
+
+      return;
+    }
+
+    if self.column > 0 {
+      self.line_break();
+    }
+
+    self.pending_whitespaces.clear();
+    self.pending_whitespaces_width = 0;
+
+    // Words that are longer than the whole line are split by characters
+    for (style, text) in pending_word {
+      let mut rest = text.as_str();
+
+      while !rest.is_empty() {
+        if self.column >= wrap_width {
+          self.line_break();
+        }
+
+        let available_width = wrap_width - self.column;
+        let split_index = rest.char_indices()
+          .nth(available_width)
+          .map(|(index, _)| index)
+          .unwrap_or(rest.len());
+
+        self.write_styled(&style, &rest[..split_index]);
+        rest = &rest[split_index..];
+      }
+    }
+  }
+
+  fn flush_pending_whitespaces(&mut self) {
+    let pending_whitespaces = std::mem::take(&mut self.pending_whitespaces);
+    self.pending_whitespaces_width = 0;
+
+    for (style, whitespaces) in pending_whitespaces {
+      self.write_styled(&style, &whitespaces);
+    }
+  }
+
+  fn write_styled(&mut self, style: &AnsiStyle, text: &str) {
+    if text.is_empty() {
+      return;
+    }
+
+    self.apply_style(style);
+    self.output.push_str(text);
+    self.column += text.chars().count();
+  }
+
+  fn apply_style(&mut self, style: &AnsiStyle) {
+    if self.current_style.link != style.link {
+      if self.current_style.link.is_some() {
+        self.output.push_str(OSC_8_START);
+        self.output.push_str(ST);
+      }
+
+      if let Option::Some(link) = &style.link {
+        self.output.push_str(OSC_8_START);
+        self.output.push_str(&strip_control_chars(link));
+        self.output.push_str(ST);
+      }
+    }
+
+    let current_sgr = self.current_style.sgr();
+    let new_sgr = style.sgr();
+
+    if current_sgr != new_sgr {
+      if !current_sgr.is_empty() {
+        self.output.push_str(RESET);
+      }
+
+      self.output.push_str(&new_sgr);
+    }
+
+    self.current_style = style.clone();
+  }
+
+  fn line_break(&mut self) {
+    self.pending_whitespaces.clear();
+    self.pending_whitespaces_width = 0;
+
+    self.apply_style(&AnsiStyle::default());
+    self.output.push('\n');
+    self.column = 0;
+  }
+
+  fn finish(mut self) -> String {
+    self.flush_pending_word();
+    self.flush_pending_whitespaces();
+    self.apply_style(&AnsiStyle::default());
+    return self.output;
+  }
+}
+
+/// Post text is untrusted, an ESC/BEL/C1 character in it would let it inject its own escape sequences.
+/// Line breaks and tabs are kept, the rest of the control characters are shown as U+FFFD.
+fn replace_control_chars(text: &str) -> String {
+  return text.chars()
+    .map(|ch| if ch.is_control() && ch != '\n' && ch != '\t' { '\u{FFFD}' } else { ch })
+    .collect();
+}
+
+/// Same as [replace_control_chars] but for urls of OSC 8 hyperlinks, nothing is kept there
+fn strip_control_chars(url: &str) -> String {
+  return url.chars().filter(|ch| !ch.is_control()).collect();
+}
+
+/// Splits text into alternating runs of whitespace and non-whitespace characters
+fn split_into_words(text: &str) -> Vec<&str> {
+  let mut words: Vec<&str> = Vec::with_capacity(16);
+  let mut word_start = 0;
+  let mut prev_is_whitespace: Option<bool> = Option::None;
+
+  for (index, ch) in text.char_indices() {
+    let is_whitespace = ch.is_whitespace();
+
+    if prev_is_whitespace.is_some() && prev_is_whitespace != Option::Some(is_whitespace) {
+      words.push(&text[word_start..index]);
+      word_start = index;
+    }
+
+    prev_is_whitespace = Option::Some(is_whitespace);
+  }
+
+  if word_start < text.len() {
+    words.push(&text[word_start..]);
+  }
+
+  return words;
+}
+
+/// The terminal colors that are the closest to the colors of the default themes, the other theme colors
+/// keep the default terminal color
+fn theme_color_sgr(color_id: &ChanThemeColorId) -> Option<&'static str> {
+  return match color_id {
+    ChanThemeColorId::PostInlineQuoteColor => Option::Some(SGR_GREEN),
+    ChanThemeColorId::PostNameColor => Option::Some(SGR_GREEN),
+    ChanThemeColorId::PostLinkColor => Option::Some(SGR_BLUE),
+    ChanThemeColorId::PostSubjectColor => Option::Some(SGR_BLUE),
+    ChanThemeColorId::AccentColor |
+    ChanThemeColorId::PostQuoteColor |
+    ChanThemeColorId::BackColorSecondary |
+    ChanThemeColorId::TextColorPrimary => Option::None
+  };
+}
+
+/// Fully transparent colors are not shown, the alpha of the rest is ignored
+fn parse_color_hex(color_hex: &str) -> Option<(u8, u8, u8)> {
+  if !is_color_hex(color_hex) {
+    return Option::None;
+  }
+
+  let color = parse_css_color(color_hex).ok()?;
+  if color.alpha == 0 {
+    return Option::None;
+  }
+
+  return Option::Some((color.red, color.green, color.blue));
+}
+
+fn rgb_to_ansi_256(red: u8, green: u8, blue: u8) -> u8 {
+  // Grays have their own (more precise) ramp: 232..=255 where 232 + n is 8 + n * 10
+  if red == green && green == blue {
+    if red < 8 {
+      return 16;
+    }
+
+    if red > 248 {
+      return 231;
+    }
+
+    return 232 + ((red - 8 + 5) / 10).min(23);
+  }
+
+  let to_cube = |value: u8| -> u8 { ((value as u16 * 5 + 127) / 255) as u8 };
+  return 16 + 36 * to_cube(red) + 6 * to_cube(green) + to_cube(blue);
+}
+
+fn rgb_to_ansi_16(red: u8, green: u8, blue: u8) -> u8 {
+  let distance = |(r, g, b, _): &(u8, u8, u8, u8)| -> i32 {
+    let dr = *r as i32 - red as i32;
+    let dg = *g as i32 - green as i32;
+    let db = *b as i32 - blue as i32;
+
+    return dr * dr + dg * dg + db * db;
+  };
+
+  return ANSI_16_COLORS.iter().min_by_key(|color| distance(color)).unwrap().3;
+}
+
+#[test]
+fn test_parse_color_hex() {
+  assert_eq!(Option::Some((0xFD, 0x4D, 0x32)), parse_color_hex("#fd4d32"));
+  assert_eq!(Option::Some((0xFF, 0x00, 0x00)), parse_color_hex("#F00"));
+  assert_eq!(Option::None, parse_color_hex("fd4d32"));
+  assert_eq!(Option::None, parse_color_hex("#fd4d3"));
+  assert_eq!(Option::Some((0xFD, 0x4D, 0x32)), parse_color_hex("#FD4D3280"));
+  assert_eq!(Option::None, parse_color_hex("#FD4D3200"));
+  assert_eq!(Option::None, parse_color_hex("red"));
+}
+
+#[test]
+fn test_color_fallbacks() {
+  assert_eq!(196, rgb_to_ansi_256(255, 0, 0));
+  assert_eq!(16, rgb_to_ansi_256(0, 0, 0));
+  assert_eq!(244, rgb_to_ansi_256(128, 128, 128));
+  assert_eq!(91, rgb_to_ansi_16(255, 0, 0));
+  assert_eq!(32, rgb_to_ansi_16(0x10, 0xC0, 0x10));
+}
+
+#[test]
+fn test_split_into_words() {
+  assert_eq!(vec!["a", "  ", "bc", " "], split_into_words("a  bc "));
+  assert!(split_into_words("").is_empty());
+}
@@ -0,0 +1,303 @@
+use std::collections::HashMap;
+use crate::{ParsedPost, ParsedSpannableText, PostDescriptor, Spannable, SpannableData, PostLink};
+use crate::renderers::span_events::{collect_span_events, SpanEvent};
+use crate::renderers::site_url_templates::SiteUrlTemplates;
+use crate::util::color_decoder::is_color_hex;
+
+const SAFE_URL_PREFIXES: [&str; 9] = ["http://", "https://", "ftp://", "magnet:", "ipfs://", "mailto:", "//", "/", "#"];
+
+/// Converts [ParsedSpannableText] back into minimal html that is safe to be shown in a WebView (all
+/// text is escaped, only a small set of tags is produced and links with unknown schemes are dropped).
+/// Overlapping spannables are split into several elements.
+pub struct HtmlRenderer {
+  site_url_templates: HashMap<String, SiteUrlTemplates>
+}
+
+impl Default for HtmlRenderer {
+  fn default() -> Self {
+    return HtmlRenderer::new();
+  }
+}
+
+impl HtmlRenderer {
+  pub fn new() -> HtmlRenderer {
+    let mut html_renderer = HtmlRenderer {
+      site_url_templates: HashMap::new()
+    };
+
+    html_renderer.add_site_url_templates("4chan", SiteUrlTemplates::four_chan());
+    return html_renderer;
+  }
+
+  /// Adds (or replaces) url templates that are used to render links of posts from [site_name]
+  pub fn add_site_url_templates(&mut self, site_name: &str, site_url_templates: SiteUrlTemplates) {
+    self.site_url_templates.insert(site_name.to_string(), site_url_templates);
+  }
+
+  pub fn render_post(&self, parsed_post: &ParsedPost) -> String {
+    return self.render(&parsed_post.post_descriptor, &parsed_post.post_comment_parsed);
+  }
+
+  pub fn render(&self, post_descriptor: &PostDescriptor, parsed_spannable_text: &ParsedSpannableText) -> String {
+    let span_events = collect_span_events(
+      &parsed_spannable_text.parsed_text,
+      &parsed_spannable_text.spannables,
+      &|spannable| self.open_tag(post_descriptor, &spannable.spannable_data).is_some()
+    );
+
+    let mut result_html = String::with_capacity(parsed_spannable_text.parsed_text.len() * 2);
+    let mut monospace_depth = 0;
+
+    for span_event in span_events {
+      match span_event {
+        SpanEvent::Open(spannable) => {
+          if is_preformatted(&spannable.spannable_data) {
+            monospace_depth += 1;
+          }
+
+          result_html.push_str(&self.open_tag(post_descriptor, &spannable.spannable_data).unwrap());
+        }
+        SpanEvent::Close(spannable) => {
+          if is_preformatted(&spannable.spannable_data) {
+            monospace_depth -= 1;
+          }
+
+          result_html.push_str(close_tag(spannable));
+        }
+        SpanEvent::Text(text) => {
+          let escaped_text = html_escape::encode_text(text);
+
+          if monospace_depth > 0 {
+            result_html.push_str(&escaped_text);
+          } else {
+            result_html.push_str(&escaped_text.replace('\n', "<br>"));
+          }
+        }
+      }
+    }
+
+    return result_html;
+  }
+
+  fn open_tag(&self, post_descriptor: &PostDescriptor, spannable_data: &SpannableData) -> Option<String> {
+    return match spannable_data {
+      SpannableData::Link(post_link) => {
+        self.open_link_tag(post_descriptor, post_link)
+      }
+      SpannableData::Spoiler => {
+        Option::Some(String::from("<s class=\"spoiler\">"))
+      }
+      SpannableData::GreenText => {
+        Option::Some(String::from("<span class=\"greentext\">"))
+      }
+      SpannableData::RedText => {
+        Option::Some(String::from("<span class=\"redtext\">"))
+      }
+      SpannableData::OrangeText => {
+        Option::Some(String::from("<span class=\"orangetext\">"))
+      }
+      SpannableData::Heading { level } => {
+        Option::Some(format!("<span class=\"heading heading{}\">", level))
+      }
+      SpannableData::BoldText => {
+        Option::Some(String::from("<b>"))
+      }
+      SpannableData::ItalicText => {
+        Option::Some(String::from("<i>"))
+      }
+      SpannableData::UnderlineText => {
+        Option::Some(String::from("<u>"))
+      }
+      SpannableData::OverlineText => {
+        Option::Some(String::from("<span style=\"text-decoration:overline\">"))
+      }
+      SpannableData::StrikethroughText => {
+        Option::Some(String::from("<del>"))
+      }
+      SpannableData::SuperscriptText => {
+        Option::Some(String::from("<sup>"))
+      }
+      SpannableData::SubscriptText => {
+        Option::Some(String::from("<sub>"))
+      }
+      SpannableData::Monospace => {
+        Option::Some(String::from("<pre>"))
+      }
+      SpannableData::AsciiArt => {
+        Option::Some(String::from("<pre class=\"aa\">"))
+      }
+      SpannableData::Math { is_display_mode } => {
+        if *is_display_mode {
+          Option::Some(String::from("<span class=\"math math-display\">"))
+        } else {
+          Option::Some(String::from("<span class=\"math\">"))
+        }
+      }
+      SpannableData::FontSize { scale, .. } => {
+        // The original size may be huge, the scale is already clamped
+        style_span("font-size", &format!("{}em", scale))
+      }
+      SpannableData::FontWeight { weight } => {
+        style_span("font-weight", &weight.to_string())
+      }
+      SpannableData::TextForegroundColorRaw { color_hex } => {
+        if !is_color_hex(color_hex) {
+          return Option::None;
+        }
+
+        style_span("color", color_hex)
+      }
+      SpannableData::TextBackgroundColorRaw { color_hex } => {
+        if !is_color_hex(color_hex) {
+          return Option::None;
+        }
+
+        style_span("background-color", color_hex)
+      }
+      SpannableData::TextForegroundColorId { color_id } => {
+        Option::Some(format!("<span class=\"theme-fg-{:?}\">", color_id))
+      }
+      SpannableData::TextBackgroundColorId { color_id } => {
+        Option::Some(format!("<span class=\"theme-bg-{:?}\">", color_id))
+      }
+      SpannableData::ThemeJson { .. } |
+      SpannableData::Magnet { .. } => {
+        // Magnets are already rendered as links
+        Option::None
+      }
+      SpannableData::SearchHighlight => {
+        Option::Some(String::from("<mark>"))
+      }
+      SpannableData::Hash { .. } => {
+        Option::Some(String::from("<span class=\"hash\">"))
+      }
+      SpannableData::ListItem => {
+        Option::Some(String::from("<span class=\"list-item\">"))
+      }
+      SpannableData::BlockQuote => {
+        Option::Some(String::from("<blockquote>"))
+      }
+      SpannableData::HorizontalRule => {
+        Option::Some(String::from("<span class=\"hr\">"))
+      }
+    }
+  }
+
+  fn open_link_tag(&self, post_descriptor: &PostDescriptor, post_link: &PostLink) -> Option<String> {
+    let url = match post_link {
+      PostLink::UrlLink { link, .. } => link.to_string(),
+      PostLink::EmailLink { email } => format!("mailto:{}", email),
+      _ => {
+        let site_url_templates = self.site_url_templates.get(SiteUrlTemplates::link_site_name(post_descriptor, post_link))?;
+        site_url_templates.format_post_link(post_descriptor, post_link)
+      }
+    };
+
+    if !is_safe_url(&url) {
+      return Option::None;
+    }
+
+    let href = html_escape::encode_double_quoted_attribute(&url);
+
+    let tag = match post_link {
+      PostLink::Quote { .. } => {
+        format!("<a class=\"quotelink\" href=\"{}\">", href)
+      }
+      PostLink::Dead { .. } => {
+        format!("<a class=\"quotelink deadlink\" href=\"{}\">", href)
+      }
+      PostLink::UrlLink { .. } |
+      PostLink::EmailLink { .. } => {
+        format!("<a rel=\"noreferrer noopener\" href=\"{}\">", href)
+      }
+      PostLink::BoardLink { .. } |
+      PostLink::SearchLink { .. } |
+      PostLink::ThreadLink { .. } |
+      PostLink::CrossSiteThreadLink { .. } |
+      PostLink::CrossSiteBoardLink { .. } |
+      PostLink::CrossSiteSearchLink { .. } => {
+        format!("<a class=\"quotelink\" href=\"{}\">", href)
+      }
+    };
+
+    return Option::Some(tag);
+  }
+}
+
+fn close_tag(spannable: &Spannable) -> &'static str {
+  return match spannable.spannable_data {
+    SpannableData::Link(_) => "</a>",
+    SpannableData::Spoiler => "</s>",
+    SpannableData::BoldText => "</b>",
+    SpannableData::ItalicText => "</i>",
+    SpannableData::UnderlineText => "</u>",
+    SpannableData::StrikethroughText => "</del>",
+    SpannableData::SuperscriptText => "</sup>",
+    SpannableData::SubscriptText => "</sub>",
+    SpannableData::Monospace |
+    SpannableData::AsciiArt => "</pre>",
+    SpannableData::SearchHighlight => "</mark>",
+    SpannableData::BlockQuote => "</blockquote>",
+    SpannableData::GreenText |
+    SpannableData::RedText |
+    SpannableData::OrangeText |
+    SpannableData::Heading { .. } |
+    SpannableData::Math { .. } |
+    SpannableData::OverlineText |
+    SpannableData::FontSize { .. } |
+    SpannableData::FontWeight { .. } |
+    SpannableData::TextForegroundColorRaw { .. } |
+    SpannableData::TextBackgroundColorRaw { .. } |
+    SpannableData::TextForegroundColorId { .. } |
+    SpannableData::TextBackgroundColorId { .. } |
+    SpannableData::ThemeJson { .. } |
+    SpannableData::Magnet { .. } |
+    SpannableData::Hash { .. } |
+    SpannableData::ListItem |
+    SpannableData::HorizontalRule => "</span>"
+  };
+}
+
+fn is_preformatted(spannable_data: &SpannableData) -> bool {
+  return matches!(spannable_data, SpannableData::Monospace | SpannableData::AsciiArt);
+}
+
+fn style_span(property_name: &str, property_value: &str) -> Option<String> {
+  if !is_safe_style_value(property_value) {
+    return Option::None;
+  }
+
+  return Option::Some(format!("<span style=\"{}:{}\">", property_name, property_value));
+}
+
+fn is_safe_style_value(style_value: &str) -> bool {
+  if style_value.is_empty() {
+    return false;
+  }
+
+  return style_value.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '#' || ch == '.' || ch == '%' || ch == '-');
+}
+
+fn is_safe_url(url: &str) -> bool {
+  let url = url.trim().to_lowercase();
+
+  return SAFE_URL_PREFIXES.iter().any(|prefix| url.starts_with(prefix));
+}
+
+#[test]
+fn test_is_safe_url() {
+  assert!(is_safe_url("https://boards.4chan.org/g/"));
+  assert!(is_safe_url("#p123"));
+  assert!(is_safe_url("mailto:user@example.com"));
+  assert!(!is_safe_url("javascript:alert(1)"));
+  assert!(!is_safe_url(" JavaScript:alert(1)"));
+  assert!(!is_safe_url("data:text/html,test"));
+}
+
+#[test]
+fn test_is_safe_style_value() {
+  assert!(is_safe_style_value("150%"));
+  assert!(is_safe_style_value("#fd4d32"));
+  assert!(!is_safe_style_value("red;background:url(x)"));
+  assert!(!is_safe_style_value("\"><script>"));
+}
@@ -0,0 +1,438 @@
+use std::collections::HashMap;
+use crate::{ParsedPost, ParsedSpannableText, PostDescriptor, Spannable, SpannableData, PostLink};
+use crate::renderers::span_events::{collect_span_events, SpanEvent};
+use crate::renderers::site_url_templates::SiteUrlTemplates;
+
+const ESCAPED_CHARACTERS: &str = "\\`*_[]<>#|~!&";
+const ESCAPED_LINE_START_CHARACTERS: &str = "-+=";
+const HARD_LINE_BREAK: &str = "  ";
+const CODE_FENCE_CHAR: char = '`';
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MarkdownSpoilerStyle {
+  // ||text||
+  Pipes,
+  // <details><summary>Spoiler</summary>text</details>
+  HtmlDetails
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum LineKind {
+  Normal,
+  Quote,
+  Code
+}
+
+/// Converts [ParsedSpannableText] into CommonMark. Greentext lines become blockquotes, [SpannableData::Monospace]
+/// and [SpannableData::AsciiArt] become fenced code blocks and every line break of the original post is preserved with a hard line break.
+/// Spannables without a Markdown counterpart (colors, font sizes etc) are dropped.
+pub struct MarkdownRenderer {
+  site_url_templates: HashMap<String, SiteUrlTemplates>,
+  spoiler_style: MarkdownSpoilerStyle
+}
+
+impl MarkdownRenderer {
+  pub fn new(spoiler_style: MarkdownSpoilerStyle) -> MarkdownRenderer {
+    let mut markdown_renderer = MarkdownRenderer {
+      site_url_templates: HashMap::new(),
+      spoiler_style
+    };
+
+    markdown_renderer.add_site_url_templates("4chan", SiteUrlTemplates::four_chan());
+    return markdown_renderer;
+  }
+
+  /// Adds (or replaces) url templates that are used to render links of posts from [site_name]
+  pub fn add_site_url_templates(&mut self, site_name: &str, site_url_templates: SiteUrlTemplates) {
+    self.site_url_templates.insert(site_name.to_string(), site_url_templates);
+  }
+
+  pub fn render_post(&self, parsed_post: &ParsedPost) -> String {
+    return self.render(&parsed_post.post_descriptor, &parsed_post.post_comment_parsed);
+  }
+
+  pub fn render(&self, post_descriptor: &PostDescriptor, parsed_spannable_text: &ParsedSpannableText) -> String {
+    let span_events = collect_span_events(
+      &parsed_spannable_text.parsed_text,
+      &parsed_spannable_text.spannables,
+      &|spannable| self.is_supported(post_descriptor, &spannable.spannable_data)
+    );
+
+    let mut markdown_writer = MarkdownWriter::new(self, post_descriptor);
+
+    for span_event in span_events {
+      match span_event {
+        SpanEvent::Open(spannable) => markdown_writer.open(spannable),
+        SpanEvent::Close(spannable) => markdown_writer.close(spannable),
+        SpanEvent::Text(text) => markdown_writer.text(text)
+      }
+    }
+
+    return markdown_writer.finish();
+  }
+
+  fn is_supported(&self, post_descriptor: &PostDescriptor, spannable_data: &SpannableData) -> bool {
+    return match spannable_data {
+      SpannableData::Link(post_link) => self.format_link_url(post_descriptor, post_link).is_some(),
+      SpannableData::Spoiler |
+      SpannableData::GreenText |
+      SpannableData::BoldText |
+      SpannableData::Heading { .. } |
+      SpannableData::ItalicText |
+      SpannableData::StrikethroughText |
+      SpannableData::Monospace |
+      SpannableData::AsciiArt => true,
+      SpannableData::RedText |
+      SpannableData::OrangeText |
+      SpannableData::UnderlineText |
+      SpannableData::OverlineText |
+      SpannableData::SuperscriptText |
+      SpannableData::SubscriptText |
+      SpannableData::Math { .. } |
+      SpannableData::FontSize { .. } |
+      SpannableData::FontWeight { .. } |
+      SpannableData::TextForegroundColorRaw { .. } |
+      SpannableData::TextBackgroundColorRaw { .. } |
+      SpannableData::TextForegroundColorId { .. } |
+      SpannableData::TextBackgroundColorId { .. } |
+      SpannableData::ThemeJson { .. } |
+      SpannableData::SearchHighlight |
+      SpannableData::Magnet { .. } |
+      SpannableData::Hash { .. } |
+      SpannableData::ListItem |
+      SpannableData::BlockQuote |
+      SpannableData::HorizontalRule => false
+    };
+  }
+
+  fn format_link_url(&self, post_descriptor: &PostDescriptor, post_link: &PostLink) -> Option<String> {
+    let url = match post_link {
+      PostLink::UrlLink { link, .. } => link.to_string(),
+      PostLink::EmailLink { email } => format!("mailto:{}", email),
+      _ => self.site_url_templates.get(SiteUrlTemplates::link_site_name(post_descriptor, post_link))?.format_post_link(post_descriptor, post_link)
+    };
+
+    return Option::Some(escape_link_url(&url));
+  }
+
+  fn open_marker(&self, spannable_data: &SpannableData) -> &'static str {
+    return match spannable_data {
+      SpannableData::Link(_) => "[",
+      SpannableData::BoldText |
+      SpannableData::Heading { .. } => "**",
+      SpannableData::ItalicText => "*",
+      SpannableData::StrikethroughText => "~~",
+      SpannableData::Spoiler => {
+        match self.spoiler_style {
+          MarkdownSpoilerStyle::Pipes => "||",
+          MarkdownSpoilerStyle::HtmlDetails => "<details><summary>Spoiler</summary>"
+        }
+      }
+      _ => ""
+    };
+  }
+
+  fn close_marker(&self, post_descriptor: &PostDescriptor, spannable_data: &SpannableData) -> String {
+    return match spannable_data {
+      SpannableData::Link(post_link) => {
+        format!("]({})", self.format_link_url(post_descriptor, post_link).unwrap_or_default())
+      }
+      SpannableData::BoldText |
+      SpannableData::Heading { .. } => String::from("**"),
+      SpannableData::ItalicText => String::from("*"),
+      SpannableData::StrikethroughText => String::from("~~"),
+      SpannableData::Spoiler => {
+        match self.spoiler_style {
+          MarkdownSpoilerStyle::Pipes => String::from("||"),
+          MarkdownSpoilerStyle::HtmlDetails => String::from("</details>")
+        }
+      }
+      _ => String::new()
+    };
+  }
+}
+
+/// Markdown has no notion of inline spans crossing block boundaries, so the writer works line by line:
+/// every inline marker that is still open at the end of a line is closed there and reopened on the next line.
+struct MarkdownWriter<'a> {
+  markdown_renderer: &'a MarkdownRenderer,
+  post_descriptor: &'a PostDescriptor,
+  output: String,
+  line: String,
+  line_is_greentext: bool,
+  prev_line_kind: Option<LineKind>,
+  // Inline spannables (links, bold, spoilers) that are currently open
+  open_spannables: Vec<&'a Spannable>,
+  // How many of [open_spannables] already had their open markers written into [line]
+  written_markers_count: usize,
+  greentext_depth: usize,
+  code_depth: usize,
+  code: String,
+  // A code block always ends the current line so the line break that follows it must be skipped
+  swallow_line_break: bool
+}
+
+impl<'a> MarkdownWriter<'a> {
+  fn new(markdown_renderer: &'a MarkdownRenderer, post_descriptor: &'a PostDescriptor) -> MarkdownWriter<'a> {
+    return MarkdownWriter {
+      markdown_renderer,
+      post_descriptor,
+      output: String::with_capacity(64),
+      line: String::with_capacity(64),
+      line_is_greentext: false,
+      prev_line_kind: Option::None,
+      open_spannables: Vec::with_capacity(8),
+      written_markers_count: 0,
+      greentext_depth: 0,
+      code_depth: 0,
+      code: String::new(),
+      swallow_line_break: false
+    };
+  }
+
+  fn open(&mut self, spannable: &'a Spannable) {
+    match spannable.spannable_data {
+      SpannableData::Monospace | SpannableData::AsciiArt => {
+        if self.code_depth == 0 {
+          self.start_code_block();
+        }
+
+        self.code_depth += 1;
+      }
+      _ if self.code_depth > 0 => {
+        // Everything inside of a code block is rendered as is
+      }
+      SpannableData::GreenText => {
+        self.greentext_depth += 1;
+      }
+      _ => {
+        self.open_spannables.push(spannable);
+      }
+    }
+  }
+
+  fn close(&mut self, spannable: &'a Spannable) {
+    match spannable.spannable_data {
+      SpannableData::Monospace | SpannableData::AsciiArt => {
+        self.code_depth -= 1;
+
+        if self.code_depth == 0 {
+          self.finish_code_block();
+        }
+      }
+      _ if self.code_depth > 0 => {
+        // Everything inside of a code block is rendered as is
+      }
+      SpannableData::GreenText => {
+        self.greentext_depth -= 1;
+      }
+      _ => {
+        self.open_spannables.pop();
+
+        if self.open_spannables.len() < self.written_markers_count {
+          self.write_close_marker(spannable);
+          self.written_markers_count -= 1;
+        }
+      }
+    }
+  }
+
+  fn text(&mut self, text: &str) {
+    if self.code_depth > 0 {
+      self.code.push_str(text);
+      return;
+    }
+
+    for (index, line_text) in text.split('\n').enumerate() {
+      if index > 0 {
+        if self.swallow_line_break {
+          self.swallow_line_break = false;
+        } else {
+          self.end_line(false);
+        }
+      }
+
+      if !line_text.is_empty() {
+        self.swallow_line_break = false;
+        self.append_to_line(line_text);
+      }
+    }
+  }
+
+  fn finish(mut self) -> String {
+    if !self.line.is_empty() {
+      self.end_line(true);
+    }
+
+    return self.output;
+  }
+
+  fn append_to_line(&mut self, text: &str) {
+    if self.greentext_depth > 0 {
+      self.line_is_greentext = true;
+    }
+
+    let trimmed_text = text.trim_start();
+    if trimmed_text.is_empty() {
+      self.line.push_str(text);
+      return;
+    }
+
+    let at_line_start = self.line.is_empty();
+
+    if self.written_markers_count < self.open_spannables.len() {
+      // Emphasis markers must not be followed by a whitespace so we move the whitespaces before the
+      // markers
+      self.line.push_str(&text[..text.len() - trimmed_text.len()]);
+
+      for spannable in &self.open_spannables[self.written_markers_count..] {
+        self.line.push_str(self.markdown_renderer.open_marker(&spannable.spannable_data));
+      }
+
+      self.written_markers_count = self.open_spannables.len();
+      self.line.push_str(&escape_markdown(trimmed_text, at_line_start));
+      return;
+    }
+
+    self.line.push_str(&escape_markdown(text, at_line_start));
+  }
+
+  fn write_close_marker(&mut self, spannable: &Spannable) {
+    // Emphasis markers must not be preceded by a whitespace so we move the whitespaces after the
+    // markers
+    let trimmed_len = self.line.trim_end().len();
+    let trailing_whitespaces = self.line.split_off(trimmed_len);
+
+    let close_marker = self.markdown_renderer.close_marker(self.post_descriptor, &spannable.spannable_data);
+    self.line.push_str(&close_marker);
+    self.line.push_str(&trailing_whitespaces);
+  }
+
+  fn end_line(&mut self, is_last_line: bool) {
+    let written_spannables = self.open_spannables[..self.written_markers_count].to_vec();
+    for spannable in written_spannables.iter().rev() {
+      self.write_close_marker(spannable);
+    }
+
+    self.written_markers_count = 0;
+
+    let line_kind = if self.line_is_greentext {
+      LineKind::Quote
+    } else {
+      LineKind::Normal
+    };
+
+    if self.prev_line_kind == Option::Some(LineKind::Quote) && line_kind == LineKind::Normal && !self.line.is_empty() {
+      // Otherwise this line would become a lazy continuation of the blockquote
+      self.output.push('\n');
+    }
+
+    if line_kind == LineKind::Quote {
+      self.output.push_str("> ");
+    }
+
+    self.output.push_str(&self.line);
+
+    if !self.line.is_empty() && !is_last_line {
+      self.output.push_str(HARD_LINE_BREAK);
+    }
+
+    if !is_last_line {
+      self.output.push('\n');
+    }
+
+    self.line.clear();
+    self.line_is_greentext = false;
+    self.prev_line_kind = Option::Some(line_kind);
+  }
+
+  fn start_code_block(&mut self) {
+    if !self.line.is_empty() {
+      self.end_line(false);
+    }
+
+    self.code.clear();
+  }
+
+  fn finish_code_block(&mut self) {
+    let code = self.code.trim_start_matches('\n').trim_end_matches('\n').to_string();
+    let fence = code_fence(&code);
+
+    self.output.push_str(&fence);
+    self.output.push('\n');
+    self.output.push_str(&code);
+    self.output.push('\n');
+    self.output.push_str(&fence);
+    self.output.push('\n');
+
+    self.code.clear();
+    self.prev_line_kind = Option::Some(LineKind::Code);
+    self.swallow_line_break = true;
+  }
+}
+
+/// The fence must be longer than any run of backticks inside of the code itself
+fn code_fence(code: &str) -> String {
+  let mut longest_run = 0;
+  let mut current_run = 0;
+
+  for ch in code.chars() {
+    if ch == CODE_FENCE_CHAR {
+      current_run += 1;
+      longest_run = longest_run.max(current_run);
+    } else {
+      current_run = 0;
+    }
+  }
+
+  return CODE_FENCE_CHAR.to_string().repeat((longest_run + 1).max(3));
+}
+
+fn escape_markdown(text: &str, at_line_start: bool) -> String {
+  let mut result = String::with_capacity(text.len() + 8);
+  let mut is_line_start = at_line_start;
+  let mut leading_digits = at_line_start;
+
+  for ch in text.chars() {
+    let needs_escape = ESCAPED_CHARACTERS.contains(ch)
+      || (is_line_start && ESCAPED_LINE_START_CHARACTERS.contains(ch))
+      // "1." or "1)" at the start of a line is an ordered list item
+      || (leading_digits && !is_line_start && (ch == '.' || ch == ')'));
+
+    if needs_escape {
+      result.push('\\');
+    }
+
+    result.push(ch);
+
+    leading_digits = leading_digits && ch.is_ascii_digit();
+    is_line_start = false;
+  }
+
+  return result;
+}
+
+fn escape_link_url(url: &str) -> String {
+  return url
+    .replace(' ', "%20")
+    .replace('(', "%28")
+    .replace(')', "%29")
+    .replace('<', "%3C")
+    .replace('>', "%3E");
+}
+
+#[test]
+fn test_escape_markdown() {
+  assert_eq!("\\>implying", escape_markdown(">implying", true));
+  assert_eq!("\\*not bold\\* \\_nor italic\\_", escape_markdown("*not bold* _nor italic_", false));
+  assert_eq!("\\- not a list - item", escape_markdown("- not a list - item", true));
+  assert_eq!("2021\\. not a list", escape_markdown("2021. not a list", true));
+  assert_eq!("2021. the year", escape_markdown("2021. the year", false));
+  assert_eq!("\\&amp; \\[link\\](url)", escape_markdown("&amp; [link](url)", false));
+}
+
+#[test]
+fn test_code_fence() {
+  assert_eq!("```", code_fence("let a = 1;"));
+  assert_eq!("````", code_fence("```rust"));
+}
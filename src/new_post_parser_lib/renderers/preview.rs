@@ -0,0 +1,288 @@
+use unicode_segmentation::UnicodeSegmentation;
+use crate::{ParsedSpannableText, Spannable, SpannableData, PostLink};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreviewLengthUnit {
+  // Unicode characters (same as Spannable.start/Spannable.len)
+  Characters,
+  // What the user perceives as a single character (e.g. an emoji with a skin tone modifier)
+  GraphemeClusters
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PreviewUrlMode {
+  Keep,
+  Strip,
+  // "https://www.youtube.com/watch?v=57tu8AtKf9E" -> "youtube.com"
+  Shorten
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PreviewOptions {
+  // Max length of the preview (including the ellipsis) in [length_unit]s
+  pub max_length: usize,
+  pub length_unit: PreviewLengthUnit,
+  pub url_mode: PreviewUrlMode,
+  pub spoiler_placeholder: String,
+  pub ellipsis: String
+}
+
+impl PreviewOptions {
+  pub fn new(max_length: usize) -> PreviewOptions {
+    return PreviewOptions {
+      max_length,
+      length_unit: PreviewLengthUnit::GraphemeClusters,
+      url_mode: PreviewUrlMode::Shorten,
+      spoiler_placeholder: String::from("[spoiler]"),
+      ellipsis: String::from("…")
+    };
+  }
+}
+
+struct Replacement {
+  start: usize,
+  end: usize,
+  text: String
+}
+
+impl ParsedSpannableText {
+
+  /// Builds a single line preview of this text (for notifications, bookmarks etc). Whitespaces and line
+  /// breaks are collapsed into single spaces, spoilers are replaced with [PreviewOptions::spoiler_placeholder],
+  /// urls are stripped/shortened and the result is truncated so that it never ends in the middle of a
+  /// post link. Spannables that survive truncation are returned with adjusted offsets.
+  pub fn preview(&self, preview_options: &PreviewOptions) -> ParsedSpannableText {
+    let chars: Vec<char> = self.parsed_text.chars().collect();
+    let replacements = collect_replacements(&chars, &self.spannables, preview_options);
+
+    // new_offsets[index] is the offset in the preview text of the original character at [index]
+    let mut new_offsets: Vec<usize> = Vec::with_capacity(chars.len() + 1);
+    let mut preview_chars: Vec<char> = Vec::with_capacity(chars.len());
+    let mut replacements_iter = replacements.iter().peekable();
+    let mut index = 0;
+
+    while index < chars.len() {
+      if let Option::Some(replacement) = replacements_iter.next_if(|replacement| replacement.start == index) {
+        new_offsets.push(preview_chars.len());
+        push_collapsing_whitespaces(&mut preview_chars, replacement.text.chars());
+
+        while index + 1 < replacement.end {
+          new_offsets.push(preview_chars.len());
+          index += 1;
+        }
+
+        index += 1;
+        continue;
+      }
+
+      new_offsets.push(preview_chars.len());
+      push_collapsing_whitespaces(&mut preview_chars, std::iter::once(chars[index]));
+      index += 1;
+    }
+
+    new_offsets.push(preview_chars.len());
+
+    while preview_chars.last() == Option::Some(&' ') {
+      preview_chars.pop();
+    }
+
+    let mut spannables: Vec<Spannable> = Vec::with_capacity(self.spannables.len());
+
+    for spannable in self.spannables.iter() {
+      let start = new_offsets[spannable.start.min(chars.len())].min(preview_chars.len());
+      let end = new_offsets[(spannable.start + spannable.len).min(chars.len())].min(preview_chars.len());
+
+      let new_spannable = Spannable {
+        start,
+        len: end.saturating_sub(start),
+        spannable_data: spannable.spannable_data.clone()
+      };
+
+      if new_spannable.is_valid() {
+        spannables.push(new_spannable);
+      }
+    }
+
+    let preview_text: String = preview_chars.iter().collect();
+    let (preview_text, spannables) = truncate(preview_text, spannables, preview_options);
+
+    return ParsedSpannableText::new(
+      &self.original_text,
+      Box::new(preview_text),
+      Box::new(spannables)
+    );
+  }
+
+}
+
+fn collect_replacements(
+  chars: &[char],
+  spannables: &[Spannable],
+  preview_options: &PreviewOptions
+) -> Vec<Replacement> {
+  let mut replacements: Vec<Replacement> = Vec::with_capacity(4);
+
+  for spannable in spannables {
+    let start = spannable.start.min(chars.len());
+    let end = (spannable.start + spannable.len).min(chars.len());
+
+    if start >= end {
+      continue;
+    }
+
+    let replacement_text = match &spannable.spannable_data {
+      SpannableData::Spoiler => preview_options.spoiler_placeholder.clone(),
+      SpannableData::Link(PostLink::UrlLink { link, .. }) => {
+        match preview_options.url_mode {
+          PreviewUrlMode::Keep => continue,
+          PreviewUrlMode::Strip => String::new(),
+          PreviewUrlMode::Shorten => shorten_url(link)
+        }
+      }
+      _ => continue
+    };
+
+    replacements.push(Replacement { start, end, text: replacement_text });
+  }
+
+  // Nested/overlapping replacements (e.g. a link inside of a spoiler) are merged into the outer one
+  replacements.sort_by(|r1, r2| r1.start.cmp(&r2.start).then(r2.end.cmp(&r1.end)));
+
+  let mut merged: Vec<Replacement> = Vec::with_capacity(replacements.len());
+
+  for replacement in replacements {
+    if let Option::Some(last) = merged.last_mut() {
+      if replacement.start < last.end {
+        last.end = last.end.max(replacement.end);
+        continue;
+      }
+    }
+
+    merged.push(replacement);
+  }
+
+  return merged;
+}
+
+fn push_collapsing_whitespaces(preview_chars: &mut Vec<char>, chars: impl Iterator<Item=char>) {
+  for ch in chars {
+    if ch.is_whitespace() {
+      if preview_chars.is_empty() || preview_chars.last() == Option::Some(&' ') {
+        continue;
+      }
+
+      preview_chars.push(' ');
+      continue;
+    }
+
+    preview_chars.push(ch);
+  }
+}
+
+fn truncate(
+  preview_text: String,
+  spannables: Vec<Spannable>,
+  preview_options: &PreviewOptions
+) -> (String, Vec<Spannable>) {
+  // Character offsets at which the text may be cut
+  let cut_offsets: Vec<usize> = match preview_options.length_unit {
+    PreviewLengthUnit::Characters => {
+      (0..=preview_text.chars().count()).collect()
+    }
+    PreviewLengthUnit::GraphemeClusters => {
+      let mut offsets = vec![0];
+      let mut offset = 0;
+
+      for grapheme in preview_text.graphemes(true) {
+        offset += grapheme.chars().count();
+        offsets.push(offset);
+      }
+
+      offsets
+    }
+  };
+
+  let total_units = cut_offsets.len() - 1;
+  if total_units <= preview_options.max_length {
+    return (preview_text, spannables);
+  }
+
+  // An ellipsis that is longer than [PreviewOptions::max_length] is cut too, the preview never gets longer
+  // than that
+  let ellipsis: String = match preview_options.length_unit {
+    PreviewLengthUnit::Characters => {
+      preview_options.ellipsis.chars().take(preview_options.max_length).collect()
+    }
+    PreviewLengthUnit::GraphemeClusters => {
+      preview_options.ellipsis.graphemes(true).take(preview_options.max_length).collect()
+    }
+  };
+
+  let ellipsis_units = match preview_options.length_unit {
+    PreviewLengthUnit::Characters => ellipsis.chars().count(),
+    PreviewLengthUnit::GraphemeClusters => ellipsis.graphemes(true).count()
+  };
+
+  let mut cut_offset = cut_offsets[preview_options.max_length - ellipsis_units];
+
+  // Never cut a post link in half, the whole link goes away instead
+  for spannable in &spannables {
+    let is_post_link = match &spannable.spannable_data {
+      SpannableData::Link(PostLink::UrlLink { .. }) |
+      SpannableData::Link(PostLink::EmailLink { .. }) => false,
+      SpannableData::Link(_) => true,
+      _ => false
+    };
+
+    if is_post_link && spannable.start < cut_offset && spannable.start + spannable.len > cut_offset {
+      cut_offset = spannable.start;
+    }
+  }
+
+  let mut truncated_text: String = preview_text.chars().take(cut_offset).collect();
+  let trimmed_len = truncated_text.trim_end().len();
+  truncated_text.truncate(trimmed_len);
+
+  let truncated_text_len = truncated_text.chars().count();
+  truncated_text.push_str(&ellipsis);
+
+  let truncated_spannables = spannables
+    .into_iter()
+    .filter_map(|spannable| {
+      let end = (spannable.start + spannable.len).min(truncated_text_len);
+
+      let truncated_spannable = Spannable {
+        start: spannable.start,
+        len: end.saturating_sub(spannable.start),
+        spannable_data: spannable.spannable_data
+      };
+
+      if !truncated_spannable.is_valid() {
+        return Option::None;
+      }
+
+      return Option::Some(truncated_spannable);
+    })
+    .collect::<Vec<Spannable>>();
+
+  return (truncated_text, truncated_spannables);
+}
+
+fn shorten_url(url: &str) -> String {
+  let without_scheme = match url.find("://") {
+    Some(index) => &url[index + 3..],
+    None => url
+  };
+
+  let host_end = without_scheme.find(['/', '?', '#']).unwrap_or(without_scheme.len());
+  let host = &without_scheme[..host_end];
+
+  return host.strip_prefix("www.").unwrap_or(host).to_string();
+}
+
+#[test]
+fn test_shorten_url() {
+  assert_eq!("youtube.com", shorten_url("https://www.youtube.com/watch?v=57tu8AtKf9E"));
+  assert_eq!("2.com", shorten_url("http://2.com"));
+  assert_eq!("boards.4chan.org", shorten_url("https://boards.4chan.org/g/catalog#s=fglt"));
+}
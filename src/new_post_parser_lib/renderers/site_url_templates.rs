@@ -0,0 +1,124 @@
+use crate::{PostLink, PostDescriptor};
+use crate::util::helpers::percent_encode;
+
+const BOARD_CODE_PLACEHOLDER: &str = "{board_code}";
+const THREAD_NO_PLACEHOLDER: &str = "{thread_no}";
+const POST_NO_PLACEHOLDER: &str = "{post_no}";
+const SEARCH_QUERY_PLACEHOLDER: &str = "{search_query}";
+
+/// Url templates that are used by the renderers to convert [PostLink]s back into urls.
+/// Supported placeholders: {board_code}, {thread_no}, {post_no}, {search_query}. The board code and the
+/// search query are percent-encoded.
+/// [SiteUrlTemplates::quote] is formatted using the board/thread of the post that is being rendered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiteUrlTemplates {
+  // "#p{post_no}"
+  pub quote: String,
+  // "https://boards.4chan.org/{board_code}/thread/{thread_no}#p{post_no}"
+  pub thread_link: String,
+  // "https://boards.4chan.org/{board_code}/"
+  pub board_link: String,
+  // "https://boards.4chan.org/{board_code}/catalog#s={search_query}"
+  pub search_link: String
+}
+
+impl SiteUrlTemplates {
+  pub fn new(quote: &str, thread_link: &str, board_link: &str, search_link: &str) -> SiteUrlTemplates {
+    return SiteUrlTemplates {
+      quote: quote.to_string(),
+      thread_link: thread_link.to_string(),
+      board_link: board_link.to_string(),
+      search_link: search_link.to_string()
+    };
+  }
+
+  pub fn four_chan() -> SiteUrlTemplates {
+    return SiteUrlTemplates::new(
+      "#p{post_no}",
+      "https://boards.4chan.org/{board_code}/thread/{thread_no}#p{post_no}",
+      "https://boards.4chan.org/{board_code}/",
+      "https://boards.4chan.org/{board_code}/catalog#s={search_query}"
+    );
+  }
+
+  /// [post_link] must be formatted using the templates of the site this returns (cross-site links point
+  /// to a different site than [post_descriptor])
+  pub fn link_site_name<'a>(post_descriptor: &'a PostDescriptor, post_link: &'a PostLink) -> &'a str {
+    return match post_link.cross_site_descriptor() {
+      Some(site_descriptor) => &site_descriptor.site_name,
+      None => post_descriptor.site_name()
+    };
+  }
+
+  pub fn format_post_link(&self, post_descriptor: &PostDescriptor, post_link: &PostLink) -> String {
+    return match post_link {
+      PostLink::Quote { post_no } | PostLink::Dead { post_no } => {
+        self.quote
+          .replace(BOARD_CODE_PLACEHOLDER, &percent_encode(post_descriptor.board_code()))
+          .replace(THREAD_NO_PLACEHOLDER, &post_descriptor.thread_no().to_string())
+          .replace(POST_NO_PLACEHOLDER, &post_no.to_string())
+      }
+      PostLink::UrlLink { link, .. } => {
+        link.to_string()
+      }
+      PostLink::EmailLink { email } => {
+        format!("mailto:{}", email)
+      }
+      PostLink::BoardLink { board_code } => {
+        self.board_link.replace(BOARD_CODE_PLACEHOLDER, &percent_encode(board_code))
+      }
+      PostLink::SearchLink { board_code, search_query } => {
+        self.search_link
+          .replace(BOARD_CODE_PLACEHOLDER, &percent_encode(board_code))
+          .replace(SEARCH_QUERY_PLACEHOLDER, &percent_encode(search_query))
+      }
+      PostLink::ThreadLink { board_code, thread_no, post_no } => {
+        self.thread_link
+          .replace(BOARD_CODE_PLACEHOLDER, &percent_encode(board_code))
+          .replace(THREAD_NO_PLACEHOLDER, &thread_no.to_string())
+          .replace(POST_NO_PLACEHOLDER, &post_no.to_string())
+      }
+      PostLink::CrossSiteThreadLink { post_descriptor } => {
+        self.thread_link
+          .replace(BOARD_CODE_PLACEHOLDER, &percent_encode(post_descriptor.board_code()))
+          .replace(THREAD_NO_PLACEHOLDER, &post_descriptor.thread_no().to_string())
+          .replace(POST_NO_PLACEHOLDER, &post_descriptor.post_no().to_string())
+      }
+      PostLink::CrossSiteBoardLink { board_descriptor } => {
+        self.board_link.replace(BOARD_CODE_PLACEHOLDER, &percent_encode(&board_descriptor.board_code))
+      }
+      PostLink::CrossSiteSearchLink { board_descriptor, search_query } => {
+        self.search_link
+          .replace(BOARD_CODE_PLACEHOLDER, &percent_encode(&board_descriptor.board_code))
+          .replace(SEARCH_QUERY_PLACEHOLDER, &percent_encode(search_query))
+      }
+    }
+  }
+}
+
+#[test]
+fn test_format_post_link() {
+  let templates = SiteUrlTemplates::four_chan();
+  let post_descriptor = PostDescriptor::new("4chan", "g", 1, 2, 0);
+
+  assert_eq!(
+    "#p123",
+    templates.format_post_link(&post_descriptor, &PostLink::Dead { post_no: 123 })
+  );
+  assert_eq!(
+    "https://boards.4chan.org/vg/thread/10#p11",
+    templates.format_post_link(&post_descriptor, &PostLink::ThreadLink { board_code: "vg".to_string(), thread_no: 10, post_no: 11 })
+  );
+  assert_eq!(
+    "https://boards.4chan.org/g/catalog#s=fglt",
+    templates.format_post_link(&post_descriptor, &PostLink::SearchLink { board_code: "g".to_string(), search_query: "fglt".to_string() })
+  );
+  assert_eq!(
+    "https://boards.4chan.org/a/thread/5#p6",
+    templates.format_post_link(&post_descriptor, &PostLink::CrossSiteThreadLink { post_descriptor: PostDescriptor::new("4chan", "a", 5, 6, 0) })
+  );
+  assert_eq!(
+    "https://boards.4chan.org/g%2F..%2F/catalog#s=a%20b%26c%22",
+    templates.format_post_link(&post_descriptor, &PostLink::SearchLink { board_code: "g/../".to_string(), search_query: "a b&c\"".to_string() })
+  );
+}
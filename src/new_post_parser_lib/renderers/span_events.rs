@@ -0,0 +1,156 @@
+use crate::Spannable;
+
+/// One step of a walk over parsed text and its spannables. The events are always properly nested
+/// (every [SpanEvent::Close] closes the most recently opened spannable) so overlapping spannables are
+/// split into several Open/Close pairs.
+#[derive(Debug, PartialEq)]
+pub enum SpanEvent<'a> {
+  Open(&'a Spannable),
+  Close(&'a Spannable),
+  Text(&'a str)
+}
+
+pub fn collect_span_events<'a>(
+  parsed_text: &'a str,
+  spannables: &'a [Spannable],
+  accepts: &dyn Fn(&Spannable) -> bool
+) -> Vec<SpanEvent<'a>> {
+  // Spannables use unicode characters (not u8!) so we need a way to convert them into byte offsets
+  let mut char_byte_offsets: Vec<usize> = parsed_text.char_indices()
+    .map(|(byte_offset, _)| byte_offset)
+    .collect();
+  char_byte_offsets.push(parsed_text.len());
+
+  let total_chars_count = char_byte_offsets.len() - 1;
+
+  let mut candidates: Vec<(usize, usize, &Spannable)> = Vec::with_capacity(spannables.len());
+
+  for spannable in spannables {
+    if !spannable.is_valid() || !accepts(spannable) {
+      continue;
+    }
+
+    let start = spannable.start.min(total_chars_count);
+    let end = (spannable.start + spannable.len).min(total_chars_count);
+
+    if start >= end {
+      continue;
+    }
+
+    candidates.push((start, end, spannable));
+  }
+
+  // Outer spannables (the ones that start earlier and end later) must be opened first
+  candidates.sort_by(|(start1, end1, _), (start2, end2, _)| {
+    return start1.cmp(start2).then(end2.cmp(end1));
+  });
+
+  let mut boundaries: Vec<usize> = Vec::with_capacity(candidates.len() * 2 + 2);
+  boundaries.push(0);
+  boundaries.push(total_chars_count);
+
+  for (start, end, _) in &candidates {
+    boundaries.push(*start);
+    boundaries.push(*end);
+  }
+
+  boundaries.sort_unstable();
+  boundaries.dedup();
+
+  let mut out_events: Vec<SpanEvent> = Vec::with_capacity(candidates.len() * 2 + 1);
+  let mut stack: Vec<(usize, &Spannable)> = Vec::with_capacity(8);
+  let mut next_candidate_index = 0;
+
+  for (boundary_index, boundary) in boundaries.iter().enumerate() {
+    let boundary = *boundary;
+
+    let lowest_ending_index = stack.iter().position(|(end, _)| *end <= boundary);
+    if let Option::Some(lowest_ending_index) = lowest_ending_index {
+      let mut to_reopen: Vec<(usize, &Spannable)> = Vec::new();
+
+      while stack.len() > lowest_ending_index {
+        let (end, spannable) = stack.pop().unwrap();
+        out_events.push(SpanEvent::Close(spannable));
+
+        if end > boundary {
+          to_reopen.push((end, spannable));
+        }
+      }
+
+      for (end, spannable) in to_reopen.into_iter().rev() {
+        out_events.push(SpanEvent::Open(spannable));
+        stack.push((end, spannable));
+      }
+    }
+
+    while next_candidate_index < candidates.len() && candidates[next_candidate_index].0 == boundary {
+      let (_, end, spannable) = candidates[next_candidate_index];
+      out_events.push(SpanEvent::Open(spannable));
+      stack.push((end, spannable));
+
+      next_candidate_index += 1;
+    }
+
+    if let Option::Some(next_boundary) = boundaries.get(boundary_index + 1) {
+      let text = &parsed_text[char_byte_offsets[boundary]..char_byte_offsets[*next_boundary]];
+      if !text.is_empty() {
+        out_events.push(SpanEvent::Text(text));
+      }
+    }
+  }
+
+  return out_events;
+}
+
+#[test]
+fn test_collect_span_events_overlapping() {
+  use crate::SpannableData;
+
+  let spannables = vec![
+    Spannable { start: 0, len: 4, spannable_data: SpannableData::BoldText },
+    Spannable { start: 2, len: 4, spannable_data: SpannableData::Spoiler },
+  ];
+
+  let events = collect_span_events("abcdefgh", &spannables, &|_| true);
+
+  assert_eq!(
+    vec![
+      SpanEvent::Open(&spannables[0]),
+      SpanEvent::Text("ab"),
+      SpanEvent::Open(&spannables[1]),
+      SpanEvent::Text("cd"),
+      SpanEvent::Close(&spannables[1]),
+      SpanEvent::Close(&spannables[0]),
+      SpanEvent::Open(&spannables[1]),
+      SpanEvent::Text("ef"),
+      SpanEvent::Close(&spannables[1]),
+      SpanEvent::Text("gh"),
+    ],
+    events
+  );
+}
+
+#[test]
+fn test_collect_span_events_unicode_and_out_of_bounds() {
+  use crate::SpannableData;
+
+  let spannables = vec![
+    Spannable { start: 1, len: 100, spannable_data: SpannableData::GreenText },
+    Spannable { start: 1, len: 1, spannable_data: SpannableData::BoldText },
+  ];
+
+  let events = collect_span_events("ニコニ", &spannables, &|_| true);
+
+  assert_eq!(
+    vec![
+      SpanEvent::Text("ニ"),
+      SpanEvent::Open(&spannables[0]),
+      SpanEvent::Open(&spannables[1]),
+      SpanEvent::Text("コ"),
+      SpanEvent::Close(&spannables[1]),
+      SpanEvent::Text("ニ"),
+      SpanEvent::Close(&spannables[0]),
+    ],
+    events
+  );
+}
@@ -0,0 +1,309 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::collections::HashSet;
+use crate::rules::rule_handler::RuleHandler;
+use crate::{empty_set, PostRaw, PostParserContext, Element, TextPart, Spannable, SpannableData, ParsingRule};
+use crate::util::helpers::SumBy;
+
+const LIST_ITEM_BULLET: &str = "\u{2022} ";
+const LIST_INDENT: &str = "  ";
+const HORIZONTAL_RULE_TEXT: &str = "\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}\u{2500}";
+
+/// Rules for the block-level elements (<p>, <div>, <h1>-<h6>, <ul>/<ol>/<li>, <blockquote> and <hr>)
+/// which would otherwise be flattened into running text. Their markup is the same on every site so all of
+/// the profiles add these after their own rules. Every call creates a new list state so the rules must not
+/// be shared between site profiles.
+pub fn block_rules() -> Vec<ParsingRule> {
+  let open_lists = Rc::new(RefCell::new(Vec::new()));
+
+  return vec![
+    ParsingRule::new("p", empty_set!(), Rc::new(BlockHandler::new(Option::None))),
+    ParsingRule::new("div", empty_set!(), Rc::new(BlockHandler::new(Option::None))),
+    ParsingRule::new("h1", empty_set!(), Rc::new(BlockHandler::new(Option::Some(SpannableData::Heading { level: 1 })))),
+    ParsingRule::new("h2", empty_set!(), Rc::new(BlockHandler::new(Option::Some(SpannableData::Heading { level: 2 })))),
+    ParsingRule::new("h3", empty_set!(), Rc::new(BlockHandler::new(Option::Some(SpannableData::Heading { level: 3 })))),
+    ParsingRule::new("h4", empty_set!(), Rc::new(BlockHandler::new(Option::Some(SpannableData::Heading { level: 4 })))),
+    ParsingRule::new("h5", empty_set!(), Rc::new(BlockHandler::new(Option::Some(SpannableData::Heading { level: 5 })))),
+    ParsingRule::new("h6", empty_set!(), Rc::new(BlockHandler::new(Option::Some(SpannableData::Heading { level: 6 })))),
+    ParsingRule::new("blockquote", empty_set!(), Rc::new(BlockHandler::new(Option::Some(SpannableData::BlockQuote)))),
+    ParsingRule::new("ul", empty_set!(), Rc::new(ListHandler::new(false, open_lists.clone()))),
+    ParsingRule::new("ol", empty_set!(), Rc::new(ListHandler::new(true, open_lists.clone()))),
+    ParsingRule::new("li", empty_set!(), Rc::new(ListItemHandler::new(open_lists))),
+    ParsingRule::new("hr", empty_set!(), Rc::new(HorizontalRuleHandler::new())),
+  ];
+}
+
+/// Puts the element on its own line and marks its text with [spannable_data] (if there is one).
+pub struct BlockHandler {
+  spannable_data: Option<SpannableData>
+}
+
+impl BlockHandler {
+  pub fn new(spannable_data: Option<SpannableData>) -> BlockHandler {
+    return BlockHandler {
+      spannable_data
+    };
+  }
+}
+
+impl RuleHandler for BlockHandler {
+
+  fn pre_handle(
+    &self,
+    _: &PostRaw,
+    _: &PostParserContext,
+    _: &Element,
+    out_text_parts: &mut Vec<TextPart>,
+    _: &mut Vec<Spannable>
+  ) -> bool {
+    start_new_line(out_text_parts);
+    return false;
+  }
+
+  fn post_handle(
+    &self,
+    _: &PostRaw,
+    _: &PostParserContext,
+    _: &Element,
+    prev_out_text_parts_index: usize,
+    out_text_parts: &mut Vec<TextPart>,
+    _: usize,
+    out_spannables: &mut Vec<Spannable>
+  ) {
+    let start = block_text_start(prev_out_text_parts_index, out_text_parts);
+    push_block_spannable(start, &self.spannable_data, out_text_parts, out_spannables);
+    start_new_line(out_text_parts);
+  }
+
+}
+
+struct OpenList {
+  is_ordered: bool,
+  next_number: i64
+}
+
+/// <ul> and <ol>, the items are handled by [ListItemHandler]
+pub struct ListHandler {
+  is_ordered: bool,
+  open_lists: Rc<RefCell<Vec<OpenList>>>
+}
+
+impl ListHandler {
+  fn new(is_ordered: bool, open_lists: Rc<RefCell<Vec<OpenList>>>) -> ListHandler {
+    return ListHandler {
+      is_ordered,
+      open_lists
+    };
+  }
+}
+
+impl RuleHandler for ListHandler {
+
+  fn pre_handle(
+    &self,
+    _: &PostRaw,
+    _: &PostParserContext,
+    element: &Element,
+    out_text_parts: &mut Vec<TextPart>,
+    _: &mut Vec<Spannable>
+  ) -> bool {
+    start_new_line(out_text_parts);
+
+    if !element.children.is_empty() {
+      let next_number = element.get_attr_value("start")
+        .and_then(|start| start.trim().parse::<i64>().ok())
+        .unwrap_or(1);
+
+      self.open_lists.borrow_mut().push(OpenList { is_ordered: self.is_ordered, next_number });
+    }
+
+    return false;
+  }
+
+  fn post_handle(
+    &self,
+    _: &PostRaw,
+    _: &PostParserContext,
+    _: &Element,
+    _: usize,
+    out_text_parts: &mut Vec<TextPart>,
+    _: usize,
+    _: &mut Vec<Spannable>
+  ) {
+    self.open_lists.borrow_mut().pop();
+    start_new_line(out_text_parts);
+  }
+
+}
+
+/// <li>, starts with a bullet ("• ") or a number ("1. ") which are indented for nested lists
+pub struct ListItemHandler {
+  open_lists: Rc<RefCell<Vec<OpenList>>>
+}
+
+impl ListItemHandler {
+  fn new(open_lists: Rc<RefCell<Vec<OpenList>>>) -> ListItemHandler {
+    return ListItemHandler {
+      open_lists
+    };
+  }
+
+  fn next_marker(&self) -> String {
+    let mut open_lists = self.open_lists.borrow_mut();
+    let indent = LIST_INDENT.repeat(open_lists.len().saturating_sub(1));
+
+    let open_list = match open_lists.last_mut() {
+      Some(open_list) => open_list,
+      // <li> outside of a list
+      None => return String::from(LIST_ITEM_BULLET)
+    };
+
+    if !open_list.is_ordered {
+      return format!("{}{}", indent, LIST_ITEM_BULLET);
+    }
+
+    let number = open_list.next_number;
+    open_list.next_number = open_list.next_number.saturating_add(1);
+
+    return format!("{}{}. ", indent, number);
+  }
+}
+
+impl RuleHandler for ListItemHandler {
+
+  fn pre_handle(
+    &self,
+    _: &PostRaw,
+    _: &PostParserContext,
+    _: &Element,
+    out_text_parts: &mut Vec<TextPart>,
+    _: &mut Vec<Spannable>
+  ) -> bool {
+    start_new_line(out_text_parts);
+
+    // Empty items still take a number
+    out_text_parts.push(TextPart::new(self.next_marker()));
+    return false;
+  }
+
+  fn post_handle(
+    &self,
+    _: &PostRaw,
+    _: &PostParserContext,
+    _: &Element,
+    prev_out_text_parts_index: usize,
+    out_text_parts: &mut Vec<TextPart>,
+    _: usize,
+    out_spannables: &mut Vec<Spannable>
+  ) {
+    let start = block_text_start(prev_out_text_parts_index, out_text_parts);
+    push_block_spannable(start, &Option::Some(SpannableData::ListItem), out_text_parts, out_spannables);
+    start_new_line(out_text_parts);
+  }
+
+}
+
+/// <hr>, replaced with a line of box drawing characters
+pub struct HorizontalRuleHandler {}
+
+impl HorizontalRuleHandler {
+  pub fn new() -> HorizontalRuleHandler {
+    return HorizontalRuleHandler {};
+  }
+}
+
+impl RuleHandler for HorizontalRuleHandler {
+
+  fn pre_handle(
+    &self,
+    _: &PostRaw,
+    _: &PostParserContext,
+    _: &Element,
+    out_text_parts: &mut Vec<TextPart>,
+    out_spannables: &mut Vec<Spannable>
+  ) -> bool {
+    start_new_line(out_text_parts);
+
+    let spannable = Spannable {
+      start: text_len(out_text_parts),
+      len: HORIZONTAL_RULE_TEXT.chars().count(),
+      spannable_data: SpannableData::HorizontalRule
+    };
+
+    out_text_parts.push(TextPart::new(String::from(HORIZONTAL_RULE_TEXT)));
+    out_text_parts.push(TextPart::block_line_break());
+    out_spannables.push(spannable);
+
+    return true;
+  }
+
+  fn post_handle(
+    &self,
+    _: &PostRaw,
+    _: &PostParserContext,
+    _: &Element,
+    _: usize,
+    _: &mut Vec<TextPart>,
+    _: usize,
+    _: &mut Vec<Spannable>
+  ) {
+    // no-op
+  }
+
+}
+
+fn text_len(out_text_parts: &[TextPart]) -> usize {
+  return out_text_parts.iter().sum_by(&|text_part| text_part.characters_count as i32) as usize;
+}
+
+/// Start of the text of the block, the line break that pre_handle() puts before it is not a part of it
+fn block_text_start(prev_out_text_parts_index: usize, out_text_parts: &[TextPart]) -> usize {
+  let leading_line_breaks = out_text_parts[prev_out_text_parts_index..]
+    .iter()
+    .take_while(|text_part| text_part.is_block_line_break)
+    .count();
+
+  return text_len(&out_text_parts[..prev_out_text_parts_index + leading_line_breaks]);
+}
+
+/// Adds a line break unless the text is empty or already ends with one. The line break is removed when
+/// the post ends with it (see [TextPart::block_line_break]).
+fn start_new_line(out_text_parts: &mut Vec<TextPart>) {
+  let last_text_part = out_text_parts.iter().rev().find(|text_part| !text_part.text.is_empty());
+
+  if let Some(last_text_part) = last_text_part {
+    if !last_text_part.text.ends_with('\n') {
+      out_text_parts.push(TextPart::block_line_break());
+    }
+  }
+}
+
+/// The trailing line breaks of the block are not a part of the spannable
+fn push_block_spannable(
+  start: usize,
+  spannable_data: &Option<SpannableData>,
+  out_text_parts: &[TextPart],
+  out_spannables: &mut Vec<Spannable>
+) {
+  let spannable_data = match spannable_data {
+    Some(spannable_data) => spannable_data,
+    None => return
+  };
+
+  let trailing_line_breaks = out_text_parts.iter()
+    .rev()
+    .flat_map(|text_part| text_part.text.chars().rev())
+    .take_while(|char| *char == '\n')
+    .count();
+  let end = text_len(out_text_parts).saturating_sub(trailing_line_breaks);
+
+  let spannable = Spannable {
+    start,
+    len: end.saturating_sub(start),
+    spannable_data: spannable_data.clone()
+  };
+
+  if spannable.is_valid() {
+    out_spannables.push(spannable);
+  }
+}
@@ -12,6 +12,7 @@ pub trait RuleHandler {
     out_spannables: &mut Vec<Spannable>
   ) -> bool;
 
+  #[allow(clippy::too_many_arguments)]
   fn post_handle(
     &self,
     post_raw: &PostRaw,
@@ -26,21 +27,21 @@ pub trait RuleHandler {
 }
 
 pub trait RuleHandlerPostHandleMeta {
-  fn get_out_text_parts_diff_text(&self, prev_out_text_parts_index: usize, out_text_parts: &Vec<TextPart>) -> String;
-  fn get_out_text_parts_diff_len(&self, prev_out_text_parts_index: usize, out_text_parts: &Vec<TextPart>) -> i32;
-  fn get_out_text_parts_new_len(&self, prev_out_text_parts_index: usize, out_text_parts: &Vec<TextPart>) -> i32;
+  fn get_out_text_parts_diff_text(&self, prev_out_text_parts_index: usize, out_text_parts: &[TextPart]) -> String;
+  fn get_out_text_parts_diff_len(&self, prev_out_text_parts_index: usize, out_text_parts: &[TextPart]) -> i32;
+  fn get_out_text_parts_new_len(&self, prev_out_text_parts_index: usize, out_text_parts: &[TextPart]) -> i32;
 }
 
 impl RuleHandlerPostHandleMeta for dyn RuleHandler {
 
-  fn get_out_text_parts_diff_text(&self, prev_out_text_parts_index: usize, out_text_parts: &Vec<TextPart>) -> String {
+  fn get_out_text_parts_diff_text(&self, prev_out_text_parts_index: usize, out_text_parts: &[TextPart]) -> String {
     return out_text_parts[prev_out_text_parts_index..]
       .iter()
       .map_join(&|text_part| text_part.text.as_str());
   }
 
-  fn get_out_text_parts_diff_len(&self, prev_out_text_parts_index: usize, out_text_parts: &Vec<TextPart>) -> i32 {
-    if prev_out_text_parts_index <= 0 {
+  fn get_out_text_parts_diff_len(&self, prev_out_text_parts_index: usize, out_text_parts: &[TextPart]) -> i32 {
+    if prev_out_text_parts_index == 0 {
       return 0;
     }
 
@@ -49,10 +50,10 @@ impl RuleHandlerPostHandleMeta for dyn RuleHandler {
       .sum_by(&|string| string.characters_count as i32);
   }
 
-  fn get_out_text_parts_new_len(&self, prev_out_text_parts_index: usize, out_text_parts: &Vec<TextPart>) -> i32 {
+  fn get_out_text_parts_new_len(&self, prev_out_text_parts_index: usize, out_text_parts: &[TextPart]) -> i32 {
     return out_text_parts[prev_out_text_parts_index..]
       .iter()
       .sum_by(&|string| string.characters_count as i32);
   }
 
-}
\ No newline at end of file
+}
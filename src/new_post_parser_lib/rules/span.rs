@@ -85,8 +85,7 @@ impl SpanHandler {
       }
     };
 
-    if quote_text_child.starts_with(">>") {
-      let quote_text = &quote_text_child[2..];
+    if let Some(quote_text) = quote_text_child.strip_prefix(">>") {
       let quote_value_result = quote_text.parse::<u64>();
 
       let quote_value = match quote_value_result {
@@ -127,17 +126,17 @@ impl SpanHandler {
   fn handle_quote_class(
     &self,
     prev_out_text_parts_index: usize,
-    out_text_parts: &mut Vec<TextPart>,
+    out_text_parts: &[TextPart],
     out_spannables: &mut Vec<Spannable>
   ) {
     let start = (self as &dyn RuleHandler).get_out_text_parts_diff_len(
       prev_out_text_parts_index,
-      &out_text_parts
+      out_text_parts
     ) as usize;
 
     let len = (self as &dyn RuleHandler).get_out_text_parts_new_len(
       prev_out_text_parts_index,
-      &out_text_parts
+      out_text_parts
     ) as usize;
 
     let spannable = Spannable {
@@ -43,28 +43,27 @@ impl RuleHandler for StyleHandler {
 
     let style_attr_value_maybe = element.get_attr_value("style");
 
-    let style_attr_value = if let Option::None = style_attr_value_maybe {
-      return;
-    } else {
-      style_attr_value_maybe.unwrap()
+    let style_attr_value = match style_attr_value_maybe {
+      None => return,
+      Some(style_attr_value) => style_attr_value
     };
 
     let start = (self as &dyn RuleHandler).get_out_text_parts_diff_len(
       prev_out_text_parts_index,
-      &out_text_parts
+      out_text_parts
     ) as usize;
 
     let len = (self as &dyn RuleHandler).get_out_text_parts_new_len(
       prev_out_text_parts_index,
-      &out_text_parts
+      out_text_parts
     ) as usize;
 
     let spannables = decode_style_spans(style_attr_value)
       .iter()
       .map(|spannable_data| {
         return Spannable {
-          start: start as usize,
-          len: len as usize,
+          start,
+          len,
           spannable_data: spannable_data.clone()
         };
       }).collect::<Vec<Spannable>>();
@@ -34,12 +34,12 @@ impl RuleHandler for TableHandler {
   ) {
     let start = (self as &dyn RuleHandler).get_out_text_parts_diff_len(
       prev_out_text_parts_index,
-      &out_text_parts
+      out_text_parts
     ) as usize;
 
     let len = (self as &dyn RuleHandler).get_out_text_parts_new_len(
       prev_out_text_parts_index,
-      &out_text_parts
+      out_text_parts
     ) as usize;
 
     let spannable = Spannable {
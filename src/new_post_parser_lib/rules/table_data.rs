@@ -34,7 +34,7 @@ impl RuleHandler for TableDataHandler {
   ) {
     let text = (self as &dyn RuleHandler).get_out_text_parts_diff_text(
       prev_out_text_parts_index,
-      &out_text_parts
+      out_text_parts
     );
 
     let mut only_contains_whitespaces = true;
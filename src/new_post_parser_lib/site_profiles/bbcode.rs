@@ -0,0 +1,65 @@
+use std::rc::Rc;
+use std::collections::HashSet;
+use crate::{empty_set, ParsingRule, SpannableData, CommentFormat};
+use crate::site_profiles::site_profile::{SiteProfile, PostLinkResolver};
+use crate::site_profiles::four_chan::FourChanPostLinkResolver;
+use crate::rules::line_break::LineBreakRuleHandler;
+use crate::rules::text_style::TextStyleHandler;
+
+/// For sites (and APIs, like the raw comments of FoolFuuka) that deliver comments as BBCode. The
+/// elements are the ones [BbCodeParser] produces. Not registered by default, see
+/// [PostParser::add_site_profile].
+pub struct BbCodeSiteProfile {
+  site_name: String,
+  // BBCode comments have no <a> elements, the resolver is only here because every profile must have one
+  post_link_resolver: Rc<FourChanPostLinkResolver>
+}
+
+impl BbCodeSiteProfile {
+  pub fn new(site_name: &str) -> BbCodeSiteProfile {
+    return BbCodeSiteProfile {
+      site_name: String::from(site_name),
+      post_link_resolver: Rc::new(FourChanPostLinkResolver {})
+    };
+  }
+}
+
+impl SiteProfile for BbCodeSiteProfile {
+
+  fn site_name(&self) -> &str {
+    return &self.site_name;
+  }
+
+  fn matching_rules(&self) -> Vec<ParsingRule> {
+    return vec![
+      ParsingRule::new("br", empty_set!(), Rc::new(LineBreakRuleHandler::new())),
+      ParsingRule::new("spoiler", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::Spoiler))),
+      ParsingRule::new("code", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::Monospace))),
+      ParsingRule::new("b", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::BoldText))),
+      ParsingRule::new("i", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::ItalicText))),
+      ParsingRule::new("u", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::UnderlineText))),
+      ParsingRule::new("s", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::StrikethroughText))),
+      ParsingRule::new("sup", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::SuperscriptText))),
+      ParsingRule::new("sub", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::SubscriptText))),
+      ParsingRule::new("math", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::Math { is_display_mode: false }))),
+      ParsingRule::new("eqn", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::Math { is_display_mode: true }))),
+    ];
+  }
+
+  fn replacement_rules(&self) -> Vec<(String, String)> {
+    return vec![];
+  }
+
+  fn post_link_resolver(&self) -> Rc<dyn PostLinkResolver> {
+    return self.post_link_resolver.clone();
+  }
+
+  fn comment_format(&self) -> CommentFormat {
+    return CommentFormat::BbCode;
+  }
+
+  fn detect_plain_text_quotes(&self) -> bool {
+    return true;
+  }
+
+}
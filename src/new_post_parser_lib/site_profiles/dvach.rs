@@ -0,0 +1,130 @@
+use std::rc::Rc;
+use std::collections::HashSet;
+use regex::Regex;
+use crate::{empty_set, set_of, Attribute, ParsingRule, SpannableData, PostRaw, PostParserContext, PostLink, Element};
+use crate::parsing_error::ParsingError;
+use crate::site_profiles::site_profile::{SiteProfile, PostLinkResolver, thread_post_link};
+use crate::site_profiles::res_link_resolver::ResLinkResolver;
+use crate::rules::anchor::AnchorRuleHandler;
+use crate::rules::line_break::LineBreakRuleHandler;
+use crate::rules::style::StyleHandler;
+use crate::rules::text_style::TextStyleHandler;
+use crate::rules::block::block_rules;
+
+pub const SITE_NAME: &str = "2ch.hk";
+
+// Reply links carry the thread and the post in these besides the "href"
+const DATA_THREAD: &str = "data-thread";
+const DATA_NUM: &str = "data-num";
+
+lazy_static! {
+  static ref BOARD_CODE_PATTERN: Regex = Regex::new(r"^(?:https?://[^/]+)?/(\w+)/").unwrap();
+}
+
+pub struct DvachSiteProfile {
+  post_link_resolver: Rc<DvachPostLinkResolver>
+}
+
+impl Default for DvachSiteProfile {
+  fn default() -> Self {
+    return DvachSiteProfile::new();
+  }
+}
+
+impl DvachSiteProfile {
+  pub fn new() -> DvachSiteProfile {
+    return DvachSiteProfile {
+      post_link_resolver: Rc::new(DvachPostLinkResolver { res_link_resolver: ResLinkResolver {} })
+    };
+  }
+}
+
+impl SiteProfile for DvachSiteProfile {
+
+  fn site_name(&self) -> &str {
+    return SITE_NAME;
+  }
+
+  fn matching_rules(&self) -> Vec<ParsingRule> {
+    let mut rules = vec![
+      // Wildcard rules go first
+      ParsingRule::new("*", set_of!(Attribute::with_name("style")), Rc::new(StyleHandler::new())),
+
+      // Then go rules that require specific attributes
+      ParsingRule::new("span", set_of!(Attribute::with_class("unkfunc")), Rc::new(TextStyleHandler::new(SpannableData::GreenText))),
+      ParsingRule::new("span", set_of!(Attribute::with_class("spoiler")), Rc::new(TextStyleHandler::new(SpannableData::Spoiler))),
+      ParsingRule::new("span", set_of!(Attribute::with_class("u")), Rc::new(TextStyleHandler::new(SpannableData::UnderlineText))),
+      ParsingRule::new("span", set_of!(Attribute::with_class("o")), Rc::new(TextStyleHandler::new(SpannableData::OverlineText))),
+      ParsingRule::new("span", set_of!(Attribute::with_class("s")), Rc::new(TextStyleHandler::new(SpannableData::StrikethroughText))),
+
+      // Then go general rules for the whole tag
+      ParsingRule::new("a", empty_set!(), Rc::new(AnchorRuleHandler::new(self.post_link_resolver()))),
+      ParsingRule::new("br", empty_set!(), Rc::new(LineBreakRuleHandler::new())),
+      ParsingRule::new("strong", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::BoldText))),
+      ParsingRule::new("b", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::BoldText))),
+      ParsingRule::new("em", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::ItalicText))),
+      ParsingRule::new("i", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::ItalicText))),
+      ParsingRule::new("sup", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::SuperscriptText))),
+      ParsingRule::new("sub", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::SubscriptText))),
+    ];
+
+    rules.extend(block_rules());
+    return rules;
+  }
+
+  fn replacement_rules(&self) -> Vec<(String, String)> {
+    return vec![];
+  }
+
+  fn post_link_resolver(&self) -> Rc<dyn PostLinkResolver> {
+    return self.post_link_resolver.clone();
+  }
+
+  fn site_hosts(&self) -> Vec<String> {
+    return vec![String::from("2ch.hk"), String::from("2ch.life")];
+  }
+
+}
+
+/// Resolves "href" the same way as [ResLinkResolver] unless the link has "data-thread" and "data-num"
+/// attributes ("<a href="/b/res/262542405.html#262542501" data-thread="262542405" data-num="262542501">"),
+/// these are preferred.
+pub struct DvachPostLinkResolver {
+  res_link_resolver: ResLinkResolver
+}
+
+impl PostLinkResolver for DvachPostLinkResolver {
+
+  fn link_raw_to_post_link(
+    &self,
+    post_raw: &PostRaw,
+    post_parser_context: &PostParserContext,
+    link_raw: &str
+  ) -> Result<PostLink, ParsingError> {
+    return self.res_link_resolver.link_raw_to_post_link(post_raw, post_parser_context, link_raw);
+  }
+
+  fn link_element_to_post_link(
+    &self,
+    post_raw: &PostRaw,
+    post_parser_context: &PostParserContext,
+    element: &Element,
+    link_raw: &str
+  ) -> Result<PostLink, ParsingError> {
+    let thread_no_maybe = element.get_attr_value(DATA_THREAD).and_then(|thread_no| thread_no.parse::<u64>().ok());
+    let post_no_maybe = element.get_attr_value(DATA_NUM).and_then(|post_no| post_no.parse::<u64>().ok());
+
+    if thread_no_maybe.is_none() || post_no_maybe.is_none() {
+      return self.link_raw_to_post_link(post_raw, post_parser_context, link_raw);
+    }
+
+    // The attributes have no board, it's only in the "href"
+    let board_code = BOARD_CODE_PATTERN.captures(link_raw)
+      .and_then(|captures| captures.get(1))
+      .map(|board_code| board_code.as_str())
+      .unwrap_or_else(|| post_raw.board_code());
+
+    return Result::Ok(thread_post_link(post_raw, post_parser_context, board_code, thread_no_maybe.unwrap(), post_no_maybe.unwrap()));
+  }
+
+}
@@ -0,0 +1,198 @@
+use std::rc::Rc;
+use std::collections::HashSet;
+use regex::Regex;
+use crate::{empty_set, set_of, Attribute, ParsingRule, PostLink, PostParserContext, PostRaw, SpannableData, UrlKind};
+use crate::parsing_error::ParsingError;
+use crate::site_profiles::site_profile::{SiteProfile, PostLinkResolver, thread_post_link};
+use crate::rules::anchor::AnchorRuleHandler;
+use crate::rules::line_break::LineBreakRuleHandler;
+use crate::rules::style::StyleHandler;
+use crate::rules::text_style::TextStyleHandler;
+use crate::rules::block::block_rules;
+use crate::util::style_tag_value_decoder::smaller_font_size;
+
+lazy_static! {
+  static ref THREAD_LINK_PATTERN: Regex = Regex::new(r"^(?:(?:https?:)?//(?:www\.)?([^/]+))?/(\w+)/thread/(\d+)/?(?:#q?(\d+))?$").unwrap();
+  static ref BOARD_LINK_PATTERN: Regex = Regex::new(r"^(?:(?:https?:)?//(?:www\.)?([^/]+))?/(\w+)/?$").unwrap();
+}
+
+/// Archives (desuarchive, archived.moe etc) that run FoolFuuka. Links to the archived posts are absolute
+/// ("https://desuarchive.org/g/thread/1/#2") so the profile needs to know the domain of the archive to
+/// tell them from the links to other sites. Not registered by default, see
+/// [PostParser::add_site_profile].
+pub struct FoolFuukaSiteProfile {
+  site_name: String,
+  post_link_resolver: Rc<FoolFuukaPostLinkResolver>
+}
+
+impl FoolFuukaSiteProfile {
+  pub fn new(site_name: &str, archive_domain: &str) -> FoolFuukaSiteProfile {
+    let post_link_resolver = FoolFuukaPostLinkResolver {
+      archive_domain: String::from(archive_domain)
+    };
+
+    return FoolFuukaSiteProfile {
+      site_name: String::from(site_name),
+      post_link_resolver: Rc::new(post_link_resolver)
+    };
+  }
+}
+
+impl SiteProfile for FoolFuukaSiteProfile {
+
+  fn site_name(&self) -> &str {
+    return &self.site_name;
+  }
+
+  fn matching_rules(&self) -> Vec<ParsingRule> {
+    let mut rules = vec![
+      // Wildcard rules go first
+      ParsingRule::new("*", set_of!(Attribute::with_name("style")), Rc::new(StyleHandler::new())),
+
+      // Then go rules that require specific attributes
+      ParsingRule::new("span", set_of!(Attribute::with_class("greentext")), Rc::new(TextStyleHandler::new(SpannableData::GreenText))),
+      ParsingRule::new("span", set_of!(Attribute::with_class("spoiler")), Rc::new(TextStyleHandler::new(SpannableData::Spoiler))),
+
+      // Then go general rules for the whole tag
+      // Quotes look like <a href="https://desuarchive.org/g/thread/1/#2" class="backlink" data-board="g" data-post="2">
+      ParsingRule::new("a", empty_set!(), Rc::new(AnchorRuleHandler::new(self.post_link_resolver()))),
+      ParsingRule::new("br", empty_set!(), Rc::new(LineBreakRuleHandler::new())),
+      ParsingRule::new("strong", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::BoldText))),
+      ParsingRule::new("b", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::BoldText))),
+      ParsingRule::new("em", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::ItalicText))),
+      ParsingRule::new("i", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::ItalicText))),
+      ParsingRule::new("u", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::UnderlineText))),
+      ParsingRule::new("ins", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::UnderlineText))),
+      ParsingRule::new("del", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::StrikethroughText))),
+      ParsingRule::new("strike", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::StrikethroughText))),
+      ParsingRule::new("sup", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::SuperscriptText))),
+      ParsingRule::new("sub", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::SubscriptText))),
+      ParsingRule::new("small", empty_set!(), Rc::new(TextStyleHandler::new(smaller_font_size()))),
+    ];
+
+    rules.extend(block_rules());
+    return rules;
+  }
+
+  fn replacement_rules(&self) -> Vec<(String, String)> {
+    return vec![
+      // HtmlParser does not support self-closing tags
+      (String::from("<br />"), String::from("<br>")),
+      (String::from("<br/>"), String::from("<br>")),
+    ];
+  }
+
+  fn post_link_resolver(&self) -> Rc<dyn PostLinkResolver> {
+    return self.post_link_resolver.clone();
+  }
+
+  fn site_hosts(&self) -> Vec<String> {
+    return vec![self.post_link_resolver.archive_domain.to_lowercase()];
+  }
+
+  /// Archives keep the quotes to the posts they did not archive (and to other boards) as plain text
+  fn detect_plain_text_quotes(&self) -> bool {
+    return true;
+  }
+
+}
+
+pub struct FoolFuukaPostLinkResolver {
+  archive_domain: String
+}
+
+impl FoolFuukaPostLinkResolver {
+  fn is_archive_link(&self, domain_maybe: Option<&str>) -> bool {
+    return match domain_maybe {
+      // Relative links always point to the archive
+      None => true,
+      Some(domain) => domain.eq_ignore_ascii_case(&self.archive_domain)
+    };
+  }
+}
+
+impl PostLinkResolver for FoolFuukaPostLinkResolver {
+
+  fn link_raw_to_post_link(
+    &self,
+    post_raw: &PostRaw,
+    post_parser_context: &PostParserContext,
+    link_raw: &str
+  ) -> Result<PostLink, ParsingError> {
+    // "https://desuarchive.org/g/thread/1/#2" or "https://desuarchive.org/g/thread/1/" (thread without a post)
+    let thread_link_captures_maybe = THREAD_LINK_PATTERN.captures(link_raw);
+    if thread_link_captures_maybe.is_some() {
+      let captures = thread_link_captures_maybe.unwrap();
+
+      if self.is_archive_link(captures.get(1).map(|domain| domain.as_str())) {
+        let board_code = captures.get(2).unwrap().as_str();
+        let thread_no_result = captures.get(3).unwrap().as_str().parse::<u64>();
+        let post_no_result = captures.get(4)
+          .map(|post_no| post_no.as_str())
+          .unwrap_or_else(|| captures.get(3).unwrap().as_str())
+          .parse::<u64>();
+
+        if thread_no_result.is_ok() && post_no_result.is_ok() {
+          let thread_no = thread_no_result.unwrap();
+          let post_no = post_no_result.unwrap();
+
+          return Result::Ok(thread_post_link(post_raw, post_parser_context, board_code, thread_no, post_no));
+        }
+      }
+
+      // Fallthrough
+    }
+
+    // Board link: "https://desuarchive.org/g/"
+    let board_link_captures_maybe = BOARD_LINK_PATTERN.captures(link_raw);
+    if board_link_captures_maybe.is_some() {
+      let captures = board_link_captures_maybe.unwrap();
+
+      if self.is_archive_link(captures.get(1).map(|domain| domain.as_str())) {
+        let board_link = PostLink::BoardLink {
+          board_code: String::from(captures.get(2).unwrap().as_str())
+        };
+
+        return Result::Ok(board_link);
+      }
+
+      // Fallthrough
+    }
+
+    let url_link = PostLink::UrlLink {
+      link: String::from(link_raw),
+      url_kind: UrlKind::Generic
+    };
+
+    return Result::Ok(url_link);
+  }
+
+}
+
+#[test]
+fn test_link_raw_to_post_link() {
+  let post_parser_context = PostParserContext::new(HashSet::new(), set_of!(2));
+  let post_raw = PostRaw::new("desuarchive", "g", 1, 10, 0, "");
+  let resolver = FoolFuukaPostLinkResolver { archive_domain: String::from("desuarchive.org") };
+
+  assert_eq!(
+    PostLink::Quote { post_no: 2 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "https://desuarchive.org/g/thread/1/#2").unwrap()
+  );
+  assert_eq!(
+    PostLink::Dead { post_no: 3 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "https://www.desuarchive.org/g/thread/1/#q3").unwrap()
+  );
+  assert_eq!(
+    PostLink::ThreadLink { board_code: "a".to_string(), thread_no: 5, post_no: 5 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "//desuarchive.org/a/thread/5/").unwrap()
+  );
+  assert_eq!(
+    PostLink::BoardLink { board_code: "a".to_string() },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "https://desuarchive.org/a/").unwrap()
+  );
+  assert_eq!(
+    PostLink::UrlLink { link: "https://example.com/g/thread/1/#2".to_string(), url_kind: UrlKind::Generic },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "https://example.com/g/thread/1/#2").unwrap()
+  );
+}
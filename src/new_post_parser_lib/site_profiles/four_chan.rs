@@ -0,0 +1,272 @@
+use std::rc::Rc;
+use std::collections::HashSet;
+use regex::Regex;
+use crate::{empty_set, set_of, Attribute, ParsingRule, PostLink, PostParserContext, PostRaw, SpannableData, UrlKind};
+use crate::parsing_error::ParsingError;
+use crate::site_profiles::site_profile::{SiteProfile, PostLinkResolver, thread_post_link};
+use crate::rules::anchor::AnchorRuleHandler;
+use crate::rules::span::SpanHandler;
+use crate::rules::line_break::LineBreakRuleHandler;
+use crate::rules::spoiler::SpoilerHandler;
+use crate::rules::table_row::TableRowHandler;
+use crate::rules::table_data::TableDataHandler;
+use crate::rules::table::TableHandler;
+use crate::rules::bold::BoldHandler;
+use crate::rules::abbr::AbbrHandler;
+use crate::rules::style::StyleHandler;
+use crate::rules::pre::PreHandler;
+use crate::rules::text_style::TextStyleHandler;
+use crate::rules::block::block_rules;
+use crate::util::style_tag_value_decoder::smaller_font_size;
+use crate::util::theme_color_mapper::ThemeColorMapper;
+
+pub const SITE_NAME: &str = "4chan";
+
+lazy_static! {
+  static ref BOARD_LINK_PATTERN: Regex = Regex::new(r"^(?://[^/]+)?/(\w+)/$").unwrap();
+  static ref BOARD_LINK_WITH_SEARCH_PATTERN: Regex = Regex::new(r"^(?://[^/]+)?/(\w+)/catalog#s=(\w+)$").unwrap();
+  static ref CROSS_THREAD_LINK_PATTERN: Regex = Regex::new(r"^(?://[^/]+)?/(\w+)/thread/(\d+)(?:/[\w-]*)?(?:#p(\d+))?$").unwrap();
+}
+
+pub struct FourChanSiteProfile {
+  post_link_resolver: Rc<FourChanPostLinkResolver>
+}
+
+impl Default for FourChanSiteProfile {
+  fn default() -> Self {
+    return FourChanSiteProfile::new();
+  }
+}
+
+impl FourChanSiteProfile {
+  pub fn new() -> FourChanSiteProfile {
+    return FourChanSiteProfile {
+      post_link_resolver: Rc::new(FourChanPostLinkResolver {})
+    };
+  }
+}
+
+impl SiteProfile for FourChanSiteProfile {
+
+  fn site_name(&self) -> &str {
+    return SITE_NAME;
+  }
+
+  fn matching_rules(&self) -> Vec<ParsingRule> {
+    let mut rules = vec![
+      // Wildcard rules go first
+      ParsingRule::new("*", set_of!(Attribute::with_name("style")), Rc::new(StyleHandler::new())),
+
+      // Then go rules that require specific attributes
+      ParsingRule::new("span", set_of!(Attribute::with_class("abbr")), Rc::new(AbbrHandler::new())),
+      ParsingRule::new("pre", set_of!(Attribute::with_name_and_value("*", "prettyprint")), Rc::new(PreHandler::new())),
+
+      // Then go general rules for the whole tag
+      ParsingRule::new("span", empty_set!(), Rc::new(SpanHandler::new())),
+      ParsingRule::new("a", empty_set!(), Rc::new(AnchorRuleHandler::new(self.post_link_resolver()))),
+      ParsingRule::new("br", empty_set!(), Rc::new(LineBreakRuleHandler::new())),
+      ParsingRule::new("s", empty_set!(), Rc::new(SpoilerHandler::new())),
+      ParsingRule::new("tr", empty_set!(), Rc::new(TableRowHandler::new())),
+      ParsingRule::new("td", empty_set!(), Rc::new(TableDataHandler::new())),
+      ParsingRule::new("b", empty_set!(), Rc::new(BoldHandler::new())),
+      ParsingRule::new("strong", empty_set!(), Rc::new(BoldHandler::new())),
+      ParsingRule::new("table", empty_set!(), Rc::new(TableHandler::new())),
+      ParsingRule::new("i", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::ItalicText))),
+      ParsingRule::new("em", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::ItalicText))),
+      ParsingRule::new("u", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::UnderlineText))),
+      ParsingRule::new("ins", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::UnderlineText))),
+      // <s> is a spoiler on 4chan
+      ParsingRule::new("del", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::StrikethroughText))),
+      ParsingRule::new("strike", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::StrikethroughText))),
+      ParsingRule::new("sup", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::SuperscriptText))),
+      ParsingRule::new("sub", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::SubscriptText))),
+      ParsingRule::new("small", empty_set!(), Rc::new(TextStyleHandler::new(smaller_font_size()))),
+    ];
+
+    rules.extend(block_rules());
+    return rules;
+  }
+
+  fn replacement_rules(&self) -> Vec<(String, String)> {
+    return vec![
+      (String::from("<wbr>"), String::new())
+    ];
+  }
+
+  fn post_link_resolver(&self) -> Rc<dyn PostLinkResolver> {
+    return self.post_link_resolver.clone();
+  }
+
+  fn site_hosts(&self) -> Vec<String> {
+    return vec![
+      String::from("boards.4chan.org"),
+      String::from("boards.4channel.org"),
+      String::from("4chan.org"),
+      String::from("4channel.org")
+    ];
+  }
+
+  fn theme_color_mapper(&self) -> ThemeColorMapper {
+    return ThemeColorMapper::four_chan();
+  }
+
+}
+
+pub struct FourChanPostLinkResolver {}
+
+impl PostLinkResolver for FourChanPostLinkResolver {
+
+  fn link_raw_to_post_link(
+    &self,
+    post_raw: &PostRaw,
+    post_parser_context: &PostParserContext,
+    link_raw: &str
+  ) -> Result<PostLink, ParsingError> {
+    if let Some(quote_str) = link_raw.strip_prefix("#p") {
+      // Normal in-thread post quote: "#p333790203"
+      let post_no = match quote_str.parse::<u64>() {
+        Ok(post_no) => post_no,
+        Err(error) => {
+          return Result::Err(ParsingError::new(format!("invalid post_no in \"{}\", error: {}", link_raw, error)));
+        }
+      };
+
+      return if post_parser_context.is_internal_thread_post(post_no) {
+        Result::Ok(PostLink::Quote { post_no })
+      } else {
+        Result::Ok(PostLink::Dead { post_no })
+      }
+    }
+
+    // Board link: "//boards.4channel.org/jp/" or "/jp/"
+    let board_link_captures_maybe = BOARD_LINK_PATTERN.captures(link_raw);
+    if board_link_captures_maybe.is_some() {
+      let captures = board_link_captures_maybe.unwrap();
+
+      let board_code_maybe = captures.get(1);
+      if board_code_maybe.is_some() {
+        let board_code = board_code_maybe.unwrap().as_str();
+
+        let board_link = PostLink::BoardLink {
+          board_code: String::from(board_code)
+        };
+
+        return Result::Ok(board_link)
+      }
+
+      // Fallthrough
+    }
+
+    // Board link with search: "//boards.4channel.org/g/catalog#s=fglt"
+    let board_link_search_captures_maybe = BOARD_LINK_WITH_SEARCH_PATTERN.captures(link_raw);
+    if board_link_search_captures_maybe.is_some() {
+      let captures = board_link_search_captures_maybe.unwrap();
+
+      let board_code_maybe = captures.get(1);
+      let search_query_maybe = captures.get(2);
+
+      if board_code_maybe.is_some() && search_query_maybe.is_some() {
+        let board_code = board_code_maybe.unwrap().as_str();
+        let search_query = search_query_maybe.unwrap().as_str();
+
+        let search_link = PostLink::SearchLink {
+          board_code: String::from(board_code),
+          search_query: String::from(search_query)
+        };
+
+        return Result::Ok(search_link)
+      }
+
+      // Fallthrough
+    }
+
+    if link_raw.starts_with("/") {
+      // Cross-thread link: "/vg/thread/333581281#p333581281", "/g/thread/101/some-slug#p102" or
+      // "/g/thread/101" (thread without a post). A link to a post of the current thread is a quote.
+      let cross_thread_link_captures_maybe = CROSS_THREAD_LINK_PATTERN.captures(link_raw);
+      if cross_thread_link_captures_maybe.is_some() {
+        let captures = cross_thread_link_captures_maybe.unwrap();
+
+        let board_code = captures.get(1).unwrap().as_str();
+        let thread_no_str = captures.get(2).unwrap().as_str();
+        let post_no_str = captures.get(3)
+          .map(|post_no| post_no.as_str())
+          .unwrap_or(thread_no_str);
+
+        let thread_no_result = thread_no_str.parse::<u64>();
+        let post_no_result = post_no_str.parse::<u64>();
+
+        if thread_no_result.is_ok() && post_no_result.is_ok() {
+          let thread_no = thread_no_result.unwrap();
+          let post_no = post_no_result.unwrap();
+
+          return Result::Ok(thread_post_link(post_raw, post_parser_context, board_code, thread_no, post_no));
+        }
+
+        // Fallthrough
+      }
+    }
+
+    let thread_link = PostLink::UrlLink {
+      link: String::from(link_raw),
+      url_kind: UrlKind::Generic
+    };
+
+    return Result::Ok(thread_link);
+  }
+
+}
+
+#[test]
+fn test_link_raw_to_post_link() {
+  let post_parser_context = PostParserContext::new(HashSet::new(), set_of!(333790203));
+  let post_raw = PostRaw::new(SITE_NAME, "g", 333790200, 333790201, 0, "");
+  let resolver = FourChanPostLinkResolver {};
+
+  assert_eq!(
+    PostLink::Quote { post_no: 333790203 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "#p333790203").unwrap()
+  );
+  assert_eq!(
+    PostLink::Dead { post_no: 333790204 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "#p333790204").unwrap()
+  );
+  assert_eq!(
+    PostLink::BoardLink { board_code: "jp".to_string() },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "//boards.4channel.org/jp/").unwrap()
+  );
+  assert_eq!(
+    PostLink::SearchLink { board_code: "g".to_string(), search_query: "fglt".to_string() },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "//boards.4channel.org/g/catalog#s=fglt").unwrap()
+  );
+  assert_eq!(
+    PostLink::ThreadLink { board_code: "vg".to_string(), thread_no: 333581281, post_no: 333581282 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "/vg/thread/333581281#p333581282").unwrap()
+  );
+  assert_eq!(
+    PostLink::ThreadLink { board_code: "g".to_string(), thread_no: 101, post_no: 102 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "/g/thread/101/#p102").unwrap()
+  );
+  assert_eq!(
+    PostLink::ThreadLink { board_code: "g".to_string(), thread_no: 101, post_no: 101 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "//boards.4chan.org/g/thread/101/").unwrap()
+  );
+  assert_eq!(
+    PostLink::Quote { post_no: 333790203 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "/g/thread/333790200#p333790203").unwrap()
+  );
+  assert_eq!(
+    PostLink::Dead { post_no: 333790204 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "/g/thread/333790200#p333790204").unwrap()
+  );
+  assert_eq!(
+    PostLink::UrlLink { link: "/static/g/thread/101#p102".to_string(), url_kind: UrlKind::Generic },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "/static/g/thread/101#p102").unwrap()
+  );
+  assert_eq!(
+    PostLink::UrlLink { link: "https://example.com/".to_string(), url_kind: UrlKind::Generic },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "https://example.com/").unwrap()
+  );
+  assert!(resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "#p").is_err());
+  assert!(resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "#p99999999999999999999").is_err());
+}
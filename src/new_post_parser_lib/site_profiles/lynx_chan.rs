@@ -0,0 +1,69 @@
+use std::rc::Rc;
+use std::collections::HashSet;
+use crate::{empty_set, set_of, Attribute, ParsingRule, SpannableData};
+use crate::site_profiles::site_profile::{SiteProfile, PostLinkResolver};
+use crate::site_profiles::res_link_resolver::ResLinkResolver;
+use crate::rules::anchor::AnchorRuleHandler;
+use crate::rules::line_break::LineBreakRuleHandler;
+use crate::rules::style::StyleHandler;
+use crate::rules::text_style::TextStyleHandler;
+use crate::rules::block::block_rules;
+
+/// LynxChan is an engine that is used by many sites so the profile is created with the site name it
+/// should be used for and is not registered by default, see [PostParser::add_site_profile].
+pub struct LynxChanSiteProfile {
+  site_name: String,
+  post_link_resolver: Rc<ResLinkResolver>
+}
+
+impl LynxChanSiteProfile {
+  pub fn new(site_name: &str) -> LynxChanSiteProfile {
+    return LynxChanSiteProfile {
+      site_name: String::from(site_name),
+      post_link_resolver: Rc::new(ResLinkResolver {})
+    };
+  }
+}
+
+impl SiteProfile for LynxChanSiteProfile {
+
+  fn site_name(&self) -> &str {
+    return &self.site_name;
+  }
+
+  fn matching_rules(&self) -> Vec<ParsingRule> {
+    let mut rules = vec![
+      // Wildcard rules go first
+      ParsingRule::new("*", set_of!(Attribute::with_name("style")), Rc::new(StyleHandler::new())),
+
+      // Then go rules that require specific attributes
+      ParsingRule::new("span", set_of!(Attribute::with_class("greenText")), Rc::new(TextStyleHandler::new(SpannableData::GreenText))),
+      ParsingRule::new("span", set_of!(Attribute::with_class("redText")), Rc::new(TextStyleHandler::new(SpannableData::RedText))),
+      ParsingRule::new("span", set_of!(Attribute::with_class("orangeText")), Rc::new(TextStyleHandler::new(SpannableData::OrangeText))),
+      ParsingRule::new("span", set_of!(Attribute::with_class("spoiler")), Rc::new(TextStyleHandler::new(SpannableData::Spoiler))),
+      ParsingRule::new("span", set_of!(Attribute::with_class("aa")), Rc::new(TextStyleHandler::new(SpannableData::AsciiArt))),
+
+      // Then go general rules for the whole tag
+      ParsingRule::new("a", empty_set!(), Rc::new(AnchorRuleHandler::new(self.post_link_resolver()))),
+      ParsingRule::new("br", empty_set!(), Rc::new(LineBreakRuleHandler::new())),
+      ParsingRule::new("strong", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::BoldText))),
+      ParsingRule::new("em", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::ItalicText))),
+      ParsingRule::new("u", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::UnderlineText))),
+      // Unlike 4chan, <s> is a strikethrough here (spoilers use span.spoiler)
+      ParsingRule::new("s", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::StrikethroughText))),
+      ParsingRule::new("code", empty_set!(), Rc::new(TextStyleHandler::new(SpannableData::Monospace))),
+    ];
+
+    rules.extend(block_rules());
+    return rules;
+  }
+
+  fn replacement_rules(&self) -> Vec<(String, String)> {
+    return vec![];
+  }
+
+  fn post_link_resolver(&self) -> Rc<dyn PostLinkResolver> {
+    return self.post_link_resolver.clone();
+  }
+
+}
@@ -0,0 +1,105 @@
+use regex::Regex;
+use crate::{PostLink, PostParserContext, PostRaw, UrlKind};
+use crate::parsing_error::ParsingError;
+use crate::site_profiles::site_profile::{PostLinkResolver, thread_post_link};
+
+lazy_static! {
+  static ref THREAD_LINK_PATTERN: Regex = Regex::new(r"^/(\w+)/res/(\d+)\.html(?:#(\d+))?$").unwrap();
+  static ref BOARD_LINK_PATTERN: Regex = Regex::new(r"^/(\w+)/(?:index\.html)?$").unwrap();
+}
+
+/// Resolves links of the imageboard engines that address threads as "/{board_code}/res/{thread_no}.html"
+/// (2ch, LynxChan, vichan and its forks). Links to posts of the current thread contain the board and the
+/// thread too so they are compared against [PostRaw] to tell a quote from a cross-thread link.
+pub struct ResLinkResolver {}
+
+impl PostLinkResolver for ResLinkResolver {
+
+  fn link_raw_to_post_link(
+    &self,
+    post_raw: &PostRaw,
+    post_parser_context: &PostParserContext,
+    link_raw: &str
+  ) -> Result<PostLink, ParsingError> {
+    // "/b/res/262542405.html#262542501" or "/b/res/262542405.html" (thread without a post)
+    let thread_link_captures_maybe = THREAD_LINK_PATTERN.captures(link_raw);
+    if thread_link_captures_maybe.is_some() {
+      let captures = thread_link_captures_maybe.unwrap();
+
+      let board_code = captures.get(1).unwrap().as_str();
+      let thread_no_result = captures.get(2).unwrap().as_str().parse::<u64>();
+      let post_no_result = captures.get(3)
+        .map(|post_no| post_no.as_str())
+        .unwrap_or_else(|| captures.get(2).unwrap().as_str())
+        .parse::<u64>();
+
+      if thread_no_result.is_ok() && post_no_result.is_ok() {
+        let thread_no = thread_no_result.unwrap();
+        let post_no = post_no_result.unwrap();
+
+        return Result::Ok(thread_post_link(post_raw, post_parser_context, board_code, thread_no, post_no));
+      }
+
+      // Fallthrough
+    }
+
+    // Board link: "/po/" or "/po/index.html"
+    let board_link_captures_maybe = BOARD_LINK_PATTERN.captures(link_raw);
+    if board_link_captures_maybe.is_some() {
+      let board_code = board_link_captures_maybe.unwrap().get(1).unwrap().as_str();
+
+      let board_link = PostLink::BoardLink {
+        board_code: String::from(board_code)
+      };
+
+      return Result::Ok(board_link);
+    }
+
+    let url_link = PostLink::UrlLink {
+      link: String::from(link_raw),
+      url_kind: UrlKind::Generic
+    };
+
+    return Result::Ok(url_link);
+  }
+
+}
+
+#[test]
+fn test_link_raw_to_post_link() {
+  use std::collections::HashSet;
+  use crate::set_of;
+
+  let post_parser_context = PostParserContext::new(HashSet::new(), set_of!(262542501));
+  let post_raw = PostRaw::new("2ch.hk", "b", 262542405, 262542600, 0, "");
+  let resolver = ResLinkResolver {};
+
+  assert_eq!(
+    PostLink::Quote { post_no: 262542501 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "/b/res/262542405.html#262542501").unwrap()
+  );
+  assert_eq!(
+    PostLink::Dead { post_no: 262542502 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "/b/res/262542405.html#262542502").unwrap()
+  );
+  assert_eq!(
+    PostLink::ThreadLink { board_code: "b".to_string(), thread_no: 262540000, post_no: 262540000 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "/b/res/262540000.html").unwrap()
+  );
+  assert_eq!(
+    PostLink::ThreadLink { board_code: "po".to_string(), thread_no: 262542405, post_no: 262542501 },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "/po/res/262542405.html#262542501").unwrap()
+  );
+  assert_eq!(
+    PostLink::BoardLink { board_code: "po".to_string() },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "/po/").unwrap()
+  );
+  assert_eq!(
+    PostLink::BoardLink { board_code: "po".to_string() },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "/po/index.html").unwrap()
+  );
+  assert_eq!(
+    PostLink::UrlLink { link: "https://example.com/".to_string(), url_kind: UrlKind::Generic },
+    resolver.link_raw_to_post_link(&post_raw, &post_parser_context, "https://example.com/").unwrap()
+  );
+}
@@ -0,0 +1,142 @@
+use std::rc::Rc;
+use crate::{ParsingRule, PostLink, PostParserContext, PostRaw, CommentFormat, Element};
+use crate::parsing_error::ParsingError;
+use crate::util::theme_color_mapper::ThemeColorMapper;
+
+/// Everything that is specific to the html markup of one site. [PostParser] picks the profile by the
+/// [SiteDescriptor::site_name] of the post that is being parsed.
+pub trait SiteProfile {
+
+  /// Must be the same as [SiteDescriptor::site_name] of the posts this profile is supposed to parse
+  fn site_name(&self) -> &str;
+
+  /// Rules are checked in the order they are returned (wildcard rules should go first, then rules that
+  /// require specific attributes and then general rules for the whole tag).
+  fn matching_rules(&self) -> Vec<ParsingRule>;
+
+  /// (pattern, value) pairs. Every pattern is replaced with the value in the raw comment before it's parsed.
+  fn replacement_rules(&self) -> Vec<(String, String)>;
+
+  fn post_link_resolver(&self) -> Rc<dyn PostLinkResolver>;
+
+  /// Hosts the site is served from ("boards.4chan.org"). Absolute urls with these hosts are resolved by
+  /// [post_link_resolver] of this profile no matter what site the post that contains them belongs to.
+  fn site_hosts(&self) -> Vec<String> {
+    return vec![];
+  }
+
+  fn comment_format(&self) -> CommentFormat {
+    return CommentFormat::Html;
+  }
+
+  /// Whether ">>123", ">>>/g/123" and ">>>/g/" written as plain text (not inside of an <a> tag) should be
+  /// converted into links
+  fn detect_plain_text_quotes(&self) -> bool {
+    return false;
+  }
+
+  /// Maps the colors that the site hard-codes into the comments and the classes of its elements to theme
+  /// color ids. Maps nothing by default.
+  fn theme_color_mapper(&self) -> ThemeColorMapper {
+    return ThemeColorMapper::new();
+  }
+
+}
+
+pub trait PostLinkResolver {
+
+  /// Converts "href" attribute value of a link into a [PostLink]. Links that do not point to anything on
+  /// the site are returned as [PostLink::UrlLink]. [post_raw] is the post that contains the link.
+  fn link_raw_to_post_link(
+    &self,
+    post_raw: &PostRaw,
+    post_parser_context: &PostParserContext,
+    link_raw: &str
+  ) -> Result<PostLink, ParsingError>;
+
+  /// Converts an <a> [element] with "href" [link_raw] into a [PostLink]. Only the "href" is used by
+  /// default, sites that put the thread and the post into other attributes of the link override this.
+  fn link_element_to_post_link(
+    &self,
+    post_raw: &PostRaw,
+    post_parser_context: &PostParserContext,
+    _element: &Element,
+    link_raw: &str
+  ) -> Result<PostLink, ParsingError> {
+    return self.link_raw_to_post_link(post_raw, post_parser_context, link_raw);
+  }
+
+}
+
+/// For links that always contain the board and the thread (even when they point to a post of the current
+/// thread). Returns [PostLink::Quote]/[PostLink::Dead] when the link points to the thread of [post_raw] and
+/// [PostLink::ThreadLink] otherwise.
+pub fn thread_post_link(
+  post_raw: &PostRaw,
+  post_parser_context: &PostParserContext,
+  board_code: &str,
+  thread_no: u64,
+  post_no: u64
+) -> PostLink {
+  if board_code == post_raw.board_code() && thread_no == post_raw.thread_no() {
+    return if post_parser_context.is_internal_thread_post(post_no) {
+      PostLink::Quote { post_no }
+    } else {
+      PostLink::Dead { post_no }
+    }
+  }
+
+  return PostLink::ThreadLink {
+    board_code: String::from(board_code),
+    thread_no,
+    post_no
+  };
+}
+
+/// "https://www.Boards.4chan.org:443/g/thread/1#p2" -> ("boards.4chan.org", "/g/thread/1#p2"). Only http(s)
+/// and protocol-relative ("//") urls have a host. The path is "/" when the url has none.
+pub fn split_absolute_url(link: &str) -> Option<(String, String)> {
+  let without_scheme = link.strip_prefix("https:")
+    .or_else(|| link.strip_prefix("http:"))
+    .unwrap_or(link);
+
+  let authority_with_path = without_scheme.strip_prefix("//")?;
+  let authority_end = authority_with_path
+    .find(['/', '?', '#'])
+    .unwrap_or(authority_with_path.len());
+
+  let authority = &authority_with_path[..authority_end];
+  let mut path = String::from(&authority_with_path[authority_end..]);
+  if !path.starts_with('/') {
+    path.insert(0, '/');
+  }
+
+  // Strip the credentials and the port
+  let host = authority.rsplit('@').next().unwrap_or(authority);
+  let host = host.split(':').next().unwrap_or(host).to_lowercase();
+  let host = host.strip_prefix("www.").map(String::from).unwrap_or(host);
+
+  if host.is_empty() {
+    return Option::None;
+  }
+
+  return Option::Some((host, path));
+}
+
+#[test]
+fn test_split_absolute_url() {
+  assert_eq!(
+    Option::Some((String::from("boards.4chan.org"), String::from("/g/thread/1#p2"))),
+    split_absolute_url("https://www.Boards.4chan.org:443/g/thread/1#p2")
+  );
+  assert_eq!(
+    Option::Some((String::from("2ch.hk"), String::from("/b/res/1.html#2"))),
+    split_absolute_url("//2ch.hk/b/res/1.html#2")
+  );
+  assert_eq!(
+    Option::Some((String::from("example.com"), String::from("/?q=1"))),
+    split_absolute_url("http://user@example.com?q=1")
+  );
+  assert_eq!(Option::None, split_absolute_url("/g/thread/1#p2"));
+  assert_eq!(Option::None, split_absolute_url("#p2"));
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use crate::parsing_error::ParsingError;
 
 lazy_static! {
   static ref COLOR_MAP: HashMap<&'static str, &'static str> = {
@@ -162,31 +163,221 @@ pub fn color_name_to_color_hex(color_name: &str) -> Option<String> {
     .map(|color_hex_str| color_hex_str.to_string());
 }
 
+/// A color with straight (not premultiplied) alpha
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RgbaColor {
+  pub red: u8,
+  pub green: u8,
+  pub blue: u8,
+  pub alpha: u8
+}
+
+impl RgbaColor {
+  pub fn new(red: u8, green: u8, blue: u8, alpha: u8) -> RgbaColor {
+    return RgbaColor { red, green, blue, alpha };
+  }
+
+  /// The canonical "#RRGGBBAA" form (uppercase) that the color spannables carry
+  pub fn to_hex(&self) -> String {
+    return format!("#{:02X}{:02X}{:02X}{:02X}", self.red, self.green, self.blue, self.alpha);
+  }
+}
+
+/// Parses a CSS color: names ("red", "transparent"), "#RGB", "#RGBA", "#RRGGBB", "#RRGGBBAA", "rgb()",
+/// "rgba()", "hsl()" and "hsla()" in both the comma ("rgb(255, 0, 0)") and the space
+/// ("rgb(255 0 0 / 50%)") syntax. Channels out of range are clamped like the browsers do.
+pub fn parse_css_color(value: &str) -> Result<RgbaColor, ParsingError> {
+  let value = value.trim();
+
+  if let Option::Some(hex) = value.strip_prefix('#') {
+    return parse_hex_color(hex).ok_or_else(|| ParsingError::new(format!("invalid hex color \"{}\"", value)));
+  }
+
+  if let Option::Some((function_name, arguments)) = split_color_function(value) {
+    let components = split_color_components(arguments)
+      .ok_or_else(|| ParsingError::new(format!("invalid arguments of \"{}\"", value)))?;
+
+    let color_maybe = match function_name.as_str() {
+      "rgb" | "rgba" => parse_rgb_components(&components),
+      "hsl" | "hsla" => parse_hsl_components(&components),
+      _ => return Result::Err(ParsingError::new(format!("unsupported color function \"{}\"", function_name)))
+    };
+
+    return color_maybe.ok_or_else(|| ParsingError::new(format!("invalid color \"{}\"", value)));
+  }
+
+  if value.eq_ignore_ascii_case("transparent") {
+    return Result::Ok(RgbaColor::new(0, 0, 0, 0));
+  }
+
+  return color_name_to_color_hex(value)
+    .and_then(|color_hex| parse_hex_color(&color_hex[1..]))
+    .ok_or_else(|| ParsingError::new(format!("unknown color \"{}\"", value)));
+}
+
+/// "#" followed by 3, 4, 6 or 8 hex digits
 pub fn is_color_hex(color_hex_maybe: &str) -> bool {
-  if color_hex_maybe.is_empty() {
-    return false;
+  return color_hex_maybe
+    .strip_prefix('#')
+    .and_then(parse_hex_color)
+    .is_some();
+}
+
+fn parse_hex_color(hex: &str) -> Option<RgbaColor> {
+  if !hex.chars().all(|ch| ch.is_ascii_hexdigit()) {
+    return Option::None;
+  }
+
+  let hex = match hex.len() {
+    3 | 4 => hex.chars().flat_map(|ch| [ch, ch]).collect::<String>(),
+    6 | 8 => hex.to_string(),
+    _ => return Option::None
+  };
+
+  let channel = |index: usize| u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok();
+  let alpha = if hex.len() == 8 { channel(3)? } else { u8::MAX };
+
+  return Option::Some(RgbaColor::new(channel(0)?, channel(1)?, channel(2)?, alpha));
+}
+
+/// "RGB(1, 2, 3)" -> ("rgb", "1, 2, 3")
+fn split_color_function(value: &str) -> Option<(String, &str)> {
+  let arguments_start = value.find('(')?;
+  let arguments = value[arguments_start + 1..].strip_suffix(')')?;
+  let function_name = value[..arguments_start].trim().to_lowercase();
+
+  if function_name.is_empty() || !function_name.chars().all(|ch| ch.is_ascii_alphabetic()) {
+    return Option::None;
   }
 
-  let mut has_number_sign = false;
+  return Option::Some((function_name, arguments));
+}
 
-  for character in color_hex_maybe.chars() {
-    if character == '#' {
-      has_number_sign = true;
-      continue;
+/// "1, 2, 3, 0.5" or "1 2 3 / 0.5" -> ["1", "2", "3", "0.5"]. The syntaxes can't be mixed.
+fn split_color_components(arguments: &str) -> Option<Vec<&str>> {
+  let components = if arguments.contains(',') {
+    if arguments.contains('/') {
+      return Option::None;
     }
 
-    if character.is_digit(16) {
-      continue;
+    arguments.split(',').map(|component| component.trim()).collect::<Vec<&str>>()
+  } else {
+    let (channels, alpha) = match arguments.split_once('/') {
+      Some((channels, alpha)) => (channels, Option::Some(alpha.trim())),
+      None => (arguments, Option::None)
+    };
+
+    let mut components = channels.split_whitespace().collect::<Vec<&str>>();
+    if components.len() != 3 && alpha.is_some() {
+      return Option::None;
     }
 
-    return false;
+    components.extend(alpha);
+    components
+  };
+
+  if components.len() != 3 && components.len() != 4 {
+    return Option::None;
+  }
+
+  if components.iter().any(|component| component.is_empty()) {
+    return Option::None;
+  }
+
+  return Option::Some(components);
+}
+
+fn parse_rgb_components(components: &[&str]) -> Option<RgbaColor> {
+  let channel = |component: &str| -> Option<u8> {
+    let value = match component.strip_suffix('%') {
+      Some(percentage) => percentage.parse::<f64>().ok()? * 255.0 / 100.0,
+      None => component.parse::<f64>().ok()?
+    };
+
+    return Option::Some(to_channel(value));
+  };
+
+  return Option::Some(RgbaColor::new(
+    channel(components[0])?,
+    channel(components[1])?,
+    channel(components[2])?,
+    parse_alpha(components.get(3).copied())?
+  ));
+}
+
+fn parse_hsl_components(components: &[&str]) -> Option<RgbaColor> {
+  let hue = parse_hue(components[0])?;
+  let saturation = parse_percentage(components[1])?.clamp(0.0, 1.0);
+  let lightness = parse_percentage(components[2])?.clamp(0.0, 1.0);
+
+  // https://www.w3.org/TR/css-color-4/#hsl-to-rgb
+  let hue_to_channel = |offset: f64| -> u8 {
+    let k = (offset + hue / 30.0) % 12.0;
+    let a = saturation * lightness.min(1.0 - lightness);
+
+    return to_channel((lightness - a * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)) * 255.0);
+  };
+
+  return Option::Some(RgbaColor::new(
+    hue_to_channel(0.0),
+    hue_to_channel(8.0),
+    hue_to_channel(4.0),
+    parse_alpha(components.get(3).copied())?
+  ));
+}
+
+/// Degrees in 0..360. "120", "120deg", "0.5turn", "3.14rad", "200grad"
+fn parse_hue(component: &str) -> Option<f64> {
+  let component = component.to_lowercase();
+
+  let degrees = if let Option::Some(value) = component.strip_suffix("deg") {
+    value.parse::<f64>().ok()?
+  } else if let Option::Some(value) = component.strip_suffix("grad") {
+    value.parse::<f64>().ok()? * 0.9
+  } else if let Option::Some(value) = component.strip_suffix("rad") {
+    value.parse::<f64>().ok()?.to_degrees()
+  } else if let Option::Some(value) = component.strip_suffix("turn") {
+    value.parse::<f64>().ok()? * 360.0
+  } else {
+    component.parse::<f64>().ok()?
+  };
+
+  if !degrees.is_finite() {
+    return Option::None;
+  }
+
+  return Option::Some(degrees.rem_euclid(360.0));
+}
+
+/// "50%" -> 0.5. The space syntax allows plain numbers too ("hsl(120 50 50)").
+fn parse_percentage(component: &str) -> Option<f64> {
+  let value = component.strip_suffix('%').unwrap_or(component).parse::<f64>().ok()?;
+  if !value.is_finite() {
+    return Option::None;
   }
 
-  if !has_number_sign {
-    return false;
+  return Option::Some(value / 100.0);
+}
+
+/// "0.5" or "50%", no alpha means opaque
+fn parse_alpha(component: Option<&str>) -> Option<u8> {
+  let alpha = match component {
+    None => return Option::Some(u8::MAX),
+    Some(component) => match component.strip_suffix('%') {
+      Some(percentage) => percentage.parse::<f64>().ok()? / 100.0,
+      None => component.parse::<f64>().ok()?
+    }
+  };
+
+  return Option::Some(to_channel(alpha * 255.0));
+}
+
+fn to_channel(value: f64) -> u8 {
+  if value.is_nan() {
+    return 0;
   }
 
-  return true;
+  return value.round().clamp(0.0, 255.0) as u8;
 }
 
 #[test]
@@ -203,4 +394,48 @@ fn test_is_color_hex() {
   assert!(!is_color_hex("f5dEB3"));
   assert!(!is_color_hex("f5dEB3Z"));
   assert!(!is_color_hex(""));
-}
\ No newline at end of file
+  assert!(is_color_hex("#F00"));
+  assert!(is_color_hex("#F00A"));
+  assert!(is_color_hex("#F5DEB3CC"));
+  assert!(!is_color_hex("#"));
+  assert!(!is_color_hex("12#34"));
+  assert!(!is_color_hex("#F5DEB"));
+}
+
+#[test]
+fn test_parse_css_color() {
+  let test_cases = vec![
+    ("red", "#FF0000FF"),
+    ("DarkGreen", "#006400FF"),
+    ("transparent", "#00000000"),
+    ("#fd4d32", "#FD4D32FF"),
+    ("#F00", "#FF0000FF"),
+    ("#F008", "#FF000088"),
+    ("#fd4d3280", "#FD4D3280"),
+    ("rgb(255, 0, 0)", "#FF0000FF"),
+    ("RGBA(255,0,0,0.5)", "#FF000080"),
+    ("rgb(255 0 0 / 50%)", "#FF000080"),
+    ("rgb(100%, 50%, 0%)", "#FF8000FF"),
+    ("rgb(300, -20, 0)", "#FF0000FF"),
+    ("rgba(0, 0, 0, 2)", "#000000FF"),
+    ("hsl(0, 100%, 50%)", "#FF0000FF"),
+    ("hsl(120deg 100% 25%)", "#008000FF"),
+    ("hsla(240, 100%, 50%, 0.5)", "#0000FF80"),
+    ("hsl(0.5turn 100% 50%)", "#00FFFFFF"),
+    ("hsl(-120, 100%, 50%)", "#0000FFFF"),
+    ("hsl(0, 0%, 100%)", "#FFFFFFFF"),
+  ];
+
+  for (value, expected_color_hex) in test_cases {
+    assert_eq!(expected_color_hex, parse_css_color(value).unwrap().to_hex(), "value: {}", value);
+  }
+
+  let invalid_colors = vec![
+    "", "#", "#12", "#12345", "#GGGGGG", "12#34", "notacolor", "rgb(1, 2)", "rgb(1, 2, 3, 4, 5)",
+    "rgb(1 2 3, 4)", "rgb(1, 2, 3 / 4)", "rgb(a, b, c)", "rgb(1, 2, 3", "cmyk(1, 2, 3, 4)", "hsl(red, 1%, 1%)",
+  ];
+
+  for value in invalid_colors {
+    assert!(parse_css_color(value).is_err(), "value: {}", value);
+  }
+}
@@ -0,0 +1,131 @@
+use crate::util::url_scanner::{UrlMatch, is_domain};
+use crate::util::helpers::percent_decode;
+
+const MAILTO_PREFIX: &str = "mailto:";
+// Only the characters people actually use, the full RFC 5322 set ("!#$&'*/=?^`{|}~") matches too much of
+// the usual text around the "@"
+const LOCAL_PART_SPECIAL_CHARS: [char; 5] = ['.', '_', '%', '+', '-'];
+const MAX_LOCAL_PART_LENGTH: usize = 64;
+
+/// [start] and [end] are in bytes. [email] is normalized (the domain is lowercase).
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmailMatch {
+  pub start: usize,
+  pub end: usize,
+  pub email: String
+}
+
+/// Finds email addresses ("user.name+tag@example.com") in plain text. Addresses that overlap with
+/// [url_matches] ("https://user@example.com") are skipped.
+pub fn find_emails(text: &str, url_matches: &[UrlMatch]) -> Vec<EmailMatch> {
+  let mut email_matches: Vec<EmailMatch> = Vec::new();
+
+  for (at_index, _) in text.match_indices('@') {
+    let email_match_maybe = match_email_at(text, at_index);
+    if email_match_maybe.is_none() {
+      continue;
+    }
+
+    let email_match = email_match_maybe.unwrap();
+
+    let overlaps_url = url_matches
+      .iter()
+      .any(|url_match| email_match.start < url_match.end && email_match.end > url_match.start);
+
+    if !overlaps_url {
+      email_matches.push(email_match);
+    }
+  }
+
+  return email_matches;
+}
+
+/// "mailto:User@Example.com?subject=hi" -> "User@example.com". Hrefs with several addresses are not
+/// supported.
+pub fn parse_mailto(href: &str) -> Option<String> {
+  let address = href.trim().get(..MAILTO_PREFIX.len())
+    .filter(|prefix| prefix.eq_ignore_ascii_case(MAILTO_PREFIX))
+    .map(|_| &href.trim()[MAILTO_PREFIX.len()..])?;
+
+  let address = address.split('?').next().unwrap_or(address);
+  // "+" is a valid part of an address here, not an encoded space
+  let address = percent_decode(&address.replace('+', "%2B"));
+
+  let (local_part, domain) = address.rsplit_once('@')?;
+  return normalize_email(local_part, domain);
+}
+
+fn match_email_at(text: &str, at_index: usize) -> Option<EmailMatch> {
+  let local_part_length = text[..at_index]
+    .chars()
+    .rev()
+    .take_while(|ch| is_local_part_char(*ch))
+    .map(|ch| ch.len_utf8())
+    .sum::<usize>();
+
+  // "...user@example.com" at the start of a sentence
+  let local_part = text[at_index - local_part_length..at_index].trim_start_matches('.');
+  let start = at_index - local_part.len();
+
+  // "テストuser@example.com" or "a@user@example.com" are not emails
+  let is_glued_to_text = text[..start]
+    .chars()
+    .last()
+    .map(|ch| ch.is_alphanumeric() || ch == '@')
+    .unwrap_or(false);
+
+  if is_glued_to_text {
+    return Option::None;
+  }
+
+  let domain_start = at_index + 1;
+  let domain_length = text[domain_start..]
+    .chars()
+    .take_while(|ch| ch.is_alphanumeric() || *ch == '-' || *ch == '.')
+    .map(|ch| ch.len_utf8())
+    .sum::<usize>();
+
+  // "mail me at user@example.com."
+  let domain = text[domain_start..domain_start + domain_length].trim_end_matches('.');
+  let end = domain_start + domain.len();
+
+  if text[end..].starts_with('@') {
+    return Option::None;
+  }
+
+  let email = normalize_email(local_part, domain)?;
+  return Option::Some(EmailMatch { start, end, email });
+}
+
+fn normalize_email(local_part: &str, domain: &str) -> Option<String> {
+  let is_valid_local_part = !local_part.is_empty()
+    && local_part.len() <= MAX_LOCAL_PART_LENGTH
+    && local_part.chars().all(is_local_part_char)
+    && !local_part.starts_with('.')
+    && !local_part.ends_with('.')
+    && !local_part.contains("..");
+
+  let is_valid_domain = domain.chars().all(|ch| ch.is_alphanumeric() || ch == '-' || ch == '.')
+    && is_domain(domain);
+
+  if !is_valid_local_part || !is_valid_domain {
+    return Option::None;
+  }
+
+  // The local part is case-sensitive in theory so only the domain is normalized
+  return Option::Some(format!("{}@{}", local_part, domain.to_lowercase()));
+}
+
+fn is_local_part_char(ch: char) -> bool {
+  return ch.is_ascii_alphanumeric() || LOCAL_PART_SPECIAL_CHARS.contains(&ch);
+}
+
+#[test]
+fn test_parse_mailto() {
+  assert_eq!(Option::Some("User@example.com".to_string()), parse_mailto("mailto:User@Example.COM"));
+  assert_eq!(Option::Some("user+tag@example.com".to_string()), parse_mailto("MAILTO:user+tag@example.com?subject=Hi"));
+  assert_eq!(Option::Some("user@example.com".to_string()), parse_mailto("mailto:user%40example.com"));
+  assert_eq!(Option::None, parse_mailto("mailto:a@example.com,b@example.com"));
+  assert_eq!(Option::None, parse_mailto("mailto:user@localhost"));
+  assert_eq!(Option::None, parse_mailto("https://example.com"));
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use crate::html_parser::node::Node;
+use crate::{Element, UrlKind};
+use crate::util::url_classifier::UrlClassifierRegistry;
+
+/// Elements that browser extensions (4chanX etc) inject into the comments, like the
+/// "<a class="embedder" data-key="Twitter" ...>(<span>un</span>embed)</a>" buttons. They are not a part
+/// of the comment that the author wrote.
+const EXTENSION_INJECTED_CLASSES: [&str; 1] = ["embedder"];
+
+const TAG_A: &str = "a";
+const HREF_ATTR: &str = "href";
+const DATA_KEY_ATTR: &str = "data-key";
+const DATA_UID_ATTR: &str = "data-uid";
+const DATA_HREF_ATTR: &str = "data-href";
+
+/// Removes extension-injected elements from [nodes] (recursively). Embedders that follow a link are
+/// converted into the [UrlKind] of that link, the result is ([Key] href of the link, [Value] url kind).
+pub fn strip_extension_markup(
+  nodes: &mut Vec<Node>,
+  url_classifier_registry: &UrlClassifierRegistry
+) -> HashMap<String, UrlKind> {
+  let mut embeds: HashMap<String, UrlKind> = HashMap::new();
+  strip_extension_markup_internal(nodes, url_classifier_registry, &mut embeds);
+
+  return embeds;
+}
+
+fn strip_extension_markup_internal(
+  nodes: &mut Vec<Node>,
+  url_classifier_registry: &UrlClassifierRegistry,
+  out_embeds: &mut HashMap<String, UrlKind>
+) {
+  let mut index = 0;
+
+  while index < nodes.len() {
+    let is_injected = match &nodes[index] {
+      Node::Element(element) => is_extension_injected(element),
+      Node::Text(_) => false
+    };
+
+    if !is_injected {
+      if let Node::Element(element) = &mut nodes[index] {
+        strip_extension_markup_internal(&mut element.children, url_classifier_registry, out_embeds);
+      }
+
+      index += 1;
+      continue;
+    }
+
+    let injected_node = nodes.remove(index);
+
+    if let (Node::Element(embedder), Option::Some(link_href)) = (&injected_node, prev_link_href(nodes, index)) {
+      if let Option::Some(url_kind) = embed_url_kind(embedder, url_classifier_registry) {
+        out_embeds.insert(link_href, url_kind);
+      }
+    }
+  }
+}
+
+fn is_extension_injected(element: &Element) -> bool {
+  return EXTENSION_INJECTED_CLASSES
+    .iter()
+    .any(|class_name| element.has_class(class_name));
+}
+
+/// Href of the link the embedder at [embedder_index] belongs to (the link that goes right before it, the
+/// whitespace between them is skipped)
+fn prev_link_href(nodes: &[Node], embedder_index: usize) -> Option<String> {
+  let prev_node = nodes[..embedder_index]
+    .iter()
+    .rev()
+    .find(|node| !matches!(node, Node::Text(text) if text.trim().is_empty()))?;
+
+  return match prev_node {
+    Node::Element(element) if element.tag_name == TAG_A => element.get_attr_value(HREF_ATTR).cloned(),
+    _ => Option::None
+  };
+}
+
+/// data-href="https://twitter.com/user/status/1" is classified as usual, unknown embeds
+/// (data-key="Vocaroo" data-uid="abc") become [UrlKind::Custom]
+fn embed_url_kind(embedder: &Element, url_classifier_registry: &UrlClassifierRegistry) -> Option<UrlKind> {
+  if let Option::Some(data_href) = embedder.get_attr_value(DATA_HREF_ATTR) {
+    let url_kind = url_classifier_registry.classify(data_href);
+    if url_kind != UrlKind::Generic {
+      return Option::Some(url_kind);
+    }
+  }
+
+  let data_key = embedder.get_attr_value(DATA_KEY_ATTR)?;
+  let data_uid = embedder.get_attr_value(DATA_UID_ATTR)?;
+
+  let custom_url_kind = UrlKind::Custom {
+    kind: data_key.to_lowercase(),
+    id: data_uid.to_string()
+  };
+
+  return Option::Some(custom_url_kind);
+}
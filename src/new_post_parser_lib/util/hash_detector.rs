@@ -0,0 +1,180 @@
+use crate::{Spannable, SpannableData, HashAlgorithm};
+use crate::util::url_scanner::UrlMatch;
+use crate::util::helpers::percent_decode;
+
+const MAGNET_PREFIX: &str = "magnet:?";
+const BTIH_URN_PREFIX: &str = "urn:btih:";
+
+const IPFS_CID_V0_PREFIX: &str = "Qm";
+const IPFS_CID_V0_LENGTH: usize = 46;
+const IPFS_CID_V1_PREFIXES: [&str; 2] = ["bafy", "bafk"];
+const IPFS_CID_V1_MIN_LENGTH: usize = 50;
+const BASE58_ALPHABET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Adds [SpannableData::Magnet] for every magnet uri in [url_matches] (the urls found in [text]) and
+/// [SpannableData::Hash] for every bare hash (md5, BTIH, sha256, sha512) or IPFS CID in [text] outside
+/// of the urls. [text_offset] is the amount of unicode characters that go before [text].
+pub fn detect_magnets_and_hashes(
+  text: &str,
+  url_matches: &[UrlMatch],
+  text_offset: usize,
+  out_spannables: &mut Vec<Spannable>
+) {
+  for url_match in url_matches {
+    let magnet_maybe = parse_magnet(&url_match.url);
+    if magnet_maybe.is_none() {
+      continue;
+    }
+
+    push_spannable(text, url_match.start, url_match.end, text_offset, magnet_maybe.unwrap(), out_spannables);
+  }
+
+  for (start, end) in find_words(text) {
+    let is_inside_of_url = url_matches
+      .iter()
+      .any(|url_match| start < url_match.end && end > url_match.start);
+
+    if is_inside_of_url {
+      continue;
+    }
+
+    let value = &text[start..end];
+    if let Option::Some(algorithm) = hash_algorithm(value) {
+      let hash = SpannableData::Hash { algorithm, value: String::from(value) };
+      push_spannable(text, start, end, text_offset, hash, out_spannables);
+    }
+  }
+}
+
+fn push_spannable(
+  text: &str,
+  start: usize,
+  end: usize,
+  text_offset: usize,
+  spannable_data: SpannableData,
+  out_spannables: &mut Vec<Spannable>
+) {
+  // [start] and [end] are in bytes but spannables use unicode characters
+  let spannable = Spannable {
+    start: text_offset + text[..start].chars().count(),
+    len: text[start..end].chars().count(),
+    spannable_data
+  };
+
+  if spannable.is_valid() {
+    out_spannables.push(spannable);
+  }
+}
+
+/// "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a&dn=Some+File" ->
+/// Magnet { info_hash: "c12fe1c06bba254a9dc9f519b335aa7c1367a88a", display_name: "Some File" }
+fn parse_magnet(url: &str) -> Option<SpannableData> {
+  let params = url.get(..MAGNET_PREFIX.len())
+    .filter(|prefix| prefix.eq_ignore_ascii_case(MAGNET_PREFIX))
+    .map(|_| &url[MAGNET_PREFIX.len()..])?;
+
+  let mut info_hash: Option<String> = Option::None;
+  let mut display_name: Option<String> = Option::None;
+
+  for (name, value) in params.split('&').filter_map(|param| param.split_once('=')) {
+    match name {
+      "xt" => {
+        let is_btih = value.get(..BTIH_URN_PREFIX.len())
+          .map(|prefix| prefix.eq_ignore_ascii_case(BTIH_URN_PREFIX))
+          .unwrap_or(false);
+
+        if is_btih && info_hash.is_none() {
+          info_hash = Option::Some(String::from(&value[BTIH_URN_PREFIX.len()..]));
+        }
+      }
+      "dn" => {
+        display_name = Option::Some(percent_decode(value));
+      }
+      _ => {}
+    }
+  }
+
+  let info_hash = info_hash?;
+
+  // 40 hex chars or 32 base32 chars
+  let is_valid_info_hash = (info_hash.len() == 40 && info_hash.chars().all(|ch| ch.is_ascii_hexdigit()))
+    || (info_hash.len() == 32 && info_hash.chars().all(|ch| matches!(ch.to_ascii_uppercase(), 'A'..='Z' | '2'..='7')));
+
+  if !is_valid_info_hash {
+    return Option::None;
+  }
+
+  return Option::Some(SpannableData::Magnet { info_hash, display_name });
+}
+
+/// Bare 40 hex chars may as well be sha1 but on imageboards they are almost always torrent info hashes.
+/// Runs of only digits are long numbers, not hashes.
+fn hash_algorithm(value: &str) -> Option<HashAlgorithm> {
+  let is_hex = value.chars().all(|ch| ch.is_ascii_hexdigit())
+    && value.chars().any(|ch| ch.is_ascii_alphabetic());
+
+  if is_hex {
+    return match value.len() {
+      32 => Option::Some(HashAlgorithm::Md5),
+      40 => Option::Some(HashAlgorithm::Btih),
+      64 => Option::Some(HashAlgorithm::Sha256),
+      128 => Option::Some(HashAlgorithm::Sha512),
+      _ => Option::None
+    };
+  }
+
+  let is_cid_v0 = value.len() == IPFS_CID_V0_LENGTH
+    && value.starts_with(IPFS_CID_V0_PREFIX)
+    && value.chars().all(|ch| BASE58_ALPHABET.contains(ch));
+
+  let is_cid_v1 = value.len() >= IPFS_CID_V1_MIN_LENGTH
+    && IPFS_CID_V1_PREFIXES.iter().any(|prefix| value.starts_with(prefix))
+    && value.chars().all(|ch| matches!(ch, 'a'..='z' | '2'..='7'));
+
+  if is_cid_v0 || is_cid_v1 {
+    return Option::Some(HashAlgorithm::IpfsCid);
+  }
+
+  return Option::None;
+}
+
+/// (start, end) in bytes of every run of ascii letters and digits that is not a part of a longer word
+/// ("abc_def" or "テストabc" have no words)
+fn find_words(text: &str) -> Vec<(usize, usize)> {
+  let mut words: Vec<(usize, usize)> = Vec::new();
+  let mut word_start: Option<usize> = Option::None;
+  let mut prev_char: Option<char> = Option::None;
+
+  for (index, ch) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+    if ch.is_ascii_alphanumeric() {
+      if word_start.is_none() && !prev_char.map(is_word_char).unwrap_or(false) {
+        word_start = Option::Some(index);
+      }
+    } else {
+      if let Option::Some(start) = word_start {
+        if !is_word_char(ch) {
+          words.push((start, index));
+        }
+      }
+
+      word_start = Option::None;
+    }
+
+    prev_char = Option::Some(ch);
+  }
+
+  return words;
+}
+
+fn is_word_char(ch: char) -> bool {
+  return ch.is_alphanumeric() || ch == '_';
+}
+
+#[test]
+fn test_find_words() {
+  assert_eq!(vec![(0, 3), (4, 7)], find_words("abc def"));
+  assert_eq!(vec![(1, 4)], find_words("(abc)"));
+  assert_eq!(Vec::<(usize, usize)>::new(), find_words("テストabc"));
+  assert_eq!(Vec::<(usize, usize)>::new(), find_words("ab_cde"));
+  assert_eq!(vec![(4, 7)], find_words("ab_ cde"));
+}
@@ -9,7 +9,7 @@ impl<T> SumBy<T> for Iter<'_, T> {
     let mut sum: i32 = 0;
 
     for element in self.as_slice() {
-      sum += func(&element);
+      sum += func(element);
     }
 
     return sum;
@@ -32,6 +32,7 @@ impl<T> LastIndex<T> for Iter<'_, T> {
 
 pub trait MapJoin<T> {
   fn map_join(&self, mapper: &dyn Fn(&T) -> &str) -> String;
+  /// [separator] goes between the elements, not after the last one
   fn map_join_cap(&self, capacity: usize, separator: &str, mapper: &dyn Fn(&T) -> &str) -> String;
 }
 
@@ -42,19 +43,74 @@ impl<T> MapJoin<T> for Iter<'_, T> {
 
   fn map_join_cap(&self, capacity: usize, separator: &str, mapper: &dyn Fn(&T) -> &str) -> String {
     let mut result_string =  String::with_capacity(capacity);
-    let mut index = 0;
     let count = self.len();
 
-    for element in self.as_slice() {
+    for (index, element) in self.as_slice().iter().enumerate() {
       result_string.push_str(mapper(element));
 
-      if index < count {
+      if index + 1 < count {
         result_string.push_str(separator);
       }
-
-      index += 1;
     }
 
     return result_string;
   }
-}
\ No newline at end of file
+}
+
+/// "Some+File%20Name" -> "Some File Name". Invalid escapes are kept as is.
+pub fn percent_decode(value: &str) -> String {
+  let bytes = value.as_bytes();
+  let mut result: Vec<u8> = Vec::with_capacity(bytes.len());
+  let mut index = 0;
+
+  while index < bytes.len() {
+    let byte = bytes[index];
+
+    if byte == b'%' && index + 2 < bytes.len() {
+      let hex = std::str::from_utf8(&bytes[index + 1..index + 3]).ok();
+      if let Option::Some(decoded) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+        result.push(decoded);
+        index += 3;
+        continue;
+      }
+    }
+
+    result.push(if byte == b'+' { b' ' } else { byte });
+    index += 1;
+  }
+
+  return String::from_utf8_lossy(&result).to_string();
+}
+
+/// "Some File/Name" -> "Some%20File%2FName". Everything except the unreserved characters of RFC 3986
+/// (letters, digits, "-", ".", "_" and "~") is escaped so the value can be put into any part of an url.
+pub fn percent_encode(value: &str) -> String {
+  let mut result = String::with_capacity(value.len());
+
+  for byte in value.bytes() {
+    if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+      result.push(byte as char);
+    } else {
+      result.push_str(&format!("%{:02X}", byte));
+    }
+  }
+
+  return result;
+}
+
+#[test]
+fn test_map_join_cap() {
+  let parts = ["a", "b", "c"];
+
+  assert_eq!("a, b, c", parts.iter().map_join_cap(16, ", ", &|part| part));
+  assert_eq!("abc", parts.iter().map_join(&|part| part));
+  assert_eq!("", Vec::<&str>::new().iter().map_join_cap(16, ", ", &|part| part));
+}
+
+#[test]
+fn test_percent_encode() {
+  assert_eq!("fglt", percent_encode("fglt"));
+  assert_eq!("a%20b%26c%3Dd%23e%2Ff", percent_encode("a b&c=d#e/f"));
+  assert_eq!("%D0%B1", percent_encode("б"));
+  assert_eq!("a b&c=d#e/f", percent_decode(&percent_encode("a b&c=d#e/f")));
+}
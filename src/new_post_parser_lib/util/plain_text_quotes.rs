@@ -0,0 +1,92 @@
+use regex::Regex;
+use crate::{PostLink, PostParserContext, PostRaw};
+
+lazy_static! {
+  static ref PLAIN_TEXT_QUOTE_PATTERN: Regex = Regex::new(r">>>/(\w+)/(\d*)|>>(\d+)").unwrap();
+}
+
+/// A quote that is written as plain text (without an <a> tag)
+#[derive(Debug, PartialEq)]
+pub struct PlainTextQuote {
+  // bytes
+  pub start: usize,
+  // bytes
+  pub end: usize,
+  pub post_link: PostLink
+}
+
+/// Finds ">>123", ">>>/g/123" and ">>>/g/" in [text]. ">>>/g/123" from the same board is a regular quote.
+/// Otherwise the thread of the post is unknown so [PostLink::ThreadLink] gets the post number as the
+/// thread number (sites redirect such links to the thread the post belongs to).
+pub fn find_plain_text_quotes(
+  post_raw: &PostRaw,
+  post_parser_context: &PostParserContext,
+  text: &str
+) -> Vec<PlainTextQuote> {
+  let mut plain_text_quotes: Vec<PlainTextQuote> = Vec::new();
+
+  for captures in PLAIN_TEXT_QUOTE_PATTERN.captures_iter(text) {
+    let whole_match = captures.get(0).unwrap();
+
+    // "a>>123" or ">>>123"
+    let prev_char_maybe = text[..whole_match.start()].chars().last();
+    if let Option::Some(prev_char) = prev_char_maybe {
+      if prev_char == '>' || prev_char.is_alphanumeric() {
+        continue;
+      }
+    }
+
+    let post_link_maybe = if let Option::Some(post_no_match) = captures.get(3) {
+      post_no_match.as_str().parse::<u64>().ok().map(|post_no| quote_post_link(post_parser_context, post_no))
+    } else {
+      let board_code = captures.get(1).unwrap().as_str();
+      let post_no_str = captures.get(2).unwrap().as_str();
+
+      if post_no_str.is_empty() {
+        Option::Some(PostLink::BoardLink { board_code: String::from(board_code) })
+      } else {
+        post_no_str.parse::<u64>().ok().map(|post_no| {
+          if board_code == post_raw.board_code() && post_parser_context.is_internal_thread_post(post_no) {
+            return PostLink::Quote { post_no };
+          }
+
+          return PostLink::ThreadLink { board_code: String::from(board_code), thread_no: post_no, post_no };
+        })
+      }
+    };
+
+    if let Option::Some(post_link) = post_link_maybe {
+      plain_text_quotes.push(PlainTextQuote { start: whole_match.start(), end: whole_match.end(), post_link });
+    }
+  }
+
+  return plain_text_quotes;
+}
+
+fn quote_post_link(post_parser_context: &PostParserContext, post_no: u64) -> PostLink {
+  return if post_parser_context.is_internal_thread_post(post_no) {
+    PostLink::Quote { post_no }
+  } else {
+    PostLink::Dead { post_no }
+  }
+}
+
+#[test]
+fn test_find_plain_text_quotes() {
+  use std::collections::HashSet;
+  use crate::set_of;
+
+  let post_raw = PostRaw::new("4chan", "g", 1, 2, 0, "");
+  let post_parser_context = PostParserContext::new(HashSet::new(), set_of!(1, 5));
+  let text = ">>1 a>>2 >>>3 >>>/g/5 >>>/a/6 >>>/jp/ >>3";
+
+  let expected = vec![
+    PlainTextQuote { start: 0, end: 3, post_link: PostLink::Quote { post_no: 1 } },
+    PlainTextQuote { start: 14, end: 21, post_link: PostLink::Quote { post_no: 5 } },
+    PlainTextQuote { start: 22, end: 29, post_link: PostLink::ThreadLink { board_code: "a".to_string(), thread_no: 6, post_no: 6 } },
+    PlainTextQuote { start: 30, end: 37, post_link: PostLink::BoardLink { board_code: "jp".to_string() } },
+    PlainTextQuote { start: 38, end: 41, post_link: PostLink::Dead { post_no: 3 } },
+  ];
+
+  assert_eq!(expected, find_plain_text_quotes(&post_raw, &post_parser_context, text));
+}
@@ -0,0 +1,117 @@
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+use crate::{Spannable, SpannableData, SearchQuery};
+
+pub fn detect_search_highlights(total_text: &str, search_query: &SearchQuery, out_spannables: &mut Vec<Spannable>) {
+  let matches = match search_query {
+    SearchQuery::Plain { query, ignore_case, ignore_diacritics } => {
+      find_plain_matches(total_text, query, *ignore_case, *ignore_diacritics)
+    }
+    SearchQuery::Regex { regex } => {
+      let mut matches: Vec<(usize, usize)> = Vec::new();
+
+      for regex_match in regex.find_iter(total_text) {
+        // Regex matches are in bytes but spannables use unicode characters (not u8!)
+        let start = total_text[..regex_match.start()].chars().count();
+        let len = regex_match.as_str().chars().count();
+
+        matches.push((start, len));
+      }
+
+      matches
+    }
+  };
+
+  for (start, len) in matches {
+    let spannable = Spannable {
+      start,
+      len,
+      spannable_data: SpannableData::SearchHighlight
+    };
+
+    if spannable.is_valid() {
+      out_spannables.push(spannable);
+    }
+  }
+}
+
+/// Returns (start, len) pairs in unicode characters of [total_text]
+fn find_plain_matches(total_text: &str, query: &str, ignore_case: bool, ignore_diacritics: bool) -> Vec<(usize, usize)> {
+  let folded_query: Vec<char> = query
+    .chars()
+    .flat_map(|ch| fold_char(ch, ignore_case, ignore_diacritics))
+    .collect();
+
+  if folded_query.is_empty() {
+    return Vec::new();
+  }
+
+  // Folding may change the amount of characters ("ǅ" -> "dž", "é" -> "e") so we need to remember which
+  // character of the original text every folded character came from.
+  let mut folded_text: Vec<char> = Vec::with_capacity(total_text.len());
+  let mut origins: Vec<usize> = Vec::with_capacity(total_text.len());
+
+  for (index, ch) in total_text.chars().enumerate() {
+    for folded_char in fold_char(ch, ignore_case, ignore_diacritics) {
+      folded_text.push(folded_char);
+      origins.push(index);
+    }
+  }
+
+  let total_chars_count = total_text.chars().count();
+  let mut matches: Vec<(usize, usize)> = Vec::new();
+  let mut offset = 0;
+
+  while offset + folded_query.len() <= folded_text.len() {
+    if folded_text[offset..offset + folded_query.len()] != folded_query[..] {
+      offset += 1;
+      continue;
+    }
+
+    let start = origins[offset];
+    let last_matched_char_end = origins[offset + folded_query.len() - 1] + 1;
+
+    // Include the characters that were folded into nothing (e.g. combining marks) right after the match
+    let end = origins
+      .get(offset + folded_query.len())
+      .copied()
+      .unwrap_or(total_chars_count)
+      .max(last_matched_char_end);
+
+    matches.push((start, end - start));
+
+    offset += folded_query.len();
+  }
+
+  return matches;
+}
+
+fn fold_char(ch: char, ignore_case: bool, ignore_diacritics: bool) -> Vec<char> {
+  let mut chars: Vec<char> = if ignore_diacritics {
+    std::iter::once(ch).nfd().filter(|ch| !is_combining_mark(*ch)).collect()
+  } else {
+    vec![ch]
+  };
+
+  if ignore_case {
+    chars = chars.into_iter().flat_map(|ch| ch.to_lowercase()).collect();
+  }
+
+  return chars;
+}
+
+#[test]
+fn test_find_plain_matches() {
+  assert_eq!(vec![(0, 3), (8, 3)], find_plain_matches("abc abd abc", "abc", false, false));
+  assert_eq!(vec![(4, 3)], find_plain_matches("ABC abc", "abc", false, false));
+  assert_eq!(vec![(0, 3), (4, 3)], find_plain_matches("ABC abc", "abc", true, false));
+  assert!(find_plain_matches("abc", "", true, true).is_empty());
+}
+
+#[test]
+fn test_find_plain_matches_ignore_diacritics() {
+  assert_eq!(vec![(4, 4)], find_plain_matches("the Café", "cafe", true, true));
+  assert_eq!(vec![(0, 5)], find_plain_matches("Cafe\u{301}", "café", true, true));
+  assert!(find_plain_matches("the Café", "cafe", true, false).is_empty());
+  assert_eq!(vec![(1, 3)], find_plain_matches("ニコニコ", "コニコ", true, true));
+}
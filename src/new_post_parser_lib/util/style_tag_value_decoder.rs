@@ -1,104 +1,460 @@
-use crate::SpannableData;
+use crate::{SpannableData, Spannable};
 use std::collections::HashMap;
-use crate::util::color_decoder::color_name_to_color_hex;
+use crate::util::color_decoder::parse_css_color;
+
+const TAG: &str = "StyleTagValueDecoder";
+const IMPORTANT_KEYWORD: &str = "important";
+
+// font-weight:bolder/lighter are relative to the parent's weight which we don't know so the usual
+// bold/light weights are used instead
+const FONT_WEIGHT_NORMAL: u16 = 400;
+const FONT_WEIGHT_BOLD: u16 = 700;
+const FONT_WEIGHT_LIGHTER: u16 = 300;
+const FONT_WEIGHT_MIN: u16 = 1;
+const FONT_WEIGHT_MAX: u16 = 1000;
+
+// The default font size of the browsers, "font-size:16px" has the scale of 1.0
+const BASE_FONT_SIZE_PX: f32 = 16.0;
+const PX_PER_PT: f32 = 4.0 / 3.0;
+// Ex and ch depend on the font, half of the em is what the browsers use when they don't know
+const EM_PER_EX: f32 = 0.5;
+pub const DEFAULT_MIN_FONT_SIZE_SCALE: f32 = 0.5;
+pub const DEFAULT_MAX_FONT_SIZE_SCALE: f32 = 2.0;
 
 lazy_static::lazy_static! {
-  static ref STYLE_DECODERS: HashMap<&'static str, fn(&str) -> Option<SpannableData>> = {
+  static ref STYLE_DECODERS: HashMap<&'static str, fn(&str) -> Vec<SpannableData>> = {
     let mut map = HashMap::<_, fn(&str) -> _>::new();
 
-    // font-size:22px;font-size:150%;
+    // font-size:22px;font-size:150%;font-size:large
     map.insert("font-size", |style_value| {
-      return Option::Some(SpannableData::FontSize { size: String::from(style_value) });
+      return decode_font_size_scale(style_value)
+        .map(|scale| vec![SpannableData::FontSize { size: String::from(style_value), scale }])
+        .unwrap_or_default();
     });
 
     // font-weight:600;font-weight:bold
-    map.insert("font-weight", |_| {
-      // TODO
-      return Option::None;
+    map.insert("font-weight", |style_value| {
+      return decode_font_weight(style_value)
+        .map(|weight| vec![SpannableData::FontWeight { weight }])
+        .unwrap_or_default();
     });
 
-    // color:red;color:#fd4d32
-    map.insert("color", |style_value| {
-      if style_value.starts_with("#") {
-        return Option::Some(SpannableData::TextForegroundColorRaw { color_hex: style_value.to_string() })
+    // font-style:italic;font-style:oblique 10deg
+    map.insert("font-style", |style_value| {
+      let style_value = style_value.to_lowercase();
+
+      if style_value == "italic" || style_value.starts_with("oblique") {
+        return vec![SpannableData::ItalicText];
       }
 
-      let color_hex_maybe = color_name_to_color_hex(style_value);
+      return Vec::new();
+    });
+
+    // text-decoration:underline line-through;text-decoration:underline dotted red
+    map.insert("text-decoration", decode_text_decoration);
+    map.insert("text-decoration-line", decode_text_decoration);
 
-      if let Option::None = color_hex_maybe {
-        return Option::None;
+    // font-family:monospace;font-family:"Courier New", monospace
+    map.insert("font-family", |style_value| {
+      let is_monospace = style_value
+        .split(',')
+        .any(|font_family| font_family.trim().eq_ignore_ascii_case("monospace"));
+
+      if is_monospace {
+        return vec![SpannableData::Monospace];
       }
 
-      return Option::Some(SpannableData::TextForegroundColorRaw { color_hex: color_hex_maybe.unwrap() });
+      return Vec::new();
+    });
+
+    // color:red;color:#fd4d32
+    map.insert("color", |style_value| {
+      return decode_color(style_value)
+        .map(|color_hex| vec![SpannableData::TextForegroundColorRaw { color_hex }])
+        .unwrap_or_default();
+    });
+
+    // background-color:yellow;background:#fd4d32
+    map.insert("background-color", decode_background_color);
+    // Only when the shorthand is just a color, images/positions/etc are ignored without logging an error
+    // since they are not colors most of the time
+    map.insert("background", |style_value| {
+      return parse_css_color(style_value)
+        .map(|color| vec![SpannableData::TextBackgroundColorRaw { color_hex: color.to_hex() }])
+        .unwrap_or_default();
     });
 
     return map;
   };
 }
 
+/// A single "name: value !important" declaration of a style attribute. [name] is lowercase, [value] is
+/// trimmed and has no "!important" and comments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CssDeclaration {
+  pub name: String,
+  pub value: String,
+  pub is_important: bool
+}
+
 pub fn decode_style_spans(style_attr_value: &str) -> Vec<SpannableData> {
   if style_attr_value.is_empty() {
     return Vec::new();
   }
 
-  let style_params: Vec<&str> = style_attr_value.split(';').collect();
-  if style_params.is_empty() {
-    return Vec::new();
+  let mut output_spannables: Vec<SpannableData> = Vec::new();
+
+  for css_declaration in resolve_css_declarations(tokenize_css_declarations(style_attr_value)) {
+    let decoder_maybe = STYLE_DECODERS.get(css_declaration.name.as_str());
+
+    let decoder = match decoder_maybe {
+      None => continue,
+      Some(decoder) => decoder
+    };
+
+    output_spannables.extend(decoder(&css_declaration.value));
   }
 
-  let mut output_spannables: Vec<SpannableData> = Vec::with_capacity(style_params.len());
+  return output_spannables;
+}
+
+/// Splits [style_attr_value] into declarations. Semicolons and colons inside of quotes
+/// ("font-family: 'a;b'") and parentheses ("background: url(data:image/png;base64,...)") do not split
+/// anything, comments are removed. Declarations without a name or a value are skipped.
+pub fn tokenize_css_declarations(style_attr_value: &str) -> Vec<CssDeclaration> {
+  let mut css_declarations: Vec<CssDeclaration> = Vec::new();
 
-  for style_param in style_params {
-    if style_param.is_empty() {
+  for raw_declaration in split_outside_of_quotes(&remove_css_comments(style_attr_value), ';') {
+    let name_end_maybe = find_outside_of_quotes(&raw_declaration, ':');
+    if name_end_maybe.is_none() {
       continue;
     }
 
-    let style_param_parts: Vec<&str> = style_param.split(':').collect();
-    if style_param_parts.len() != 2 {
+    let name_end = name_end_maybe.unwrap();
+    let name = raw_declaration[..name_end].trim().to_lowercase();
+    let (value, is_important) = strip_important(raw_declaration[name_end + 1..].trim());
+
+    if name.is_empty() || value.is_empty() {
       continue;
     }
 
-    let style_param_name = style_param_parts.get(0).unwrap().trim();
-    let style_param_value = style_param_parts.get(1).unwrap().trim();
-    let decoder_maybe = STYLE_DECODERS.get(style_param_name);
+    css_declarations.push(CssDeclaration { name, value: String::from(value), is_important });
+  }
 
-    let decoder = if let Option::None = decoder_maybe {
-      continue
-    } else {
-      decoder_maybe.unwrap()
-    };
+  return css_declarations;
+}
+
+/// The same property may be declared several times, like in the browsers the last declaration wins
+/// unless an earlier one is "!important". The order of the properties is kept.
+fn resolve_css_declarations(css_declarations: Vec<CssDeclaration>) -> Vec<CssDeclaration> {
+  let mut resolved_declarations: Vec<CssDeclaration> = Vec::with_capacity(css_declarations.len());
 
-    let decoded_spannable_data = decoder(style_param_value);
+  for css_declaration in css_declarations {
+    let prev_declaration_index = resolved_declarations
+      .iter()
+      .position(|prev_declaration| prev_declaration.name == css_declaration.name);
 
-    if let Option::Some { .. } = decoded_spannable_data {
-      output_spannables.push(decoded_spannable_data.unwrap());
+    match prev_declaration_index {
+      Some(index) => {
+        if css_declaration.is_important || !resolved_declarations[index].is_important {
+          resolved_declarations[index] = css_declaration;
+        }
+      }
+      None => resolved_declarations.push(css_declaration)
     }
   }
 
-  return output_spannables;
+  return resolved_declarations;
+}
+
+/// "bold !important" -> ("bold", true), "! important" with spaces is valid too
+fn strip_important(value: &str) -> (&str, bool) {
+  let bang_index_maybe = find_outside_of_quotes(value, '!');
+  if bang_index_maybe.is_none() {
+    return (value, false);
+  }
+
+  let bang_index = bang_index_maybe.unwrap();
+
+  if !value[bang_index + 1..].trim().eq_ignore_ascii_case(IMPORTANT_KEYWORD) {
+    return (value, false);
+  }
+
+  return (value[..bang_index].trim_end(), true);
+}
+
+fn remove_css_comments(style_attr_value: &str) -> String {
+  let mut result = String::with_capacity(style_attr_value.len());
+  let mut rest = style_attr_value;
+
+  while let Option::Some(comment_start) = rest.find("/*") {
+    result.push_str(&rest[..comment_start]);
+
+    match rest[comment_start + 2..].find("*/") {
+      Some(comment_end) => rest = &rest[comment_start + 2 + comment_end + 2..],
+      // Unclosed comment takes the rest of the string
+      None => rest = ""
+    }
+  }
+
+  result.push_str(rest);
+  return result;
+}
+
+fn split_outside_of_quotes(text: &str, separator: char) -> Vec<String> {
+  let mut parts: Vec<String> = Vec::new();
+  let mut rest = text;
+
+  while let Option::Some(separator_index) = find_outside_of_quotes(rest, separator) {
+    parts.push(String::from(&rest[..separator_index]));
+    rest = &rest[separator_index + separator.len_utf8()..];
+  }
+
+  parts.push(String::from(rest));
+  return parts;
+}
+
+/// Byte index of the first [target] that is not inside of quotes or parentheses. Backslash escapes the
+/// next character.
+fn find_outside_of_quotes(text: &str, target: char) -> Option<usize> {
+  let mut quote: Option<char> = Option::None;
+  let mut parentheses_depth: usize = 0;
+  let mut is_escaped = false;
+
+  for (index, ch) in text.char_indices() {
+    if is_escaped {
+      is_escaped = false;
+      continue;
+    }
+
+    if ch == '\\' {
+      is_escaped = true;
+      continue;
+    }
+
+    if let Option::Some(quote_char) = quote {
+      if ch == quote_char {
+        quote = Option::None;
+      }
+
+      continue;
+    }
+
+    match ch {
+      '"' | '\'' => quote = Option::Some(ch),
+      '(' => parentheses_depth += 1,
+      ')' => parentheses_depth = parentheses_depth.saturating_sub(1),
+      _ if ch == target && parentheses_depth == 0 => return Option::Some(index),
+      _ => {}
+    }
+  }
+
+  return Option::None;
+}
+
+/// "bold" -> 700, "600" -> 600, numbers are clamped to 1..=1000
+fn decode_font_weight(style_value: &str) -> Option<u16> {
+  let weight = match style_value.to_lowercase().as_str() {
+    "normal" => FONT_WEIGHT_NORMAL,
+    "bold" | "bolder" => FONT_WEIGHT_BOLD,
+    "lighter" => FONT_WEIGHT_LIGHTER,
+    number => {
+      // parse() also accepts "nan" and "inf"
+      let number = number.parse::<f32>().ok().filter(|number| number.is_finite())?;
+      number.round().clamp(FONT_WEIGHT_MIN as f32, FONT_WEIGHT_MAX as f32) as u16
+    }
+  };
+
+  return Option::Some(weight);
+}
+
+/// "24px" -> 1.5, "150%" -> 1.5, "1.5em" -> 1.5, "x-large" -> 1.5. Not clamped (see
+/// [clamp_font_size_scales]). Units that depend on the viewport or the parent element ("vw", "calc()")
+/// are not supported.
+pub fn decode_font_size_scale(style_value: &str) -> Option<f32> {
+  let style_value = style_value.trim().to_lowercase();
+
+  // https://www.w3.org/TR/css-fonts-4/#absolute-size-mapping
+  let keyword_scale = match style_value.as_str() {
+    "xx-small" => Option::Some(3.0 / 5.0),
+    "x-small" => Option::Some(3.0 / 4.0),
+    "small" => Option::Some(8.0 / 9.0),
+    "medium" => Option::Some(1.0),
+    "large" => Option::Some(6.0 / 5.0),
+    "x-large" => Option::Some(3.0 / 2.0),
+    "xx-large" => Option::Some(2.0 / 1.0),
+    "xxx-large" => Option::Some(3.0 / 1.0),
+    "smaller" => Option::Some(5.0 / 6.0),
+    "larger" => Option::Some(6.0 / 5.0),
+    _ => Option::None
+  };
+
+  if keyword_scale.is_some() {
+    return keyword_scale;
+  }
+
+  let number_end = style_value
+    .find(|ch: char| !(ch.is_ascii_digit() || ch == '.' || ch == '-' || ch == '+'))
+    .unwrap_or(style_value.len());
+
+  let number = style_value[..number_end].parse::<f32>().ok()?;
+  if !number.is_finite() || number < 0.0 {
+    return Option::None;
+  }
+
+  let scale = match &style_value[number_end..] {
+    "px" => number / BASE_FONT_SIZE_PX,
+    "pt" => number * PX_PER_PT / BASE_FONT_SIZE_PX,
+    "em" | "rem" => number,
+    "ex" | "ch" => number * EM_PER_EX,
+    "%" => number / 100.0,
+    // Unitless zero is the only valid unitless length
+    "" if number == 0.0 => 0.0,
+    _ => return Option::None
+  };
+
+  return Option::Some(scale);
+}
+
+/// What <small> does, the same as "font-size:smaller"
+pub fn smaller_font_size() -> SpannableData {
+  let size = "smaller";
+  return SpannableData::FontSize { size: String::from(size), scale: decode_font_size_scale(size).unwrap() };
+}
+
+/// Clamps the [SpannableData::FontSize] scales to [min_scale]..=[max_scale] so that nobody can post text
+/// that takes the whole screen (or that can't be seen)
+pub fn clamp_font_size_scales(spannables: &mut [Spannable], min_scale: f32, max_scale: f32) {
+  for spannable in spannables.iter_mut() {
+    if let SpannableData::FontSize { scale, .. } = &mut spannable.spannable_data {
+      *scale = scale.clamp(min_scale, max_scale);
+    }
+  }
+}
+
+fn decode_text_decoration(style_value: &str) -> Vec<SpannableData> {
+  let mut spannables: Vec<SpannableData> = Vec::new();
+
+  for text_decoration_line in style_value.to_lowercase().split_whitespace() {
+    let spannable_data = match text_decoration_line {
+      "underline" => SpannableData::UnderlineText,
+      "line-through" => SpannableData::StrikethroughText,
+      "overline" => SpannableData::OverlineText,
+      _ => continue
+    };
+
+    if !spannables.contains(&spannable_data) {
+      spannables.push(spannable_data);
+    }
+  }
+
+  return spannables;
+}
+
+fn decode_background_color(style_value: &str) -> Vec<SpannableData> {
+  return decode_color(style_value)
+    .map(|color_hex| vec![SpannableData::TextBackgroundColorRaw { color_hex }])
+    .unwrap_or_default();
+}
+
+/// Colors are normalized to "#RRGGBBAA"
+fn decode_color(style_value: &str) -> Option<String> {
+  return match parse_css_color(style_value) {
+    Ok(color) => Option::Some(color.to_hex()),
+    Err(error) => {
+      eprintln!("{} Failed to decode color, error: {}", TAG, error);
+      Option::None
+    }
+  };
 }
 
 #[test]
 fn test_decode_style_spans() {
   assert_eq!(
-    &SpannableData::FontSize { size: String::from("22px") },
+    &SpannableData::FontSize { size: String::from("22px"), scale: 1.375 },
     decode_style_spans("font-size:22px").first().unwrap()
   );
   assert_eq!(
-    &SpannableData::FontSize { size: String::from("150%") },
+    &SpannableData::FontSize { size: String::from("150%"), scale: 1.5 },
     decode_style_spans("font-size:150%").first().unwrap()
   );
+  assert!(decode_style_spans("font-size:10vw").is_empty());
 
-  assert_eq!(Option::None, decode_style_spans("font-weight:600").first());
-  assert_eq!(Option::None, decode_style_spans("font-weight:bold").first());
+  assert_eq!(&SpannableData::FontWeight { weight: 600 }, decode_style_spans("font-weight:600").first().unwrap());
+  assert_eq!(&SpannableData::FontWeight { weight: 700 }, decode_style_spans("font-weight:BOLD").first().unwrap());
+  assert_eq!(&SpannableData::FontWeight { weight: 1000 }, decode_style_spans("font-weight:5000").first().unwrap());
+  assert_eq!(Option::None, decode_style_spans("font-weight:heavy").first());
+  assert_eq!(Option::None, decode_style_spans("font-weight:nan").first());
+  assert_eq!(Option::None, decode_style_spans("font-weight:-inf").first());
 
   assert_eq!(
-    &SpannableData::TextForegroundColorRaw { color_hex: String::from("#FF0000") },
+    &SpannableData::TextForegroundColorRaw { color_hex: String::from("#FF0000FF") },
     decode_style_spans("color:red").first().unwrap());
   assert_eq!(
-    &SpannableData::TextForegroundColorRaw {color_hex: String::from("#FD4D32") },
+    &SpannableData::TextForegroundColorRaw {color_hex: String::from("#FD4D32FF") },
     decode_style_spans("color:#FD4D32").first().unwrap());
   assert_eq!(
-    &SpannableData::TextForegroundColorRaw {color_hex: String::from("#fd4d32") },
+    &SpannableData::TextForegroundColorRaw {color_hex: String::from("#FD4D32FF") },
     decode_style_spans("color:#fd4d32").first().unwrap());
-}
\ No newline at end of file
+  assert_eq!(
+    &SpannableData::TextForegroundColorRaw {color_hex: String::from("#FD4D3280") },
+    decode_style_spans("color: rgba(253, 77, 50, 0.5)").first().unwrap());
+  assert!(decode_style_spans("color:#fd4d3").is_empty());
+
+  assert_eq!(
+    vec![SpannableData::TextBackgroundColorRaw { color_hex: String::from("#FFFF00FF") }, SpannableData::ItalicText],
+    decode_style_spans("background-color: yellow; font-style: italic"));
+  assert_eq!(
+    vec![SpannableData::UnderlineText, SpannableData::StrikethroughText],
+    decode_style_spans("text-decoration: underline line-through red"));
+  assert_eq!(
+    vec![SpannableData::Monospace],
+    decode_style_spans("font-family: \"Courier New\", monospace"));
+  assert!(decode_style_spans("background: url(image.png) no-repeat").is_empty());
+}
+
+#[test]
+fn test_tokenize_css_declarations() {
+  let declaration = |name: &str, value: &str, is_important: bool| {
+    return CssDeclaration { name: name.to_string(), value: value.to_string(), is_important };
+  };
+
+  assert_eq!(
+    vec![declaration("color", "red", true), declaration("font-weight", "bold", false)],
+    tokenize_css_declarations(" COLOR : red ! important ;font-weight:bold;;"));
+  assert_eq!(
+    vec![declaration("font-family", "'a;b:c', monospace", false)],
+    tokenize_css_declarations("font-family: 'a;b:c', monospace"));
+  assert_eq!(
+    vec![declaration("background", "url(data:image/png;base64,AAAA)", false), declaration("color", "red", false)],
+    tokenize_css_declarations("background: url(data:image/png;base64,AAAA); /* comment; */color: red"));
+  assert_eq!(
+    vec![declaration("content", "\"\\\"!important\"", false)],
+    tokenize_css_declarations("content: \"\\\"!important\"; broken; :value; name:"));
+}
+
+#[test]
+fn test_important_declarations_win() {
+  assert_eq!(
+    vec![SpannableData::TextForegroundColorRaw { color_hex: String::from("#0000FFFF") }],
+    decode_style_spans("color: red; color: blue"));
+  assert_eq!(
+    vec![SpannableData::TextForegroundColorRaw { color_hex: String::from("#FF0000FF") }],
+    decode_style_spans("color: red !important; color: blue"));
+}
+
+#[test]
+fn test_decode_font_size_scale() {
+  assert_eq!(Option::Some(1.5), decode_font_size_scale("24px"));
+  assert_eq!(Option::Some(1.0), decode_font_size_scale("12pt"));
+  assert_eq!(Option::Some(1.5), decode_font_size_scale("1.5EM"));
+  assert_eq!(Option::Some(0.75), decode_font_size_scale(".75rem"));
+  assert_eq!(Option::Some(1.0), decode_font_size_scale("2ex"));
+  assert_eq!(Option::Some(1.5), decode_font_size_scale("x-large"));
+  assert_eq!(Option::Some(31.25), decode_font_size_scale("500px"));
+  assert_eq!(Option::Some(0.0), decode_font_size_scale("0"));
+  assert_eq!(Option::None, decode_font_size_scale("22"));
+  assert_eq!(Option::None, decode_font_size_scale("-2em"));
+  assert_eq!(Option::None, decode_font_size_scale("calc(1em + 2px)"));
+  assert_eq!(Option::None, decode_font_size_scale("huge"));
+}
@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+use crate::{ChanThemeColorId, Element, Spannable, SpannableData};
+use crate::util::color_decoder::{parse_css_color, RgbaColor};
+
+const TAG: &str = "ThemeColorMapper";
+const CLASS_ATTR: &str = "class";
+
+// WCAG AA for large text, normal text needs 4.5 but colored text in posts is usually decorative
+pub const DEFAULT_MIN_CONTRAST_RATIO: f64 = 3.0;
+// How much of white/black is mixed in at every step when a color is adjusted for contrast
+const CONTRAST_ADJUSTMENT_STEP: f64 = 0.05;
+const DARK_BACKGROUND_MAX_LUMINANCE: f64 = 0.179;
+
+/// Converts the colors that sites hard-code into the comments (4chan's "#789922" greentext etc) and the
+/// elements with known classes into theme color ids so that the app shows them with the colors of the
+/// current theme. Every site has its own mapper, see [SiteProfile::theme_color_mapper]. The rest of the
+/// raw foreground colors can be made readable on the background of the theme (see
+/// [ThemeColorMapper::set_contrast_background]).
+#[derive(Debug, Clone)]
+pub struct ThemeColorMapper {
+  /// [Key] "#RRGGBBAA" color, [Value] theme color id
+  color_ids: HashMap<String, ChanThemeColorId>,
+  /// [Key] lowercase class name, [Value] theme color id
+  class_color_ids: HashMap<String, ChanThemeColorId>,
+  contrast_background: Option<RgbaColor>,
+  min_contrast_ratio: f64
+}
+
+impl Default for ThemeColorMapper {
+  fn default() -> Self {
+    return ThemeColorMapper::new();
+  }
+}
+
+impl ThemeColorMapper {
+  /// Mapper that maps nothing
+  pub fn new() -> ThemeColorMapper {
+    return ThemeColorMapper {
+      color_ids: HashMap::new(),
+      class_color_ids: HashMap::new(),
+      contrast_background: Option::None,
+      min_contrast_ratio: DEFAULT_MIN_CONTRAST_RATIO
+    };
+  }
+
+  /// Mapper with the colors and classes of the 4chan themes (Yotsuba and Yotsuba B). Black and maroon are
+  /// not mapped since posters use them on purpose as well.
+  pub fn four_chan() -> ThemeColorMapper {
+    let mut theme_color_mapper = ThemeColorMapper::new();
+
+    // Greentext
+    theme_color_mapper.add_color("#789922", ChanThemeColorId::PostInlineQuoteColor);
+    // Links
+    theme_color_mapper.add_color("#34345C", ChanThemeColorId::PostLinkColor);
+    theme_color_mapper.add_color("#000080", ChanThemeColorId::PostLinkColor);
+    // Subjects
+    theme_color_mapper.add_color("#0F0C5D", ChanThemeColorId::PostSubjectColor);
+    theme_color_mapper.add_color("#CC1105", ChanThemeColorId::PostSubjectColor);
+    // Names
+    theme_color_mapper.add_color("#117743", ChanThemeColorId::PostNameColor);
+    // Post backgrounds
+    theme_color_mapper.add_color("#D6DAF0", ChanThemeColorId::BackColorSecondary);
+    theme_color_mapper.add_color("#F0E0D6", ChanThemeColorId::BackColorSecondary);
+
+    theme_color_mapper.add_class("subject", ChanThemeColorId::PostSubjectColor);
+    theme_color_mapper.add_class("name", ChanThemeColorId::PostNameColor);
+    theme_color_mapper.add_class("postertrip", ChanThemeColorId::PostNameColor);
+
+    return theme_color_mapper;
+  }
+
+  /// [color] is any CSS color ("#789922", "rgb(120, 153, 34)", "green"), invalid colors are ignored
+  pub fn add_color(&mut self, color: &str, color_id: ChanThemeColorId) {
+    match parse_css_color(color) {
+      Ok(color) => {
+        self.color_ids.insert(color.to_hex(), color_id);
+      }
+      Err(error) => {
+        eprintln!("{} Failed to add color \"{}\", error: {}", TAG, color, error);
+      }
+    }
+  }
+
+  /// Text of the elements with [class_name] (one of the classes of the element) gets [color_id]
+  pub fn add_class(&mut self, class_name: &str, color_id: ChanThemeColorId) {
+    self.class_color_ids.insert(class_name.to_lowercase(), color_id);
+  }
+
+  /// Raw foreground colors that are not mapped to a theme color id and have a contrast ratio against
+  /// [background] lower than [min_contrast_ratio] (1.0..=21.0) are made lighter or darker until they
+  /// are readable. [Option::None] (the default) disables the adjustment. A NaN or infinite
+  /// [min_contrast_ratio] is replaced with [DEFAULT_MIN_CONTRAST_RATIO].
+  pub fn set_contrast_background(&mut self, background: Option<&str>, min_contrast_ratio: f64) {
+    let min_contrast_ratio = if min_contrast_ratio.is_finite() { min_contrast_ratio } else { DEFAULT_MIN_CONTRAST_RATIO };
+
+    self.contrast_background = background.and_then(|background| parse_css_color(background).ok());
+    self.min_contrast_ratio = min_contrast_ratio.clamp(1.0, 21.0);
+  }
+
+  pub fn class_color_id(&self, element: &Element) -> Option<ChanThemeColorId> {
+    let class_attr = element.get_attr_value(CLASS_ATTR)?;
+
+    return class_attr
+      .split_whitespace()
+      .find_map(|class_name| self.class_color_ids.get(&class_name.to_lowercase()))
+      .cloned();
+  }
+
+  /// Replaces [SpannableData::TextForegroundColorRaw]/[SpannableData::TextBackgroundColorRaw] of the
+  /// known colors with [SpannableData::TextForegroundColorId]/[SpannableData::TextBackgroundColorId]
+  pub fn map_colors(&self, spannables: &mut [Spannable]) {
+    for spannable in spannables.iter_mut() {
+      let mapped_spannable_data = match &spannable.spannable_data {
+        SpannableData::TextForegroundColorRaw { color_hex } => {
+          match self.color_ids.get(color_hex) {
+            Some(color_id) => SpannableData::TextForegroundColorId { color_id: color_id.clone() },
+            None => {
+              let adjusted_color_hex_maybe = self.adjust_for_contrast(color_hex);
+              if adjusted_color_hex_maybe.is_none() {
+                continue;
+              }
+
+              SpannableData::TextForegroundColorRaw { color_hex: adjusted_color_hex_maybe.unwrap() }
+            }
+          }
+        }
+        SpannableData::TextBackgroundColorRaw { color_hex } => {
+          match self.color_ids.get(color_hex) {
+            Some(color_id) => SpannableData::TextBackgroundColorId { color_id: color_id.clone() },
+            None => continue
+          }
+        }
+        _ => continue
+      };
+
+      spannable.spannable_data = mapped_spannable_data;
+    }
+  }
+
+  /// [Option::None] when the color does not need to be adjusted
+  fn adjust_for_contrast(&self, color_hex: &str) -> Option<String> {
+    let background = self.contrast_background?;
+    let color = parse_css_color(color_hex).ok()?;
+
+    if contrast_ratio(&color, &background) >= self.min_contrast_ratio {
+      return Option::None;
+    }
+
+    // Dark backgrounds need lighter text and vice versa. Below this luminance white has a better contrast
+    // with the background than black.
+    let target = if relative_luminance(&background) < DARK_BACKGROUND_MAX_LUMINANCE {
+      RgbaColor::new(255, 255, 255, color.alpha)
+    } else {
+      RgbaColor::new(0, 0, 0, color.alpha)
+    };
+
+    let mut amount = CONTRAST_ADJUSTMENT_STEP;
+    let mut adjusted_color = color;
+
+    while amount < 1.0 {
+      adjusted_color = mix(&color, &target, amount);
+
+      if contrast_ratio(&adjusted_color, &background) >= self.min_contrast_ratio {
+        break;
+      }
+
+      amount += CONTRAST_ADJUSTMENT_STEP;
+    }
+
+    if amount >= 1.0 {
+      adjusted_color = target;
+    }
+
+    return Option::Some(adjusted_color.to_hex());
+  }
+}
+
+/// https://www.w3.org/TR/WCAG21/#dfn-relative-luminance
+fn relative_luminance(color: &RgbaColor) -> f64 {
+  let linear = |channel: u8| -> f64 {
+    let channel = channel as f64 / 255.0;
+
+    if channel <= 0.03928 {
+      return channel / 12.92;
+    }
+
+    return ((channel + 0.055) / 1.055).powf(2.4);
+  };
+
+  return 0.2126 * linear(color.red) + 0.7152 * linear(color.green) + 0.0722 * linear(color.blue);
+}
+
+/// 1.0 (the same luminance) ..= 21.0 (black on white)
+fn contrast_ratio(first: &RgbaColor, second: &RgbaColor) -> f64 {
+  let first_luminance = relative_luminance(first);
+  let second_luminance = relative_luminance(second);
+
+  return (first_luminance.max(second_luminance) + 0.05) / (first_luminance.min(second_luminance) + 0.05);
+}
+
+fn mix(color: &RgbaColor, target: &RgbaColor, amount: f64) -> RgbaColor {
+  let mix_channel = |from: u8, to: u8| -> u8 {
+    return (from as f64 + (to as f64 - from as f64) * amount).round() as u8;
+  };
+
+  return RgbaColor::new(
+    mix_channel(color.red, target.red),
+    mix_channel(color.green, target.green),
+    mix_channel(color.blue, target.blue),
+    color.alpha
+  );
+}
+
+#[test]
+fn test_contrast_ratio() {
+  let black = RgbaColor::new(0, 0, 0, 255);
+  let white = RgbaColor::new(255, 255, 255, 255);
+
+  assert!((contrast_ratio(&black, &white) - 21.0).abs() < 0.01);
+  assert!((contrast_ratio(&white, &white) - 1.0).abs() < 0.01);
+}
@@ -43,17 +43,10 @@ pub fn detect_and_extract_theme_json(total_text: &str, out_spannables: &mut Vec<
 }
 
 fn try_figure_out_is_theme_light(theme_json: &str) -> Option<bool> {
-  let theme_type_captures_maybe = THEME_TYPE_PATTERN.captures(theme_json);
-  if theme_type_captures_maybe.is_none() {
-    return Option::None
-  }
-
-  let theme_type_match_maybe = theme_type_captures_maybe.unwrap().get(1);
-  if theme_type_match_maybe.is_none() {
-    return Option::None
-  }
+  let theme_type_captures = THEME_TYPE_PATTERN.captures(theme_json)?;
+  let theme_type_match = theme_type_captures.get(1)?;
 
-  let parse_result_result = theme_type_match_maybe.unwrap().as_str().parse::<bool>();
+  let parse_result_result = theme_type_match.as_str().parse::<bool>();
   if let Result::Err { .. } = parse_result_result {
     return Option::None;
   }
@@ -85,6 +78,8 @@ fn is_probably_theme_json(theme_json: &str) -> bool {
   return true;
 }
 
+/// Byte ranges of the top-level "{...}" parts of [total_text]. The end is exclusive (the index after the
+/// closing bracket) so a range can be used to slice [total_text] directly.
 fn collect_all_json_parts(total_text: &str) -> Vec<(usize, usize)> {
   let mut json_open_bracket_index = -1;
   let mut opened_brackets_count = 0;
@@ -95,7 +90,7 @@ fn collect_all_json_parts(total_text: &str) -> Vec<(usize, usize)> {
   }
 
   for (index, char) in total_text.char_indices() {
-    if char == '{' as char {
+    if char == '{' {
       opened_brackets_count += 1;
 
       if json_open_bracket_index == -1 {
@@ -105,7 +100,7 @@ fn collect_all_json_parts(total_text: &str) -> Vec<(usize, usize)> {
       continue;
     }
 
-    if char == '}' as char {
+    if char == '}' {
       opened_brackets_count -= 1;
 
       if opened_brackets_count < 0 {
@@ -129,7 +124,7 @@ fn text_contains_valid_json(total_text: &str) -> bool {
   let mut json_close_bracket_index = -1;
 
   for (index, char) in total_text.char_indices() {
-    if char == '{' as char && json_open_bracket_index == -1 {
+    if char == '{' && json_open_bracket_index == -1 {
       json_open_bracket_index = index as i32;
     }
 
@@ -181,7 +176,7 @@ fn test_simple_theme_json() {
 
   assert_eq!(1, json_parts.len());
   assert_eq!(0, json_parts[0].0);
-  assert_eq!(text.len() - 1, json_parts[0].1);
+  assert_eq!(text.len(), json_parts[0].1);
 }
 
 #[test]
@@ -0,0 +1,302 @@
+use std::rc::Rc;
+use crate::UrlKind;
+use crate::site_profiles::site_profile::split_absolute_url;
+
+const IMAGE_EXTENSIONS: [&str; 8] = ["jpg", "jpeg", "png", "gif", "webp", "bmp", "avif", "svg"];
+const VIDEO_EXTENSIONS: [&str; 5] = ["webm", "mp4", "mov", "mkv", "m4v"];
+const AUDIO_EXTENSIONS: [&str; 6] = ["mp3", "ogg", "flac", "wav", "m4a", "opus"];
+
+const YOUTUBE_VIDEO_ID_LENGTH: usize = 11;
+
+/// Decides what kind of content a url points to. [host] is lowercase without "www." and the port, [path]
+/// always starts with "/" and includes the query and the fragment ("/watch?v=57tu8AtKf9E#t=10").
+pub trait UrlClassifier {
+  fn classify(&self, host: &str, path: &str) -> Option<UrlKind>;
+}
+
+/// Classifiers are checked in order, the first one that recognizes a url wins. Urls nobody recognizes
+/// are [UrlKind::Generic].
+pub struct UrlClassifierRegistry {
+  classifiers: Vec<Rc<dyn UrlClassifier>>
+}
+
+impl Default for UrlClassifierRegistry {
+  fn default() -> Self {
+    return UrlClassifierRegistry::new();
+  }
+}
+
+impl UrlClassifierRegistry {
+  /// Registry with all of the built-in classifiers
+  pub fn new() -> UrlClassifierRegistry {
+    return UrlClassifierRegistry {
+      classifiers: vec![
+        Rc::new(YouTubeClassifier {}),
+        Rc::new(StreamableClassifier {}),
+        Rc::new(TwitterClassifier {}),
+        Rc::new(SoundCloudClassifier {}),
+        Rc::new(PixivClassifier {}),
+        Rc::new(PastebinClassifier {}),
+        // Goes last so that the sites above win even if their urls end with an extension
+        Rc::new(MediaFileClassifier {}),
+      ]
+    };
+  }
+
+  /// [url_classifier] is checked before the ones that are already registered
+  pub fn add_classifier(&mut self, url_classifier: Rc<dyn UrlClassifier>) {
+    self.classifiers.insert(0, url_classifier);
+  }
+
+  pub fn classify(&self, url: &str) -> UrlKind {
+    let split_url_maybe = split_absolute_url(url);
+    if split_url_maybe.is_none() {
+      return UrlKind::Generic;
+    }
+
+    let (host, path) = split_url_maybe.unwrap();
+
+    for classifier in &self.classifiers {
+      if let Option::Some(url_kind) = classifier.classify(&host, &path) {
+        return url_kind;
+      }
+    }
+
+    return UrlKind::Generic;
+  }
+}
+
+/// "https://www.youtube.com/watch?v=57tu8AtKf9E&t=1m30s", "https://youtu.be/57tu8AtKf9E?t=90",
+/// "https://www.youtube.com/shorts/57tu8AtKf9E" etc
+pub struct YouTubeClassifier {}
+
+impl UrlClassifier for YouTubeClassifier {
+  fn classify(&self, host: &str, path: &str) -> Option<UrlKind> {
+    let segments = path_segments(path);
+
+    let video_id = match host {
+      "youtu.be" => segments.first().copied(),
+      "youtube.com" | "m.youtube.com" | "music.youtube.com" | "youtube-nocookie.com" => {
+        match segments.as_slice() {
+          ["watch"] => query_param(path, "v"),
+          ["shorts", video_id, ..] | ["embed", video_id, ..] | ["live", video_id, ..] | ["v", video_id, ..] => Option::Some(*video_id),
+          _ => Option::None
+        }
+      }
+      _ => Option::None
+    }?;
+
+    let is_valid_video_id = video_id.len() == YOUTUBE_VIDEO_ID_LENGTH
+      && video_id.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '-' || ch == '_');
+
+    if !is_valid_video_id {
+      return Option::None;
+    }
+
+    let start_time_seconds = query_param(path, "t")
+      .or_else(|| query_param(path, "start"))
+      .or_else(|| fragment_param(path, "t"))
+      .and_then(parse_time_seconds);
+
+    return Option::Some(UrlKind::YouTube { video_id: String::from(video_id), start_time_seconds });
+  }
+}
+
+/// "https://streamable.com/abc123" or "https://streamable.com/e/abc123"
+pub struct StreamableClassifier {}
+
+impl UrlClassifier for StreamableClassifier {
+  fn classify(&self, host: &str, path: &str) -> Option<UrlKind> {
+    if host != "streamable.com" {
+      return Option::None;
+    }
+
+    let video_id = match path_segments(path).as_slice() {
+      [video_id] | ["e", video_id] | ["o", video_id] => *video_id,
+      _ => return Option::None
+    };
+
+    if video_id.is_empty() || !video_id.chars().all(|ch| ch.is_ascii_alphanumeric()) {
+      return Option::None;
+    }
+
+    return Option::Some(UrlKind::Streamable { video_id: String::from(video_id) });
+  }
+}
+
+/// "https://twitter.com/user/status/1234567890" or "https://x.com/user/status/1234567890/photo/1"
+pub struct TwitterClassifier {}
+
+impl UrlClassifier for TwitterClassifier {
+  fn classify(&self, host: &str, path: &str) -> Option<UrlKind> {
+    if !matches!(host, "twitter.com" | "mobile.twitter.com" | "x.com" | "fxtwitter.com" | "vxtwitter.com") {
+      return Option::None;
+    }
+
+    let tweet_id = match path_segments(path).as_slice() {
+      [_, "status", tweet_id, ..] => tweet_id.parse::<u64>().ok()?,
+      _ => return Option::None
+    };
+
+    return Option::Some(UrlKind::Twitter { tweet_id });
+  }
+}
+
+/// "https://soundcloud.com/artist/track" or "https://soundcloud.com/artist/sets/playlist"
+pub struct SoundCloudClassifier {}
+
+impl UrlClassifier for SoundCloudClassifier {
+  fn classify(&self, host: &str, path: &str) -> Option<UrlKind> {
+    if host != "soundcloud.com" && host != "m.soundcloud.com" {
+      return Option::None;
+    }
+
+    let segments = path_segments(path);
+    if segments.len() < 2 {
+      return Option::None;
+    }
+
+    return Option::Some(UrlKind::SoundCloud { track_path: segments.join("/") });
+  }
+}
+
+/// "https://www.pixiv.net/en/artworks/12345678" or
+/// "https://www.pixiv.net/member_illust.php?mode=medium&illust_id=12345678"
+pub struct PixivClassifier {}
+
+impl UrlClassifier for PixivClassifier {
+  fn classify(&self, host: &str, path: &str) -> Option<UrlKind> {
+    if host != "pixiv.net" {
+      return Option::None;
+    }
+
+    let artwork_id = match path_segments(path).as_slice() {
+      ["artworks", artwork_id] | [_, "artworks", artwork_id] => artwork_id.parse::<u64>().ok()?,
+      ["member_illust.php"] => query_param(path, "illust_id")?.parse::<u64>().ok()?,
+      _ => return Option::None
+    };
+
+    return Option::Some(UrlKind::Pixiv { artwork_id });
+  }
+}
+
+/// "https://pastebin.com/YTGdpqZL" or "https://pastebin.com/raw/YTGdpqZL"
+pub struct PastebinClassifier {}
+
+impl UrlClassifier for PastebinClassifier {
+  fn classify(&self, host: &str, path: &str) -> Option<UrlKind> {
+    if host != "pastebin.com" {
+      return Option::None;
+    }
+
+    let paste_id = match path_segments(path).as_slice() {
+      [paste_id] | ["raw", paste_id] | ["dl", paste_id] | ["embed", paste_id] => *paste_id,
+      _ => return Option::None
+    };
+
+    if paste_id.len() != 8 || !paste_id.chars().all(|ch| ch.is_ascii_alphanumeric()) {
+      return Option::None;
+    }
+
+    return Option::Some(UrlKind::Pastebin { paste_id: String::from(paste_id) });
+  }
+}
+
+/// Direct links to images/videos/audio by the extension of the file ("https://i.imgur.com/3CDmFQm.jpg")
+pub struct MediaFileClassifier {}
+
+impl UrlClassifier for MediaFileClassifier {
+  fn classify(&self, _: &str, path: &str) -> Option<UrlKind> {
+    let file_name = path_segments(path).last().copied()?;
+    let (_, extension) = file_name.rsplit_once('.')?;
+    let extension = extension.to_lowercase();
+
+    if IMAGE_EXTENSIONS.contains(&extension.as_str()) {
+      return Option::Some(UrlKind::Image { extension });
+    }
+
+    if VIDEO_EXTENSIONS.contains(&extension.as_str()) {
+      return Option::Some(UrlKind::Video { extension });
+    }
+
+    if AUDIO_EXTENSIONS.contains(&extension.as_str()) {
+      return Option::Some(UrlKind::Audio { extension });
+    }
+
+    return Option::None;
+  }
+}
+
+/// "/a/b/?c=1#d" -> ["a", "b"]
+pub fn path_segments(path: &str) -> Vec<&str> {
+  let path_end = path.find(['?', '#']).unwrap_or(path.len());
+
+  return path[..path_end]
+    .split('/')
+    .filter(|segment| !segment.is_empty())
+    .collect();
+}
+
+pub fn query_param<'a>(path: &'a str, name: &str) -> Option<&'a str> {
+  let query_start = path.find('?')? + 1;
+  let query_end = path[query_start..].find('#').map(|index| query_start + index).unwrap_or(path.len());
+
+  return find_param(&path[query_start..query_end], name);
+}
+
+fn fragment_param<'a>(path: &'a str, name: &str) -> Option<&'a str> {
+  let fragment_start = path.find('#')? + 1;
+  return find_param(&path[fragment_start..], name);
+}
+
+fn find_param<'a>(params: &'a str, name: &str) -> Option<&'a str> {
+  return params
+    .split('&')
+    .filter_map(|param| param.split_once('='))
+    .find(|(param_name, _)| *param_name == name)
+    .map(|(_, value)| value);
+}
+
+/// "90", "90s", "1m30s" or "1h2m3s" -> seconds
+fn parse_time_seconds(time: &str) -> Option<u64> {
+  if let Ok(seconds) = time.parse::<u64>() {
+    return Option::Some(seconds);
+  }
+
+  let mut total_seconds: u64 = 0;
+  let mut number: Option<u64> = Option::None;
+
+  for ch in time.chars() {
+    if let Some(digit) = ch.to_digit(10) {
+      number = Option::Some(number.unwrap_or(0).checked_mul(10)?.checked_add(digit as u64)?);
+      continue;
+    }
+
+    let multiplier = match ch {
+      'h' => 3600,
+      'm' => 60,
+      's' => 1,
+      _ => return Option::None
+    };
+
+    total_seconds = total_seconds.checked_add(number?.checked_mul(multiplier)?)?;
+    number = Option::None;
+  }
+
+  if number.is_some() {
+    return Option::None;
+  }
+
+  return Option::Some(total_seconds);
+}
+
+#[test]
+fn test_parse_time_seconds() {
+  assert_eq!(Option::Some(90), parse_time_seconds("90"));
+  assert_eq!(Option::Some(90), parse_time_seconds("90s"));
+  assert_eq!(Option::Some(90), parse_time_seconds("1m30s"));
+  assert_eq!(Option::Some(3723), parse_time_seconds("1h2m3s"));
+  assert_eq!(Option::None, parse_time_seconds("1x"));
+  assert_eq!(Option::None, parse_time_seconds("1m30"));
+  assert_eq!(Option::None, parse_time_seconds("m"));
+}
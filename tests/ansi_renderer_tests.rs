@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod test_main {
+  use new_post_parser_lib::{PostParserContext, PostRaw, PostParser, set_of, ParsedSpannableText, Spannable, SpannableData, PostDescriptor, PostLink, UrlKind, ChanThemeColorId};
+  use new_post_parser_lib::renderers::ansi_renderer::{AnsiRenderer, AnsiColorMode};
+  use std::collections::HashSet;
+
+  fn run_test(
+    ansi_renderer: &AnsiRenderer,
+    post_parser_context: &PostParserContext,
+    raw_comment: &str,
+    expected_text: &str
+  ) {
+    let post_raw = PostRaw::new("4chan", "g", 1234567890, 1234567891, 0, raw_comment);
+
+    let post_parser = PostParser::new(post_parser_context);
+    let parsed_post = post_parser.parse_post(&post_raw);
+
+    assert_eq!(expected_text, ansi_renderer.render_post(&parsed_post));
+  }
+
+  fn render_text(ansi_renderer: &AnsiRenderer, text: &str, spannables: Vec<Spannable>) -> String {
+    let parsed_spannable_text = ParsedSpannableText::new("", Box::new(String::from(text)), Box::new(spannables));
+    return ansi_renderer.render(&PostDescriptor::new("4chan", "g", 1, 1, 0), &parsed_spannable_text);
+  }
+
+  #[test]
+  fn ansi_renderer_test_greentext_and_spoiler() {
+    let post_comment_raw = "<span class=\"quote\">&gt;be me</span><br><s>spoiler</s>";
+    let expected_text = "\x1b[32m>be me\x1b[0m\n\x1b[7mspoiler\x1b[0m";
+
+    let ansi_renderer = AnsiRenderer::new(AnsiColorMode::TrueColor, Option::None);
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&ansi_renderer, &post_parser_context, post_comment_raw, expected_text);
+  }
+
+  #[test]
+  fn ansi_renderer_test_links_are_osc8_hyperlinks() {
+    let post_comment_raw = "<a href=\"/g/thread/76759434#p76759434\" class=\"quotelink\">&gt;&gt;76759434</a> see https://rentry.org/a";
+    let expected_text = "\x1b]8;;https://boards.4chan.org/g/thread/76759434#p76759434\x1b\\\x1b[4m>>76759434 →\x1b]8;;\x1b\\\x1b[0m see \
+    \x1b]8;;https://rentry.org/a\x1b\\\x1b[4mhttps://rentry.org/a\x1b]8;;\x1b\\\x1b[0m";
+
+    let ansi_renderer = AnsiRenderer::new(AnsiColorMode::Color16, Option::None);
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&ansi_renderer, &post_parser_context, post_comment_raw, expected_text);
+  }
+
+  #[test]
+  fn ansi_renderer_test_color_modes() {
+    let spannables = || vec![
+      Spannable { start: 0, len: 3, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#FF0000FF".to_string() } },
+    ];
+
+    assert_eq!(
+      "\x1b[38;2;255;0;0mred\x1b[0m",
+      render_text(&AnsiRenderer::new(AnsiColorMode::TrueColor, Option::None), "red", spannables())
+    );
+    assert_eq!(
+      "\x1b[38;5;196mred\x1b[0m",
+      render_text(&AnsiRenderer::new(AnsiColorMode::Color256, Option::None), "red", spannables())
+    );
+    assert_eq!(
+      "\x1b[91mred\x1b[0m",
+      render_text(&AnsiRenderer::new(AnsiColorMode::Color16, Option::None), "red", spannables())
+    );
+    assert_eq!(
+      "red",
+      render_text(&AnsiRenderer::new(AnsiColorMode::NoColor, Option::None), "red", spannables())
+    );
+  }
+
+  #[test]
+  fn ansi_renderer_test_theme_color_ids() {
+    let spannables = vec![
+      Spannable { start: 0, len: 4, spannable_data: SpannableData::TextForegroundColorId { color_id: ChanThemeColorId::PostSubjectColor } },
+      Spannable { start: 5, len: 4, spannable_data: SpannableData::TextForegroundColorId { color_id: ChanThemeColorId::PostNameColor } },
+      Spannable { start: 10, len: 5, spannable_data: SpannableData::TextForegroundColorId { color_id: ChanThemeColorId::PostInlineQuoteColor } },
+      Spannable { start: 16, len: 4, spannable_data: SpannableData::TextForegroundColorId { color_id: ChanThemeColorId::PostLinkColor } },
+      Spannable { start: 21, len: 4, spannable_data: SpannableData::TextForegroundColorId { color_id: ChanThemeColorId::AccentColor } },
+    ];
+
+    let ansi_renderer = AnsiRenderer::new(AnsiColorMode::Color16, Option::None);
+
+    assert_eq!(
+      "\x1b[34msubj\x1b[0m \x1b[32mname\x1b[0m \x1b[32m>text\x1b[0m \x1b[34mlink\x1b[0m rest",
+      render_text(&ansi_renderer, "subj name >text link rest", spannables)
+    );
+  }
+
+  #[test]
+  fn ansi_renderer_test_nested_styles_are_combined() {
+    let spannables = vec![
+      Spannable { start: 0, len: 9, spannable_data: SpannableData::BoldText },
+      Spannable { start: 5, len: 4, spannable_data: SpannableData::Spoiler },
+    ];
+
+    let ansi_renderer = AnsiRenderer::new(AnsiColorMode::TrueColor, Option::None);
+
+    assert_eq!(
+      "\x1b[1mbold \x1b[0m\x1b[1;7mboth\x1b[0m",
+      render_text(&ansi_renderer, "bold both", spannables)
+    );
+  }
+
+  #[test]
+  fn ansi_renderer_test_word_wrapping_keeps_sequences_balanced() {
+    let spannables = vec![
+      Spannable { start: 4, len: 9, spannable_data: SpannableData::BoldText },
+    ];
+
+    let ansi_renderer = AnsiRenderer::new(AnsiColorMode::TrueColor, Option::Some(10));
+
+    assert_eq!(
+      "one \x1b[1mtwo\x1b[0m\n\x1b[1mthree\x1b[0m four\nfive\nabcdefghij\nklm",
+      render_text(&ansi_renderer, "one two three four five abcdefghijklm", spannables)
+    );
+  }
+
+  #[test]
+  fn ansi_renderer_test_word_wrapping_with_style_change_inside_word() {
+    let ansi_renderer = AnsiRenderer::new(AnsiColorMode::TrueColor, Option::Some(10));
+
+    let spannables = vec![
+      Spannable { start: 10, len: 2, spannable_data: SpannableData::ItalicText },
+    ];
+
+    assert_eq!(
+      "aaa\nbbbbbb\x1b[3mcc\x1b[0mdd",
+      render_text(&ansi_renderer, "aaa bbbbbbccdd", spannables)
+    );
+
+    let spannables = vec![
+      Spannable { start: 8, len: 4, spannable_data: SpannableData::ItalicText },
+    ];
+
+    assert_eq!(
+      "abcdefgh\x1b[3mij\x1b[0m\n\x1b[3mkl\x1b[0mm",
+      render_text(&ansi_renderer, "abcdefghijklm", spannables)
+    );
+  }
+
+  #[test]
+  fn ansi_renderer_test_control_characters_are_not_written() {
+    let ansi_renderer = AnsiRenderer::new(AnsiColorMode::TrueColor, Option::None);
+
+    assert_eq!(
+      "\u{FFFD}]0;title\u{FFFD} \u{FFFD}[2Jtext\u{FFFD}\ttab",
+      render_text(&ansi_renderer, "\x1b]0;title\x07 \u{9b}[2Jtext\x1b\ttab", vec![])
+    );
+
+    let spannables = vec![
+      Spannable {
+        start: 0,
+        len: 4,
+        spannable_data: SpannableData::Link(PostLink::UrlLink {
+          link: "https://a.com/\x1b\\\x1b[2J\x07".to_string(),
+          url_kind: UrlKind::Generic
+        })
+      },
+    ];
+
+    assert_eq!(
+      "\x1b]8;;https://a.com/\\[2J\x1b\\\x1b[4mlink\x1b]8;;\x1b\\\x1b[0m",
+      render_text(&ansi_renderer, "link", spannables)
+    );
+
+    // The same goes for the text that comes from html entities
+    let post_comment_raw = "&#27;[31mred?";
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&ansi_renderer, &post_parser_context, post_comment_raw, "\u{FFFD}[31mred?");
+  }
+
+}
@@ -0,0 +1,115 @@
+#[cfg(test)]
+mod test_main {
+  use new_post_parser_lib::{BbCodeParser, HtmlParser, PostParserContext, PostRaw, PostParser, set_of, Spannable, SpannableData, PostLink};
+  use new_post_parser_lib::site_profiles::bbcode::BbCodeSiteProfile;
+  use std::collections::HashSet;
+
+  fn run_test(
+    post_parser_context: &PostParserContext,
+    raw_comment: &str,
+    expected_parsed_text: &str,
+    expected_spannables: &Vec<Spannable>
+  ) {
+    let post_raw = PostRaw::new("bbcode_site", "g", 1234567890, 1234567891, 0, raw_comment);
+
+    let mut post_parser = PostParser::new(post_parser_context);
+    post_parser.add_site_profile(&BbCodeSiteProfile::new("bbcode_site"));
+
+    let post_comment_parsed = post_parser.parse_post(&post_raw).post_comment_parsed;
+
+    assert_eq!(expected_parsed_text, post_comment_parsed.parsed_text.as_str());
+    assert_eq!(expected_spannables, post_comment_parsed.spannables.as_ref());
+  }
+
+  #[test]
+  fn bbcode_parser_test_node_tree() {
+    let text = "[B]bold [i]both[/i][/b]\n>>123 [spoiler]unclosed & [code=c][b]raw[/b]\n[/code] [/u]";
+
+    let expected = r#"<b>
+bold 
+<i>
+both
+<br>
+&gt;&gt;123 
+[spoiler]
+unclosed &amp; 
+<code>
+[b]raw[/b]
+
+ [/u]
+"#;
+
+    let nodes = BbCodeParser::new().parse(text);
+    assert_eq!(expected, HtmlParser::new().debug_concat_into_string(&nodes));
+  }
+
+  #[test]
+  fn bbcode_parser_test_spoiler_and_quotes() {
+    let post_comment_raw = "[spoiler]<secret>[/spoiler] >>1234567880 >>1234567885 >>>/a/1 >>>/a/";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 8, spannable_data: SpannableData::Spoiler },
+      Spannable { start: 9, len: 12, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567880 }) },
+      Spannable { start: 22, len: 19, spannable_data: SpannableData::Link(PostLink::Dead { post_no: 1234567885 }) },
+      Spannable { start: 42, len: 9, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: String::from("a"), thread_no: 1, post_no: 1 }) },
+      Spannable { start: 52, len: 6, spannable_data: SpannableData::Link(PostLink::BoardLink { board_code: String::from("a") }) },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(1234567880));
+    run_test(
+      &post_parser_context,
+      post_comment_raw,
+      "<secret> >>1234567880 >>1234567885 (DEAD) >>>/a/1 → >>>/a/",
+      &expected_spannables
+    );
+  }
+
+  #[test]
+  fn bbcode_parser_test_styles_after_text() {
+    let expected_spannables = vec![
+      Spannable { start: 2, len: 4, spannable_data: SpannableData::BoldText },
+      Spannable { start: 7, len: 2, spannable_data: SpannableData::ItalicText },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, "a [b]bold[/b] [i]it[/i]", "a bold it", &expected_spannables);
+  }
+
+  #[test]
+  fn bbcode_parser_test_code_and_math() {
+    let post_comment_raw = "[code]if a < b {\n  [b]x[/b]\n}[/code]";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 23, spannable_data: SpannableData::Monospace },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, post_comment_raw, "if a < b {\n  [b]x[/b]\n}", &expected_spannables);
+
+    // Quotes inside of code blocks are not links
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 12, spannable_data: SpannableData::Monospace },
+    ];
+
+    run_test(&post_parser_context, "[code]>>1234567880[/code]", ">>1234567880", &expected_spannables);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 8, spannable_data: SpannableData::Math { is_display_mode: true } },
+    ];
+
+    run_test(&post_parser_context, "[eqn]\\sqrt{2}[/eqn]", "\\sqrt{2}", &expected_spannables);
+  }
+
+  #[test]
+  fn bbcode_parser_test_unclosed_tags() {
+    let nodes = BbCodeParser::new().parse("[i]a[b]b[/i]c[/b]");
+    assert_eq!("<i>\na\n[b]\nb\nc[/b]\n", HtmlParser::new().debug_concat_into_string(&nodes));
+
+    // Every tag is unclosed, must not take quadratic time
+    let text = format!("{}{}{}", "[b][code]".repeat(50_000), "x", "[/i]".repeat(50_000));
+    let nodes = BbCodeParser::new().parse(&text);
+
+    assert_eq!(100_000, nodes.len());
+  }
+
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod test_main {
+  use new_post_parser_lib::{PostParserContext, PostRaw, PostParser, set_of, Spannable, SpannableData, PostLink, UrlKind, HashAlgorithm};
+  use std::collections::HashSet;
+
+  fn hash(algorithm: HashAlgorithm, value: &str) -> SpannableData {
+    return SpannableData::Hash { algorithm, value: value.to_string() };
+  }
+
+  fn parse_spannables(post_comment_raw: &str) -> Vec<Spannable> {
+    let post_raw = PostRaw::new("4chan", "t", 1234567890, 1234567891, 0, post_comment_raw);
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    let post_parser = PostParser::new(&post_parser_context);
+
+    return *post_parser.parse_post(&post_raw).post_comment_parsed.spannables;
+  }
+
+  #[test]
+  fn hash_detector_test_magnets_and_hashes() {
+    let post_comment_raw = "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a&amp;dn=Some+File%21 \
+    sha256: e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855 \
+    (md5 d41d8cd98f00b204e9800998ecf8427e) QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG";
+
+    let expected_spannables = vec![
+      Spannable {
+        start: 0,
+        len: 76,
+        spannable_data: SpannableData::Link(PostLink::UrlLink {
+          link: "magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a&dn=Some+File%21".to_string(),
+          url_kind: UrlKind::Generic
+        })
+      },
+      Spannable {
+        start: 0,
+        len: 76,
+        spannable_data: SpannableData::Magnet {
+          info_hash: "c12fe1c06bba254a9dc9f519b335aa7c1367a88a".to_string(),
+          display_name: Option::Some("Some File!".to_string())
+        }
+      },
+      Spannable { start: 85, len: 64, spannable_data: hash(HashAlgorithm::Sha256, "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855") },
+      Spannable { start: 155, len: 32, spannable_data: hash(HashAlgorithm::Md5, "d41d8cd98f00b204e9800998ecf8427e") },
+      Spannable { start: 189, len: 46, spannable_data: hash(HashAlgorithm::IpfsCid, "QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG") },
+    ];
+
+    assert_eq!(expected_spannables, parse_spannables(post_comment_raw));
+  }
+
+  #[test]
+  fn hash_detector_test_bare_info_hash() {
+    let expected_spannables = vec![
+      Spannable { start: 3, len: 40, spannable_data: hash(HashAlgorithm::Btih, "C12FE1C06BBA254A9DC9F519B335AA7C1367A88A") },
+    ];
+
+    assert_eq!(expected_spannables, parse_spannables("ih C12FE1C06BBA254A9DC9F519B335AA7C1367A88A."));
+  }
+
+  #[test]
+  fn hash_detector_test_not_hashes() {
+    let not_hashes = vec![
+      // Wrong length
+      "c12fe1c06bba254a9dc9f519b335aa7c1367a88",
+      "c12fe1c06bba254a9dc9f519b335aa7c1367a88aa",
+      // Not hex
+      "g12fe1c06bba254a9dc9f519b335aa7c1367a88a",
+      // Only digits
+      "12345678901234567890123456789012",
+      "1234567890123456789012345678901234567890",
+      // Part of a longer word
+      "c12fe1c06bba254a9dc9f519b335aa7c1367a88a_suffix",
+      "テストc12fe1c06bba254a9dc9f519b335aa7c1367a88a",
+      // Inside of a url
+      "https://example.com/c12fe1c06bba254a9dc9f519b335aa7c1367a88a",
+      // Magnet without a valid info hash only gets the url link
+      "magnet:?xt=urn:btih:xyz&amp;dn=file",
+    ];
+
+    for not_hash in not_hashes {
+      let has_hash_spannables = parse_spannables(not_hash)
+        .iter()
+        .any(|spannable| matches!(spannable.spannable_data, SpannableData::Hash { .. } | SpannableData::Magnet { .. }));
+
+      assert!(!has_hash_spannables, "text: {}", not_hash);
+    }
+  }
+
+}
@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod test_main {
+  use new_post_parser_lib::{PostParserContext, PostRaw, PostParser, set_of, ParsedSpannableText, Spannable, SpannableData, PostDescriptor};
+  use new_post_parser_lib::renderers::html_renderer::HtmlRenderer;
+  use new_post_parser_lib::renderers::site_url_templates::SiteUrlTemplates;
+  use std::collections::HashSet;
+
+  fn run_test(
+    post_parser_context: &PostParserContext,
+    raw_comment: &str,
+    expected_html: &str
+  ) {
+    let post_raw = PostRaw::new("4chan", "g", 1234567890, 1234567891, 0, raw_comment);
+
+    let post_parser = PostParser::new(post_parser_context);
+    let parsed_post = post_parser.parse_post(&post_raw);
+
+    let html_renderer = HtmlRenderer::new();
+    assert_eq!(expected_html, html_renderer.render_post(&parsed_post));
+  }
+
+  #[test]
+  fn html_renderer_test_quotes_and_greentext() {
+    let post_comment_raw = "<span class=\"quote\">&gt;implying &lt;tags&gt;</span><br>\
+    <a href=\"#p333520145\" class=\"quotelink\">&gt;&gt;333520145</a><br><a href=\"#p333520391\" class=\"quotelink\">&gt;&gt;333520391</a>";
+
+    let expected_html = "<span class=\"greentext\">&gt;implying &lt;tags&gt;</span><br>\
+    <a class=\"quotelink\" href=\"#p333520145\">&gt;&gt;333520145</a><br>\
+    <a class=\"quotelink deadlink\" href=\"#p333520391\">&gt;&gt;333520391 (DEAD)</a>";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(333520145));
+    run_test(&post_parser_context, post_comment_raw, expected_html);
+  }
+
+  #[test]
+  fn html_renderer_test_cross_thread_and_search_links() {
+    let post_comment_raw = "<a href=\"/g/thread/76759434#p76759434\" class=\"quotelink\">&gt;&gt;76759434</a> \
+    <a href=\"//boards.4channel.org/g/catalog#s=fglt\" class=\"quotelink\">&gt;&gt;&gt;/g/fglt</a>";
+
+    let expected_html = "<a class=\"quotelink\" href=\"https://boards.4chan.org/g/thread/76759434#p76759434\">&gt;&gt;76759434 →</a> \
+    <a class=\"quotelink\" href=\"https://boards.4chan.org/g/catalog#s=fglt\">&gt;&gt;&gt;/g/fglt</a>";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, post_comment_raw, expected_html);
+  }
+
+  #[test]
+  fn html_renderer_test_bold_and_style() {
+    let post_comment_raw = "<b style=\"color: red;\">(USER WAS BANNED FOR THIS POST)</b><br>";
+    let expected_html = "<span style=\"color:#FF0000FF\"><b>(USER WAS BANNED FOR THIS POST)</b></span><br>";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, post_comment_raw, expected_html);
+  }
+
+  #[test]
+  fn html_renderer_test_pre_keeps_line_breaks() {
+    let post_comment_raw = "<pre class=\"prettyprint\">fn main() {<br>  &lt;&gt;<br>}</pre>";
+    let expected_html = "<pre>fn main() {\n  &lt;&gt;\n}</pre>";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, post_comment_raw, expected_html);
+  }
+
+  #[test]
+  fn html_renderer_test_url_link_is_escaped() {
+    let post_comment_raw = "see https://example.com/?a=1&amp;b=\"2\" now";
+    let expected_html = "see <a rel=\"noreferrer noopener\" href=\"https://example.com/?a=1&amp;b=\">https://example.com/?a=1&amp;b=</a>\"2\" now";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, post_comment_raw, expected_html);
+  }
+
+  #[test]
+  fn html_renderer_test_spoiler() {
+    let post_comment_raw = "<s>Should I use a female version of my name</s>?";
+    let expected_html = "<s class=\"spoiler\">Should I use a female version of my name</s>?";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, post_comment_raw, expected_html);
+  }
+
+  #[test]
+  fn html_renderer_test_overlapping_spannables_are_split() {
+    let parsed_spannable_text = ParsedSpannableText::new(
+      "",
+      Box::new(String::from("bold spoiler")),
+      Box::new(vec![
+        Spannable { start: 0, len: 7, spannable_data: SpannableData::BoldText },
+        Spannable { start: 5, len: 7, spannable_data: SpannableData::Spoiler },
+      ])
+    );
+
+    let html_renderer = HtmlRenderer::new();
+    let html = html_renderer.render(&PostDescriptor::new("4chan", "g", 1, 1, 0), &parsed_spannable_text);
+
+    assert_eq!("<b>bold <s class=\"spoiler\">sp</s></b><s class=\"spoiler\">oiler</s>", html);
+  }
+
+  #[test]
+  fn html_renderer_test_custom_site_url_templates() {
+    let parsed_spannable_text = ParsedSpannableText::new(
+      "",
+      Box::new(String::from(">>>/b/")),
+      Box::new(vec![
+        Spannable {
+          start: 0,
+          len: 6,
+          spannable_data: SpannableData::Link(new_post_parser_lib::PostLink::BoardLink { board_code: "b".to_string() })
+        },
+      ])
+    );
+
+    let mut html_renderer = HtmlRenderer::new();
+    html_renderer.add_site_url_templates(
+      "2ch",
+      SiteUrlTemplates::new("#{post_no}", "https://2ch.hk/{board_code}/res/{thread_no}.html#{post_no}", "https://2ch.hk/{board_code}/", "")
+    );
+
+    let html = html_renderer.render(&PostDescriptor::new("2ch", "b", 1, 1, 0), &parsed_spannable_text);
+    assert_eq!("<a class=\"quotelink\" href=\"https://2ch.hk/b/\">&gt;&gt;&gt;/b/</a>", html);
+
+    // Unknown sites have no templates so the link is rendered as plain text
+    let html = html_renderer.render(&PostDescriptor::new("unknown", "b", 1, 1, 0), &parsed_spannable_text);
+    assert_eq!("&gt;&gt;&gt;/b/", html);
+  }
+
+}
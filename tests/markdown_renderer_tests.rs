@@ -0,0 +1,90 @@
+#[cfg(test)]
+mod test_main {
+  use new_post_parser_lib::{PostParserContext, PostRaw, PostParser, set_of, ParsedSpannableText, Spannable, SpannableData, PostDescriptor};
+  use new_post_parser_lib::renderers::markdown_renderer::{MarkdownRenderer, MarkdownSpoilerStyle};
+  use std::collections::HashSet;
+
+  fn run_test(
+    post_parser_context: &PostParserContext,
+    spoiler_style: MarkdownSpoilerStyle,
+    raw_comment: &str,
+    expected_markdown: &str
+  ) {
+    let post_raw = PostRaw::new("4chan", "g", 1234567890, 1234567891, 0, raw_comment);
+
+    let post_parser = PostParser::new(post_parser_context);
+    let parsed_post = post_parser.parse_post(&post_raw);
+
+    let markdown_renderer = MarkdownRenderer::new(spoiler_style);
+    assert_eq!(expected_markdown, markdown_renderer.render_post(&parsed_post));
+  }
+
+  #[test]
+  fn markdown_renderer_test_greentext_becomes_blockquote() {
+    let post_comment_raw = "<span class=\"quote\">&gt;be me</span><br><span class=\"quote\">&gt;write *markdown*</span><br>mfw";
+    let expected_markdown = "> \\>be me  \n> \\>write \\*markdown\\*  \n\nmfw";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, MarkdownSpoilerStyle::Pipes, post_comment_raw, expected_markdown);
+  }
+
+  #[test]
+  fn markdown_renderer_test_quotes_and_cross_thread_links() {
+    let post_comment_raw = "<a href=\"#p333520145\" class=\"quotelink\">&gt;&gt;333520145</a><br>\
+    <a href=\"/g/thread/76759434#p76759434\" class=\"quotelink\">&gt;&gt;76759434</a><br>\
+    https://en.wikipedia.org/wiki/Rust_(programming_language)";
+
+    let expected_markdown = "[\\>\\>333520145](#p333520145)  \n\
+    [\\>\\>76759434 →](https://boards.4chan.org/g/thread/76759434#p76759434)  \n\
+    [https://en.wikipedia.org/wiki/Rust\\_(programming\\_language)](https://en.wikipedia.org/wiki/Rust_%28programming_language%29)";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(333520145));
+    run_test(&post_parser_context, MarkdownSpoilerStyle::Pipes, post_comment_raw, expected_markdown);
+  }
+
+  #[test]
+  fn markdown_renderer_test_spoiler_styles() {
+    let post_comment_raw = "<s>Should I use a female version of my name</s>?";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+
+    run_test(
+      &post_parser_context,
+      MarkdownSpoilerStyle::Pipes,
+      post_comment_raw,
+      "||Should I use a female version of my name||?"
+    );
+    run_test(
+      &post_parser_context,
+      MarkdownSpoilerStyle::HtmlDetails,
+      post_comment_raw,
+      "<details><summary>Spoiler</summary>Should I use a female version of my name</details>?"
+    );
+  }
+
+  #[test]
+  fn markdown_renderer_test_pre_becomes_fenced_code_block() {
+    let post_comment_raw = "<pre class=\"prettyprint\">let a = `b`;<br>```</pre><br>and write a function";
+    let expected_markdown = "````\nlet a = `b`;\n```\n````\nand write a function";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, MarkdownSpoilerStyle::Pipes, post_comment_raw, expected_markdown);
+  }
+
+  #[test]
+  fn markdown_renderer_test_bold_markers_do_not_touch_whitespaces() {
+    let parsed_spannable_text = ParsedSpannableText::new(
+      "",
+      Box::new(String::from("this is bold\ntext")),
+      Box::new(vec![
+        Spannable { start: 4, len: 13, spannable_data: SpannableData::BoldText },
+      ])
+    );
+
+    let markdown_renderer = MarkdownRenderer::new(MarkdownSpoilerStyle::Pipes);
+    let markdown = markdown_renderer.render(&PostDescriptor::new("4chan", "g", 1, 1, 0), &parsed_spannable_text);
+
+    assert_eq!("this **is bold**  \n**text**", markdown);
+  }
+
+}
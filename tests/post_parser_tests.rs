@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test_main {
-  use new_post_parser_lib::{PostParserContext, Spannable, PostRaw, PostParser, SpannableData, PostLink, set_of, Element, ThreadDescriptor, BoardDescriptor, SiteDescriptor, PostDescriptor};
+  use new_post_parser_lib::{PostParserContext, Spannable, PostRaw, PostParser, SpannableData, PostLink, UrlKind, set_of, ThreadDescriptor, BoardDescriptor, SiteDescriptor, PostDescriptor};
   use std::collections::HashSet;
 
   fn create_post_parser_context(
@@ -19,7 +19,7 @@ mod test_main {
     post_parser_context: &PostParserContext,
     raw_comment: &str,
     expected_parsed_comment: &str,
-    expected_spannables: &Vec<Spannable>
+    expected_spannables: &[Spannable]
   ) {
     let thread_descriptor = ThreadDescriptor {
       board_descriptor: BoardDescriptor {
@@ -38,7 +38,7 @@ mod test_main {
       com: String::from(raw_comment)
     };
 
-    let post_parser = PostParser::new(&post_parser_context);
+    let post_parser = PostParser::new(post_parser_context);
     let post_comment_parsed = post_parser.parse_post(&post_raw).post_comment_parsed;
     let spannables = post_comment_parsed.spannables;
 
@@ -53,8 +53,6 @@ mod test_main {
 
       assert_eq!(expected_spannable, actual_spannable);
 
-      assert!(actual_spannable.start >= 0, "start={}", actual_spannable.start);
-
       assert!(
         (actual_spannable.start + actual_spannable.len) <= parsed_text.chars().count(),
         "end={}, chars_count={}",
@@ -228,8 +226,8 @@ stop you</span><br><s>Should I use a female version of my name for maximal self-
 
     let expected_spannables = vec![
       Spannable { start: 0, len: 11, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 333890765 }) },
-      Spannable { start: 11, len: 28, spannable_data: SpannableData::GreenText },
-      Spannable { start: 39, len: 68, spannable_data: SpannableData::Spoiler },
+      Spannable { start: 12, len: 27, spannable_data: SpannableData::GreenText },
+      Spannable { start: 40, len: 67, spannable_data: SpannableData::Spoiler },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -315,7 +313,7 @@ https://pastebin.com/1 https://w4534gerhnrh.com/2 https://pastebin.com/3
 https://www.youtube.com/watch?v=57tu8AtKf9E";
 
     let expected_parsed_comment = "https://www.youtube.com/watch?v=57tu8AtKf9E
-https://boards.4channel.org/vg/thread/333979978 test https://boards.4channel.org/v/
+https://boards.4channel.org/vg/thread/333979978 → test https://boards.4channel.org/v/
 
 http://visual-novels-general.wikia.com/wiki/
 https://sites.google.com/view/moechart/
@@ -330,21 +328,21 @@ https://pastebin.com/1 https://w4534gerhnrh.com/2 https://pastebin.com/3
 https://www.youtube.com/watch?v=57tu8AtKf9E";
 
     let expected_spannables = vec![
-      Spannable { start: 0, len: 43, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://www.youtube.com/watch?v=57tu8AtKf9E") }) },
-      Spannable { start: 44, len: 47, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://boards.4channel.org/vg/thread/333979978") }) },
-      Spannable { start: 97, len: 30, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://boards.4channel.org/v/") }) },
-      Spannable { start: 129, len: 44, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("http://visual-novels-general.wikia.com/wiki/") }) },
-      Spannable { start: 174, len: 39, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://sites.google.com/view/moechart/") }) },
-      Spannable { start: 214, len: 35, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://files.catbox.moe/143by7.png") }) },
-      Spannable { start: 258, len: 31, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://i.imgur.com/3CDmFQm.jpg") }) },
-      Spannable { start: 298, len: 17, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("http://vndb.org/g") }) },
-      Spannable { start: 316, len: 29, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://pastebin.com/YTGdpqZL") }) },
-      Spannable { start: 354, len: 29, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://pastebin.com/YTGdpqZL") }) },
-      Spannable { start: 384, len: 12, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("http://2.com") }) },
-      Spannable { start: 397, len: 22, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://pastebin.com/1") }) },
-      Spannable { start: 420, len: 26, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://w4534gerhnrh.com/2") }) },
-      Spannable { start: 447, len: 22, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://pastebin.com/3") }) },
-      Spannable { start: 471, len: 43, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://www.youtube.com/watch?v=57tu8AtKf9E") }) },
+      Spannable { start: 0, len: 43, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://www.youtube.com/watch?v=57tu8AtKf9E"), url_kind: UrlKind::YouTube { video_id: String::from("57tu8AtKf9E"), start_time_seconds: Option::None } }) },
+      Spannable { start: 44, len: 49, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: String::from("vg"), thread_no: 333979978, post_no: 333979978 }) },
+      Spannable { start: 99, len: 30, spannable_data: SpannableData::Link(PostLink::BoardLink { board_code: String::from("v") }) },
+      Spannable { start: 131, len: 44, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("http://visual-novels-general.wikia.com/wiki/"), url_kind: UrlKind::Generic }) },
+      Spannable { start: 176, len: 39, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://sites.google.com/view/moechart/"), url_kind: UrlKind::Generic }) },
+      Spannable { start: 216, len: 35, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://files.catbox.moe/143by7.png"), url_kind: UrlKind::Image { extension: String::from("png") } }) },
+      Spannable { start: 260, len: 31, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://i.imgur.com/3CDmFQm.jpg"), url_kind: UrlKind::Image { extension: String::from("jpg") } }) },
+      Spannable { start: 300, len: 17, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("http://vndb.org/g"), url_kind: UrlKind::Generic }) },
+      Spannable { start: 318, len: 29, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://pastebin.com/YTGdpqZL"), url_kind: UrlKind::Pastebin { paste_id: String::from("YTGdpqZL") } }) },
+      Spannable { start: 356, len: 29, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://pastebin.com/YTGdpqZL"), url_kind: UrlKind::Pastebin { paste_id: String::from("YTGdpqZL") } }) },
+      Spannable { start: 386, len: 12, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("http://2.com"), url_kind: UrlKind::Generic }) },
+      Spannable { start: 399, len: 22, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://pastebin.com/1"), url_kind: UrlKind::Generic }) },
+      Spannable { start: 422, len: 26, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://w4534gerhnrh.com/2"), url_kind: UrlKind::Generic }) },
+      Spannable { start: 449, len: 22, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://pastebin.com/3"), url_kind: UrlKind::Generic }) },
+      Spannable { start: 473, len: 43, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://www.youtube.com/watch?v=57tu8AtKf9E"), url_kind: UrlKind::YouTube { video_id: String::from("57tu8AtKf9E"), start_time_seconds: Option::None } }) },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -406,23 +404,23 @@ https://www.youtube.com/watch?v=57tu8AtKf9E";
     let expected_spannables = vec![
       Spannable { start: 18, len: 12, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: String::from("g"), thread_no: 76759434, post_no: 76759434 }) },
       Spannable { start: 0, len: 30, spannable_data: SpannableData::GreenText },
-      Spannable { start: 31, len: 22, spannable_data: SpannableData::GreenText },
+      Spannable { start: 32, len: 21, spannable_data: SpannableData::GreenText },
       Spannable { start: 53, len: 10, spannable_data: SpannableData::Link(PostLink::SearchLink { board_code: String::from("g"), search_query: String::from("fglt") }) },
-      Spannable { start: 64, len: 20, spannable_data: SpannableData::GreenText },
+      Spannable { start: 65, len: 19, spannable_data: SpannableData::GreenText },
       Spannable { start: 84, len: 9, spannable_data: SpannableData::Link(PostLink::SearchLink { board_code: String::from("g"), search_query: String::from("fwt") }) },
-      Spannable { start: 94, len: 15, spannable_data: SpannableData::GreenText },
+      Spannable { start: 95, len: 14, spannable_data: SpannableData::GreenText },
       Spannable { start: 109, len: 10, spannable_data: SpannableData::Link(PostLink::SearchLink { board_code: String::from("g"), search_query: String::from("pcbg") }) },
-      Spannable { start: 120, len: 24, spannable_data: SpannableData::GreenText },
+      Spannable { start: 121, len: 23, spannable_data: SpannableData::GreenText },
       Spannable { start: 144, len: 9, spannable_data: SpannableData::Link(PostLink::SearchLink { board_code: String::from("g"), search_query: String::from("dpt") }) },
-      Spannable { start: 154, len: 23, spannable_data: SpannableData::GreenText },
+      Spannable { start: 155, len: 22, spannable_data: SpannableData::GreenText },
       Spannable { start: 177, len: 9, spannable_data: SpannableData::Link(PostLink::SearchLink { board_code: String::from("g"), search_query: String::from("tpg") }) },
-      Spannable { start: 187, len: 20, spannable_data: SpannableData::GreenText },
+      Spannable { start: 188, len: 19, spannable_data: SpannableData::GreenText },
       Spannable { start: 207, len: 9, spannable_data: SpannableData::Link(PostLink::SearchLink { board_code: String::from("g"), search_query: String::from("csg") }) },
-      Spannable { start: 217, len: 19, spannable_data: SpannableData::GreenText },
+      Spannable { start: 218, len: 18, spannable_data: SpannableData::GreenText },
       Spannable { start: 236, len: 9, spannable_data: SpannableData::Link(PostLink::SearchLink { board_code: String::from("g"), search_query: String::from("hsg") }) },
-      Spannable { start: 246, len: 20, spannable_data: SpannableData::GreenText },
+      Spannable { start: 247, len: 19, spannable_data: SpannableData::GreenText },
       Spannable { start: 266, len: 9, spannable_data: SpannableData::Link(PostLink::SearchLink { board_code: String::from("g"), search_query: String::from("hpg") }) },
-      Spannable { start: 322, len: 33, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://rentry.org/installwindows") }) },
+      Spannable { start: 322, len: 33, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://rentry.org/installwindows"), url_kind: UrlKind::Generic }) },
       Spannable { start: 373, len: 12, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: String::from("g"), thread_no: 81404563, post_no: 81404563 }) },
     ];
 
@@ -471,7 +469,7 @@ what?";
 
     let expected_spannables = vec![
       Spannable { start: 0, len: 11, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 221656514 }) },
-      Spannable { start: 54, len: 74, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://s1.desu-usergeneratedcontent.xyz/a/image/1614/51/1614513969521.png") }) },
+      Spannable { start: 54, len: 74, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://s1.desu-usergeneratedcontent.xyz/a/image/1614/51/1614513969521.png"), url_kind: UrlKind::Image { extension: String::from("png") } }) },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -497,11 +495,102 @@ what?";
 
     let expected_spannables = vec![
       Spannable { start: 0, len: 13, spannable_data: SpannableData::GreenText },
-      Spannable { start: 14, len: 42, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://desuarchive.org/aco/thread/5189244") }) },
-      Spannable { start: 57, len: 20, spannable_data: SpannableData::GreenText },
-      Spannable { start: 78, len: 38, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://boards.4chan.org/search#/cyoag") }) },
-      Spannable { start: 117, len: 48, spannable_data: SpannableData::GreenText },
-      Spannable { start: 166, len: 29, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://pastebin.com/vrqYhnpu") }) },
+      Spannable { start: 14, len: 42, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://desuarchive.org/aco/thread/5189244"), url_kind: UrlKind::Generic }) },
+      Spannable { start: 58, len: 19, spannable_data: SpannableData::GreenText },
+      Spannable { start: 78, len: 38, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://boards.4chan.org/search#/cyoag"), url_kind: UrlKind::Generic }) },
+      Spannable { start: 118, len: 47, spannable_data: SpannableData::GreenText },
+      Spannable { start: 166, len: 29, spannable_data: SpannableData::Link(PostLink::UrlLink { link: String::from("https://pastebin.com/vrqYhnpu"), url_kind: UrlKind::Pastebin { paste_id: String::from("vrqYhnpu") } }) },
+    ];
+
+    let post_parser_context = create_post_parser_context(
+      set_of!(),
+      set_of!()
+    );
+
+    run_test(1235, 1235, &post_parser_context, post_comment_raw, expected_parsed_comment, &expected_spannables);
+  }
+
+  #[test]
+  fn post_parser_test_extension_embedder_after_whitespace() {
+    let post_comment_raw = "<a class=\"linkify\" href=\"https://voca.ro/abc\">https://voca.ro/abc</a> \
+    <a class=\"embedder\" href=\"javascript:;\" data-key=\"Vocaroo\" data-uid=\"abc\" data-href=\"https://voca.ro/abc\">(embed)</a>";
+
+    let expected_spannables = vec![
+      Spannable {
+        start: 0,
+        len: 19,
+        spannable_data: SpannableData::Link(PostLink::UrlLink {
+          link: String::from("https://voca.ro/abc"),
+          url_kind: UrlKind::Custom { kind: String::from("vocaroo"), id: String::from("abc") }
+        })
+      },
+    ];
+
+    let post_parser_context = create_post_parser_context(
+      set_of!(),
+      set_of!()
+    );
+
+    run_test(1235, 1235, &post_parser_context, post_comment_raw, "https://voca.ro/abc ", &expected_spannables);
+  }
+
+  #[test]
+  fn post_parser_test_extension_embedder_markup_removal() {
+    let post_comment_raw = "<s><a class=\"linkify twitter\" rel=\"noreferrer noopener\" target=\"_blank\" \
+    href=\"https://twitter.com/denonbu_eng/status/1388107521022468102\">https://twitter.com/denonbu_eng/sta<wbr>tus/1388107521022468102</a>\
+    <a class=\"embedder\" href=\"javascript:;\" data-key=\"Twitter\" data-uid=\"denonbu_eng/status/1388107521022468102\" \
+    data-options=\"undefined\" data-href=\"https://twitter.com/denonbu_eng/status/1388107521022468102\">(<span>un</span>embed)</a></s><br>\
+    <a class=\"linkify\" href=\"https://voca.ro/abc\">https://voca.ro/abc</a>\
+    <a class=\"embedder\" href=\"javascript:;\" data-key=\"Vocaroo\" data-uid=\"abc\" data-href=\"https://voca.ro/abc\">(embed)</a>";
+
+    let expected_parsed_comment = "https://twitter.com/denonbu_eng/status/1388107521022468102\nhttps://voca.ro/abc";
+
+    let expected_spannables = vec![
+      Spannable {
+        start: 0,
+        len: 58,
+        spannable_data: SpannableData::Link(PostLink::UrlLink {
+          link: String::from("https://twitter.com/denonbu_eng/status/1388107521022468102"),
+          url_kind: UrlKind::Twitter { tweet_id: 1388107521022468102 }
+        })
+      },
+      Spannable { start: 0, len: 58, spannable_data: SpannableData::Spoiler },
+      Spannable {
+        start: 59,
+        len: 19,
+        spannable_data: SpannableData::Link(PostLink::UrlLink {
+          link: String::from("https://voca.ro/abc"),
+          url_kind: UrlKind::Custom { kind: String::from("vocaroo"), id: String::from("abc") }
+        })
+      },
+    ];
+
+    let post_parser_context = create_post_parser_context(
+      set_of!(),
+      set_of!()
+    );
+
+    run_test(1235, 1235, &post_parser_context, post_comment_raw, expected_parsed_comment, &expected_spannables);
+  }
+
+  #[test]
+  fn post_parser_test_email_detection() {
+    let post_comment_raw = "Contact: John.Doe+jobs@Example.COM. Not: https://example.com/a/b@c.com, テストa@b.com, a@localhost<br>\
+    <a href=\"mailto:Sales@Example.com?subject=Hi\">mail us</a>";
+
+    let expected_parsed_comment = "Contact: John.Doe+jobs@Example.COM. Not: https://example.com/a/b@c.com, テストa@b.com, a@localhost\nmail us";
+
+    let expected_spannables = vec![
+      Spannable {
+        start: 41,
+        len: 29,
+        spannable_data: SpannableData::Link(PostLink::UrlLink {
+          link: String::from("https://example.com/a/b@c.com"),
+          url_kind: UrlKind::Generic
+        })
+      },
+      Spannable { start: 9, len: 25, spannable_data: SpannableData::Link(PostLink::EmailLink { email: String::from("John.Doe+jobs@example.com") }) },
+      Spannable { start: 96, len: 7, spannable_data: SpannableData::Link(PostLink::EmailLink { email: String::from("Sales@example.com") }) },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -552,9 +641,9 @@ what?";
 
     let expected_spannables = vec![
       Spannable { start: 193, len: 9, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 3878363 }) },
-      Spannable { start: 331, len: 28, spannable_data: SpannableData::BoldText },
-      Spannable { start: 416, len: 27, spannable_data: SpannableData::BoldText },
-      Spannable { start: 331, len: 187, spannable_data: SpannableData::Monospace },
+      Spannable { start: 332, len: 27, spannable_data: SpannableData::BoldText },
+      Spannable { start: 417, len: 26, spannable_data: SpannableData::BoldText },
+      Spannable { start: 332, len: 186, spannable_data: SpannableData::Monospace },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -571,8 +660,8 @@ what?";
     let expected_parsed_comment = "More of her? \n\n(USER WAS BANNED FOR THIS POST)";
 
     let expected_spannables = vec![
-      Spannable { start: 14, len: 32, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#FF0000".to_string() } },
-      Spannable { start: 14, len: 32, spannable_data: SpannableData::BoldText },
+      Spannable { start: 15, len: 31, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#FF0000FF".to_string() } },
+      Spannable { start: 15, len: 31, spannable_data: SpannableData::BoldText },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -583,14 +672,49 @@ what?";
     run_test(1235, 1235, &post_parser_context, post_comment_raw, expected_parsed_comment, &expected_spannables);
   }
 
+  #[test]
+  fn post_parser_test_bold_and_spoiler_start_at_element() {
+    let post_parser_context = create_post_parser_context(
+      set_of!(),
+      set_of!()
+    );
+
+    let expected_spannables = vec![
+      Spannable { start: 11, len: 2, spannable_data: SpannableData::BoldText },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, "aaaa bbbbbb<b>cc</b>dd", "aaaa bbbbbbccdd", &expected_spannables);
+
+    let expected_spannables = vec![
+      Spannable { start: 11, len: 2, spannable_data: SpannableData::Spoiler },
+      Spannable { start: 5, len: 10, spannable_data: SpannableData::BoldText },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, "aaaa <strong>bbbbbb<s>cc</s>dd</strong>", "aaaa bbbbbbccdd", &expected_spannables);
+  }
+
+  #[test]
+  fn post_parser_test_greentext_starts_at_element() {
+    let post_parser_context = create_post_parser_context(
+      set_of!(),
+      set_of!()
+    );
+
+    let expected_spannables = vec![
+      Spannable { start: 4, len: 4, spannable_data: SpannableData::GreenText },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, "aaa <span class=\"quote\">&gt;bbb</span>", "aaa >bbb", &expected_spannables);
+  }
+
   #[test]
   fn post_parser_test_style_attr1() {
     let post_comment_raw = "More of her? <br><br><strong style=\"color: red;\">(USER WAS BANNED FOR THIS POST)</strong>";
     let expected_parsed_comment = "More of her? \n\n(USER WAS BANNED FOR THIS POST)";
 
     let expected_spannables = vec![
-      Spannable { start: 14, len: 32, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#FF0000".to_string() } },
-      Spannable { start: 14, len: 32, spannable_data: SpannableData::BoldText },
+      Spannable { start: 15, len: 31, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#FF0000FF".to_string() } },
+      Spannable { start: 15, len: 31, spannable_data: SpannableData::BoldText },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -608,8 +732,8 @@ what?";
     let expected_parsed_comment = "1. You must check your #fortune in order to post in this thread.\n\nYour fortune: Excellent Luck";
 
     let expected_spannables = vec![
-      Spannable { start: 65, len: 29, spannable_data: SpannableData::BoldText },
-      Spannable { start: 0, len: 94, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#fd4d32".to_string() } },
+      Spannable { start: 66, len: 28, spannable_data: SpannableData::BoldText },
+      Spannable { start: 64, len: 30, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#FD4D32FF".to_string() } },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -620,6 +744,211 @@ what?";
     run_test(1235, 1235, &post_parser_context, post_comment_raw, expected_parsed_comment, &expected_spannables);
   }
 
+  #[test]
+  fn post_parser_test_style_attr3() {
+    let post_comment_raw = "<span style=\"font-weight: 600 !important; font-weight: normal; text-decoration: underline; \
+    background-color: yellow; font-family: 'Courier New', monospace\">Mod notice</span>";
+    let expected_parsed_comment = "Mod notice";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 10, spannable_data: SpannableData::FontWeight { weight: 600 } },
+      Spannable { start: 0, len: 10, spannable_data: SpannableData::UnderlineText },
+      Spannable { start: 0, len: 10, spannable_data: SpannableData::TextBackgroundColorRaw { color_hex: "#FFFF00FF".to_string() } },
+      Spannable { start: 0, len: 10, spannable_data: SpannableData::Monospace },
+    ];
+
+    let post_parser_context = create_post_parser_context(
+      set_of!(),
+      set_of!()
+    );
+
+    run_test(1235, 1235, &post_parser_context, post_comment_raw, expected_parsed_comment, &expected_spannables);
+  }
+
+  #[test]
+  fn post_parser_test_font_size_scale() {
+    let post_comment_raw = "<span style=\"font-size: 500px\">HUGE</span>";
+    let post_raw = PostRaw::new("4chan", "g", 1234567890, 1234567891, 0, post_comment_raw);
+
+    let post_parser_context = create_post_parser_context(
+      set_of!(),
+      set_of!()
+    );
+
+    let mut post_parser = PostParser::new(&post_parser_context);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 4, spannable_data: SpannableData::FontSize { size: "500px".to_string(), scale: 2.0 } },
+    ];
+
+    assert_eq!(&expected_spannables, post_parser.parse_post(&post_raw).post_comment_parsed.spannables.as_ref());
+
+    post_parser.set_font_size_scale_range(0.75, 4.0);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 4, spannable_data: SpannableData::FontSize { size: "500px".to_string(), scale: 4.0 } },
+    ];
+
+    assert_eq!(&expected_spannables, post_parser.parse_post(&post_raw).post_comment_parsed.spannables.as_ref());
+
+    // Non-finite bounds are replaced with the default ones
+    post_parser.set_font_size_scale_range(f32::NAN, f32::INFINITY);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 4, spannable_data: SpannableData::FontSize { size: "500px".to_string(), scale: 2.0 } },
+    ];
+
+    assert_eq!(&expected_spannables, post_parser.parse_post(&post_raw).post_comment_parsed.spannables.as_ref());
+
+    post_parser.set_font_size_scale_range(3.0, f32::NAN);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 4, spannable_data: SpannableData::FontSize { size: "500px".to_string(), scale: 3.0 } },
+    ];
+
+    assert_eq!(&expected_spannables, post_parser.parse_post(&post_raw).post_comment_parsed.spannables.as_ref());
+  }
+
+  #[test]
+  fn post_parser_test_inline_text_style_tags() {
+    let post_parser_context = create_post_parser_context(
+      set_of!(),
+      set_of!()
+    );
+
+    let test_cases = vec![
+      ("b", SpannableData::BoldText),
+      ("strong", SpannableData::BoldText),
+      ("s", SpannableData::Spoiler),
+      ("i", SpannableData::ItalicText),
+      ("em", SpannableData::ItalicText),
+      ("u", SpannableData::UnderlineText),
+      ("ins", SpannableData::UnderlineText),
+      ("del", SpannableData::StrikethroughText),
+      ("strike", SpannableData::StrikethroughText),
+      ("sup", SpannableData::SuperscriptText),
+      ("sub", SpannableData::SubscriptText),
+      ("small", SpannableData::FontSize { size: "smaller".to_string(), scale: 5.0 / 6.0 }),
+    ];
+
+    for (tag, spannable_data) in test_cases {
+      let post_comment_raw = format!("<{}>styled</{}> text", tag, tag);
+      let expected_spannables = vec![Spannable { start: 0, len: 6, spannable_data: spannable_data.clone() }];
+
+      run_test(1235, 1235, &post_parser_context, &post_comment_raw, "styled text", &expected_spannables);
+
+      // The spannable starts at the element, not at the text that goes before it
+      let post_comment_raw = format!("foo bar <{}>styled</{}> text", tag, tag);
+      let expected_spannables = vec![Spannable { start: 8, len: 6, spannable_data }];
+
+      run_test(1235, 1235, &post_parser_context, &post_comment_raw, "foo bar styled text", &expected_spannables);
+    }
+  }
+
+  #[test]
+  fn post_parser_test_inline_text_style_tags_nesting() {
+    let post_parser_context = create_post_parser_context(
+      set_of!(),
+      set_of!()
+    );
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 7, spannable_data: SpannableData::ItalicText },
+      Spannable { start: 0, len: 7, spannable_data: SpannableData::Spoiler },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, "<s><i>spoiler</i></s>", "spoiler", &expected_spannables);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 7, spannable_data: SpannableData::Spoiler },
+      Spannable { start: 0, len: 7, spannable_data: SpannableData::StrikethroughText },
+      Spannable { start: 0, len: 7, spannable_data: SpannableData::UnderlineText },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, "<u><del><s>spoiler</s></del></u>", "spoiler", &expected_spannables);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 9, spannable_data: SpannableData::GreenText },
+      Spannable { start: 0, len: 9, spannable_data: SpannableData::ItalicText },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, "<em><span class=\"quote\">&gt;implying</span></em>", ">implying", &expected_spannables);
+
+    let expected_spannables = vec![
+      Spannable { start: 8, len: 2, spannable_data: SpannableData::SubscriptText },
+      Spannable { start: 4, len: 6, spannable_data: SpannableData::UnderlineText },
+      Spannable { start: 15, len: 3, spannable_data: SpannableData::ItalicText },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, "foo <u>bar <sub>x2</sub></u> and <i>baz</i>", "foo bar x2 and baz", &expected_spannables);
+  }
+
+  #[test]
+  fn post_parser_test_block_elements() {
+    let post_parser_context = create_post_parser_context(
+      set_of!(),
+      set_of!()
+    );
+
+    let post_comment_raw = "<h1>Rules</h1><p>Be nice.</p><hr><ol start=\"3\"><li>One</li><li>Two<ul><li>Nested</li></ul></li></ol><blockquote>Quoted</blockquote>text";
+    let expected_parsed_comment = "Rules\nBe nice.\n──────────\n3. One\n4. Two\n  • Nested\nQuoted\ntext";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 5, spannable_data: SpannableData::Heading { level: 1 } },
+      Spannable { start: 15, len: 10, spannable_data: SpannableData::HorizontalRule },
+      Spannable { start: 26, len: 6, spannable_data: SpannableData::ListItem },
+      Spannable { start: 40, len: 10, spannable_data: SpannableData::ListItem },
+      Spannable { start: 33, len: 17, spannable_data: SpannableData::ListItem },
+      Spannable { start: 51, len: 6, spannable_data: SpannableData::BlockQuote },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, post_comment_raw, expected_parsed_comment, &expected_spannables);
+
+    // Text around the blocks goes on separate lines, empty blocks do not add empty lines
+    let post_comment_raw = "before<div>inside</div><div></div>after<br><p>line</p>";
+    let expected_parsed_comment = "before\ninside\nafter\nline";
+
+    run_test(1235, 1235, &post_parser_context, post_comment_raw, expected_parsed_comment, &[]);
+
+    let post_comment_raw = "<h3><b>Bold</b> heading</h3>";
+    let expected_parsed_comment = "Bold heading";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 4, spannable_data: SpannableData::BoldText },
+      Spannable { start: 0, len: 12, spannable_data: SpannableData::Heading { level: 3 } },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, post_comment_raw, expected_parsed_comment, &expected_spannables);
+
+    // Spannables of the elements around the last block do not cover the removed line break
+    let post_comment_raw = "intro <i><ul><li>One</li></ul></i>";
+    let expected_parsed_comment = "intro \n• One";
+
+    let expected_spannables = vec![
+      Spannable { start: 7, len: 5, spannable_data: SpannableData::ListItem },
+      Spannable { start: 6, len: 6, spannable_data: SpannableData::ItalicText },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, post_comment_raw, expected_parsed_comment, &expected_spannables);
+
+    let expected_spannables = vec![
+      Spannable { start: 5, len: 10, spannable_data: SpannableData::HorizontalRule },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, "text<hr>", "text\n──────────", &expected_spannables);
+
+    // The numbers of the items stop at the largest one instead of overflowing
+    let post_comment_raw = "<ol start=\"9223372036854775807\"><li>a</li><li>b</li></ol>";
+    let expected_parsed_comment = "9223372036854775807. a\n9223372036854775807. b";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 22, spannable_data: SpannableData::ListItem },
+      Spannable { start: 23, len: 22, spannable_data: SpannableData::ListItem },
+    ];
+
+    run_test(1235, 1235, &post_parser_context, post_comment_raw, expected_parsed_comment, &expected_spannables);
+  }
+
   #[test]
   fn post_parser_test_pre_tag() {
     let post_comment_raw = "<a href=\"/g/thread/81446291#p81478722\" class=\"quotelink\">&gt;&gt;81478722</a><br>nvm I guess I will just use<br>\
@@ -629,7 +958,7 @@ what?";
 
     let expected_spannables = vec![
       Spannable { start: 0, len: 12, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "g".to_string(), thread_no: 81446291, post_no: 81478722 }) },
-      Spannable { start: 40, len: 29, spannable_data: SpannableData::Monospace },
+      Spannable { start: 41, len: 28, spannable_data: SpannableData::Monospace },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -721,27 +1050,27 @@ what?";
 
     let expected_spannables = vec![
       Spannable { start: 34, len: 13, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "vg".to_string(), thread_no: 334945645, post_no: 334945645 }) },
-      Spannable { start: 48, len: 24, spannable_data: SpannableData::GreenText },
-      Spannable { start: 116, len: 44, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://projectignis.github.io/download.html".to_string() }) },
-      Spannable { start: 199, len: 27, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://www.duelingbook.com".to_string() }) },
-      Spannable { start: 311, len: 14, spannable_data: SpannableData::GreenText },
-      Spannable { start: 336, len: 61, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "http://www.yugioh-card.com/en/rulebook/SD_RuleBook_EN_V10.pdf".to_string() }) },
-      Spannable { start: 404, len: 36, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://yugipedia.com/wiki/Yugipedia".to_string() }) },
-      Spannable { start: 465, len: 19, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "http://yugioh.party".to_string() }) },
-      Spannable { start: 499, len: 23, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "http://yugiohprices.com".to_string() }) },
-      Spannable { start: 533, len: 30, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://www.db.yugioh-card.com".to_string() }) },
-      Spannable { start: 564, len: 11, spannable_data: SpannableData::GreenText },
-      Spannable { start: 581, len: 45, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://www.izazin.com/taikai/results?tag=遊戯王".to_string() }) },
-      Spannable { start: 632, len: 35, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "http://yugiohtopdecks.com/decklists".to_string() }) },
-      Spannable { start: 668, len: 6, spannable_data: SpannableData::GreenText },
-      Spannable { start: 679, len: 29, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "http://blog.livedoor.jp/maxut".to_string() }) },
-      Spannable { start: 713, len: 26, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://ygorganization.com".to_string() }) },
-      Spannable { start: 740, len: 19, spannable_data: SpannableData::GreenText },
-      Spannable { start: 1306, len: 23, spannable_data: SpannableData::GreenText },
-      Spannable { start: 1361, len: 29, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://challonge.com/dngcup8".to_string() }) },
-      Spannable { start: 1428, len: 34, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://challonge.com/dngfisherman".to_string() }) },
-      Spannable { start: 1496, len: 28, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://challonge.com/dngHAT".to_string() }) },
-      Spannable { start: 1565, len: 35, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://challonge.com/dngbattlecity".to_string() }) },
+      Spannable { start: 49, len: 23, spannable_data: SpannableData::GreenText },
+      Spannable { start: 116, len: 44, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://projectignis.github.io/download.html".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 199, len: 27, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://www.duelingbook.com".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 312, len: 13, spannable_data: SpannableData::GreenText },
+      Spannable { start: 336, len: 61, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "http://www.yugioh-card.com/en/rulebook/SD_RuleBook_EN_V10.pdf".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 404, len: 36, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://yugipedia.com/wiki/Yugipedia".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 465, len: 19, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "http://yugioh.party".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 499, len: 23, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "http://yugiohprices.com".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 533, len: 30, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://www.db.yugioh-card.com".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 565, len: 10, spannable_data: SpannableData::GreenText },
+      Spannable { start: 581, len: 45, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://www.izazin.com/taikai/results?tag=遊戯王".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 632, len: 35, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "http://yugiohtopdecks.com/decklists".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 669, len: 5, spannable_data: SpannableData::GreenText },
+      Spannable { start: 679, len: 29, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "http://blog.livedoor.jp/maxut".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 713, len: 26, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://ygorganization.com".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 741, len: 18, spannable_data: SpannableData::GreenText },
+      Spannable { start: 1307, len: 22, spannable_data: SpannableData::GreenText },
+      Spannable { start: 1361, len: 29, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://challonge.com/dngcup8".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 1428, len: 34, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://challonge.com/dngfisherman".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 1496, len: 28, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://challonge.com/dngHAT".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable { start: 1565, len: 35, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://challonge.com/dngbattlecity".to_string(), url_kind: UrlKind::Generic }) },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -758,7 +1087,7 @@ what?";
     let expected_parsed_comment = "●/dng/ Battle City (June 27, 1600 UTC): https://challonge.com/dngbattlecity";
 
     let expected_spannables = vec![
-      Spannable { start: 40, len: 35, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://challonge.com/dngbattlecity".to_string() }) },
+      Spannable { start: 40, len: 35, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://challonge.com/dngbattlecity".to_string(), url_kind: UrlKind::Generic }) },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -775,7 +1104,7 @@ what?";
     let expected_parsed_comment = "/dng/ Battle City (June 27, 1600 UTC): https://www.izazin.com/taikai/results?tag=遊戯王";
 
     let expected_spannables = vec![
-      Spannable { start: 39, len: 45, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://www.izazin.com/taikai/results?tag=遊戯王".to_string() }) },
+      Spannable { start: 39, len: 45, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://www.izazin.com/taikai/results?tag=遊戯王".to_string(), url_kind: UrlKind::Generic }) },
     ];
 
     let post_parser_context = create_post_parser_context(
@@ -0,0 +1,128 @@
+#[cfg(test)]
+mod test_main {
+  use new_post_parser_lib::{PostParserContext, PostRaw, PostParser, set_of, ParsedSpannableText, Spannable, SpannableData, PostLink, UrlKind};
+  use new_post_parser_lib::renderers::preview::{PreviewOptions, PreviewLengthUnit, PreviewUrlMode};
+  use std::collections::HashSet;
+
+  fn run_test(
+    post_parser_context: &PostParserContext,
+    raw_comment: &str,
+    preview_options: &PreviewOptions,
+    expected_preview: &str,
+    expected_spannables: &Vec<Spannable>
+  ) {
+    let post_raw = PostRaw::new("4chan", "g", 1234567890, 1234567891, 0, raw_comment);
+
+    let post_parser = PostParser::new(post_parser_context);
+    let post_comment_parsed = post_parser.parse_post(&post_raw).post_comment_parsed;
+    let preview = post_comment_parsed.preview(preview_options);
+
+    assert_eq!(expected_preview, preview.parsed_text.as_str());
+    assert_eq!(expected_spannables, preview.spannables.as_ref());
+  }
+
+  #[test]
+  fn preview_test_whitespaces_are_collapsed() {
+    let post_comment_raw = "<a href=\"#p333520145\" class=\"quotelink\">&gt;&gt;333520145</a><br><br>   Feel free<br>to tell me<br><br>";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 11, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 333520145 }) },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(333520145));
+    run_test(&post_parser_context, post_comment_raw, &PreviewOptions::new(100), ">>333520145 Feel free to tell me", &expected_spannables);
+  }
+
+  #[test]
+  fn preview_test_spoilers_are_hidden() {
+    let post_comment_raw = "<s>Snape kills Dumbledore</s> lol";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 9, spannable_data: SpannableData::Spoiler },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, post_comment_raw, &PreviewOptions::new(100), "[spoiler] lol", &expected_spannables);
+  }
+
+  #[test]
+  fn preview_test_urls_are_shortened_or_stripped() {
+    let post_comment_raw = "watch https://www.youtube.com/watch?v=57tu8AtKf9E now";
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+
+    let expected_spannables = vec![
+      Spannable { start: 6, len: 11, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://www.youtube.com/watch?v=57tu8AtKf9E".to_string(), url_kind: UrlKind::YouTube { video_id: "57tu8AtKf9E".to_string(), start_time_seconds: Option::None } }) },
+    ];
+
+    run_test(&post_parser_context, post_comment_raw, &PreviewOptions::new(100), "watch youtube.com now", &expected_spannables);
+
+    let mut preview_options = PreviewOptions::new(100);
+    preview_options.url_mode = PreviewUrlMode::Strip;
+    run_test(&post_parser_context, post_comment_raw, &preview_options, "watch now", &vec![]);
+  }
+
+  #[test]
+  fn preview_test_truncation_never_cuts_post_links() {
+    let post_comment_raw = "Previous Thread <a href=\"/g/thread/81404563#p81404563\" class=\"quotelink\">&gt;&gt;81404563</a>";
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+
+    run_test(&post_parser_context, post_comment_raw, &PreviewOptions::new(20), "Previous Thread…", &vec![]);
+
+    let expected_spannables = vec![
+      Spannable { start: 16, len: 12, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "g".to_string(), thread_no: 81404563, post_no: 81404563 }) },
+    ];
+
+    run_test(&post_parser_context, post_comment_raw, &PreviewOptions::new(28), "Previous Thread >>81404563 →", &expected_spannables);
+  }
+
+  #[test]
+  fn preview_test_truncation_by_grapheme_clusters() {
+    // Every flag is 2 unicode characters but a single grapheme cluster
+    let parsed_spannable_text = ParsedSpannableText::new(
+      "",
+      Box::new(String::from("🇯🇵🇯🇵🇯🇵🇯🇵")),
+      Box::new(vec![
+        Spannable { start: 0, len: 8, spannable_data: SpannableData::BoldText },
+      ])
+    );
+
+    let mut preview_options = PreviewOptions::new(3);
+    let preview = parsed_spannable_text.preview(&preview_options);
+
+    assert_eq!("🇯🇵🇯🇵…", preview.parsed_text.as_str());
+    assert_eq!(&vec![Spannable { start: 0, len: 4, spannable_data: SpannableData::BoldText }], preview.spannables.as_ref());
+
+    preview_options.length_unit = PreviewLengthUnit::Characters;
+    let preview = parsed_spannable_text.preview(&preview_options);
+
+    assert_eq!("🇯🇵…", preview.parsed_text.as_str());
+    assert_eq!(&vec![Spannable { start: 0, len: 2, spannable_data: SpannableData::BoldText }], preview.spannables.as_ref());
+  }
+
+  #[test]
+  fn preview_test_max_length_shorter_than_ellipsis() {
+    let parsed_spannable_text = ParsedSpannableText::new(
+      "",
+      Box::new(String::from("some text")),
+      Box::new(vec![
+        Spannable { start: 0, len: 4, spannable_data: SpannableData::BoldText },
+      ])
+    );
+
+    let mut preview_options = PreviewOptions::new(0);
+    preview_options.ellipsis = String::from("...");
+
+    for (max_length, expected_text) in [(0, ""), (1, "."), (2, ".."), (3, "..."), (4, "s...")] {
+      preview_options.max_length = max_length;
+      let preview = parsed_spannable_text.preview(&preview_options);
+
+      assert_eq!(expected_text, preview.parsed_text.as_str(), "max_length: {}", max_length);
+      assert!(preview.parsed_text.chars().count() <= max_length);
+    }
+
+    let preview = parsed_spannable_text.preview(&PreviewOptions::new(0));
+    assert_eq!("", preview.parsed_text.as_str());
+    assert!(preview.spannables.is_empty());
+  }
+
+}
@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod test_main {
+  use new_post_parser_lib::{PostParserContext, PostRaw, PostParser, set_of, Spannable, SpannableData, PostLink, SearchQuery};
+  use std::collections::HashSet;
+  use regex::Regex;
+
+  fn run_test(
+    post_parser_context: &PostParserContext,
+    search_query: SearchQuery,
+    raw_comment: &str,
+    expected_parsed_text: &str,
+    expected_spannables: &Vec<Spannable>
+  ) {
+    let post_raw = PostRaw::new("4chan", "g", 1234567890, 1234567891, 0, raw_comment);
+
+    let mut post_parser = PostParser::new(post_parser_context);
+    post_parser.set_search_query(Option::Some(search_query));
+
+    let post_comment_parsed = post_parser.parse_post(&post_raw).post_comment_parsed;
+
+    assert_eq!(expected_parsed_text, post_comment_parsed.parsed_text.as_str());
+    assert_eq!(expected_spannables, post_comment_parsed.spannables.as_ref());
+  }
+
+  fn plain(query: &str) -> SearchQuery {
+    return SearchQuery::Plain { query: query.to_string(), ignore_case: true, ignore_diacritics: true };
+  }
+
+  #[test]
+  fn search_highlight_test_offsets_after_entities_and_dead_suffix() {
+    let post_comment_raw = "<a href=\"#p333520391\" class=\"quotelink\">&gt;&gt;333520391</a><br>&lt;Café&gt; &amp; cafe";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 18, spannable_data: SpannableData::Link(PostLink::Dead { post_no: 333520391 }) },
+      Spannable { start: 20, len: 4, spannable_data: SpannableData::SearchHighlight },
+      Spannable { start: 28, len: 4, spannable_data: SpannableData::SearchHighlight },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, plain("CAFE"), post_comment_raw, ">>333520391 (DEAD)\n<Café> & cafe", &expected_spannables);
+  }
+
+  #[test]
+  fn search_highlight_test_case_and_diacritics_sensitive() {
+    let post_comment_raw = "Café cafe CAFE";
+
+    let expected_spannables = vec![
+      Spannable { start: 5, len: 4, spannable_data: SpannableData::SearchHighlight },
+    ];
+
+    let search_query = SearchQuery::Plain { query: "cafe".to_string(), ignore_case: false, ignore_diacritics: false };
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, search_query, post_comment_raw, "Café cafe CAFE", &expected_spannables);
+  }
+
+  #[test]
+  fn search_highlight_test_regex() {
+    let post_comment_raw = "ニコニコ 123 abc 4567";
+
+    let expected_spannables = vec![
+      Spannable { start: 5, len: 3, spannable_data: SpannableData::SearchHighlight },
+      Spannable { start: 13, len: 4, spannable_data: SpannableData::SearchHighlight },
+    ];
+
+    let search_query = SearchQuery::Regex { regex: Regex::new(r"\d+").unwrap() };
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test(&post_parser_context, search_query, post_comment_raw, "ニコニコ 123 abc 4567", &expected_spannables);
+  }
+
+  #[test]
+  fn search_highlight_test_match_ranges() {
+    let post_raw = PostRaw::new("4chan", "g", 1234567890, 1234567891, 0, "<b>test</b> one test two<br>test");
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+
+    let mut post_parser = PostParser::new(&post_parser_context);
+    post_parser.set_search_query(Option::Some(plain("test")));
+
+    let post_comment_parsed = post_parser.parse_post(&post_raw).post_comment_parsed;
+    assert_eq!(vec![(0, 4), (9, 4), (18, 4)], post_comment_parsed.search_highlights());
+
+    post_parser.set_search_query(Option::None);
+
+    let post_comment_parsed = post_parser.parse_post(&post_raw).post_comment_parsed;
+    assert!(post_comment_parsed.search_highlights().is_empty());
+  }
+
+}
@@ -0,0 +1,495 @@
+#[cfg(test)]
+mod test_main {
+  use new_post_parser_lib::{PostParserContext, PostRaw, PostParser, set_of, Spannable, SpannableData, PostLink, UrlKind, PostDescriptor, BoardDescriptor};
+  use new_post_parser_lib::site_profiles::site_profile::SiteProfile;
+  use new_post_parser_lib::site_profiles::lynx_chan::LynxChanSiteProfile;
+  use new_post_parser_lib::site_profiles::vichan::VichanSiteProfile;
+  use new_post_parser_lib::site_profiles::foolfuuka::FoolFuukaSiteProfile;
+  use std::collections::HashSet;
+
+  fn run_test(
+    site_name: &str,
+    board_code: &str,
+    post_parser_context: &PostParserContext,
+    raw_comment: &str,
+    expected_parsed_text: &str,
+    expected_spannables: &Vec<Spannable>
+  ) {
+    run_test_with_profile(Option::None, site_name, board_code, post_parser_context, raw_comment, expected_parsed_text, expected_spannables);
+  }
+
+  fn run_test_with_profile(
+    site_profile: Option<&dyn SiteProfile>,
+    site_name: &str,
+    board_code: &str,
+    post_parser_context: &PostParserContext,
+    raw_comment: &str,
+    expected_parsed_text: &str,
+    expected_spannables: &Vec<Spannable>
+  ) {
+    let post_raw = PostRaw::new(site_name, board_code, 1234567890, 1234567891, 0, raw_comment);
+
+    let mut post_parser = PostParser::new(post_parser_context);
+    if let Option::Some(site_profile) = site_profile {
+      post_parser.add_site_profile(site_profile);
+    }
+
+    let post_comment_parsed = post_parser.parse_post(&post_raw).post_comment_parsed;
+
+    assert_eq!(expected_parsed_text, post_comment_parsed.parsed_text.as_str());
+    assert_eq!(expected_spannables, post_comment_parsed.spannables.as_ref());
+  }
+
+  #[test]
+  fn site_profile_test_four_chan() {
+    let post_comment_raw = "<a href=\"#p1234567880\" class=\"quotelink\">&gt;&gt;1234567880</a><br>very<wbr>long<wbr>word";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 12, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567880 }) },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(1234567880));
+    run_test("4chan", "g", &post_parser_context, post_comment_raw, ">>1234567880\nverylongword", &expected_spannables);
+  }
+
+  #[test]
+  fn site_profile_test_four_chan_same_thread_links_are_quotes() {
+    let post_comment_raw = "<a href=\"/g/thread/1234567890#p1234567880\" class=\"quotelink\">&gt;&gt;1234567880</a> \
+    <a href=\"/g/thread/1234567890#p77\" class=\"quotelink\">&gt;&gt;77</a> \
+    <a href=\"/g/thread/1#p2\" class=\"quotelink\">&gt;&gt;2</a>";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 12, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567880 }) },
+      Spannable { start: 13, len: 11, spannable_data: SpannableData::Link(PostLink::Dead { post_no: 77 }) },
+      Spannable {
+        start: 25,
+        len: 5,
+        spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "g".to_string(), thread_no: 1, post_no: 2 })
+      },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(1234567880));
+    run_test("4chan", "g", &post_parser_context, post_comment_raw, ">>1234567880 >>77 (DEAD) >>2 →", &expected_spannables);
+  }
+
+  #[test]
+  #[allow(deprecated)]
+  fn site_profile_test_comment_parser_compat() {
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    let mut post_parser = PostParser::new(&post_parser_context);
+
+    let comment_parser = post_parser.comment_parser_mut("4chan").unwrap();
+    comment_parser.add_default_matching_rules();
+    comment_parser.add_replacement_rule("[sic]", "");
+
+    let post_raw = PostRaw::new("4chan", "g", 1234567890, 1234567891, 0, "<b>bold</b>[sic]");
+    let post_comment_parsed = post_parser.parse_post(&post_raw).post_comment_parsed;
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 4, spannable_data: SpannableData::BoldText },
+    ];
+
+    assert_eq!("bold", post_comment_parsed.parsed_text.as_str());
+    assert_eq!(&expected_spannables, post_comment_parsed.spannables.as_ref());
+    assert!(post_parser.comment_parser_mut("unknown").is_none());
+  }
+
+  #[test]
+  fn site_profile_test_unknown_site_falls_back_to_four_chan() {
+    let post_comment_raw = "<a href=\"/vg/thread/333581281#p333581282\" class=\"quotelink\">&gt;&gt;333581282</a>";
+
+    let expected_spannables = vec![
+      Spannable {
+        start: 0,
+        len: 13,
+        spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "vg".to_string(), thread_no: 333581281, post_no: 333581282 })
+      },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    run_test("unknown_site", "vg", &post_parser_context, post_comment_raw, ">>333581282 →", &expected_spannables);
+  }
+
+  #[test]
+  fn site_profile_test_dvach_quotes() {
+    let post_comment_raw = "<a href=\"/b/res/1234567890.html#1234567880\" class=\"post-reply-link\" data-thread=\"1234567890\" data-num=\"1234567880\">&gt;&gt;1234567880</a><br>\
+    <a href=\"/b/res/1234567890.html#1234567890\" class=\"post-reply-link\" data-thread=\"1234567890\" data-num=\"1234567890\">&gt;&gt;1234567890</a><br>\
+    <a href=\"/po/res/55.html#56\" class=\"post-reply-link\" data-thread=\"55\" data-num=\"56\">&gt;&gt;56</a>";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 19, spannable_data: SpannableData::Link(PostLink::Dead { post_no: 1234567880 }) },
+      Spannable { start: 20, len: 17, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567890 }) },
+      Spannable { start: 38, len: 6, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "po".to_string(), thread_no: 55, post_no: 56 }) },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(1234567890));
+    run_test(
+      "2ch.hk",
+      "b",
+      &post_parser_context,
+      post_comment_raw,
+      ">>1234567880 (DEAD)\n>>1234567890 (OP)\n>>56 →",
+      &expected_spannables
+    );
+  }
+
+  #[test]
+  fn site_profile_test_dvach_quotes_prefer_data_attributes() {
+    // The "href" points to the thread only, the post is in "data-num"
+    let post_comment_raw = "<a href=\"/b/res/1234567890.html\" class=\"post-reply-link\" data-thread=\"1234567890\" data-num=\"1234567885\">&gt;&gt;1234567885</a><br>\
+    <a href=\"https://2ch.hk/po/res/55.html\" class=\"post-reply-link\" data-thread=\"55\" data-num=\"56\">&gt;&gt;56</a><br>\
+    <a href=\"/b/res/1234567890.html#1234567880\" class=\"post-reply-link\" data-num=\"bad\">&gt;&gt;1234567880</a>";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 12, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567885 }) },
+      Spannable { start: 13, len: 6, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "po".to_string(), thread_no: 55, post_no: 56 }) },
+      Spannable { start: 20, len: 19, spannable_data: SpannableData::Link(PostLink::Dead { post_no: 1234567880 }) },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(1234567885));
+    run_test(
+      "2ch.hk",
+      "b",
+      &post_parser_context,
+      post_comment_raw,
+      ">>1234567885\n>>56 →\n>>1234567880 (DEAD)",
+      &expected_spannables
+    );
+  }
+
+  #[test]
+  fn site_profile_test_dvach_styles() {
+    let cases = vec![
+      ("<span class=\"unkfunc\">&gt;greentext</span>", ">greentext", SpannableData::GreenText),
+      ("<span class=\"spoiler\">spoiler</span>", "spoiler", SpannableData::Spoiler),
+      ("<strong>strong</strong>", "strong", SpannableData::BoldText),
+      ("<em>em</em>", "em", SpannableData::ItalicText),
+      ("<span class=\"u\">underline</span>", "underline", SpannableData::UnderlineText),
+      ("<span class=\"o\">overline</span>", "overline", SpannableData::OverlineText),
+      ("<span class=\"s\">strikethrough</span>", "strikethrough", SpannableData::StrikethroughText),
+      ("<sup>sup</sup>", "sup", SpannableData::SuperscriptText),
+      ("<sub>sub</sub>", "sub", SpannableData::SubscriptText),
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+
+    for (post_comment_raw, expected_parsed_text, spannable_data) in cases {
+      let expected_spannables = vec![
+        Spannable { start: 0, len: expected_parsed_text.chars().count(), spannable_data },
+      ];
+
+      run_test("2ch.hk", "b", &post_parser_context, post_comment_raw, expected_parsed_text, &expected_spannables);
+    }
+
+    // The text before the element is not marked
+    let expected_spannables = vec![
+      Spannable { start: 8, len: 2, spannable_data: SpannableData::ItalicText },
+      Spannable { start: 11, len: 9, spannable_data: SpannableData::UnderlineText },
+    ];
+
+    run_test("2ch.hk", "b", &post_parser_context, "foo bar <em>it</em> <span class=\"u\">underline</span> baz", "foo bar it underline baz", &expected_spannables);
+  }
+
+  #[test]
+  fn site_profile_test_lynx_chan_quotes() {
+    let post_comment_raw = "<a class=\"quoteLink\" href=\"/v/res/1234567890.html#1234567880\">&gt;&gt;1234567880</a><br>\
+    <a class=\"quoteLink\" href=\"/v/res/1234567890.html#1234567885\">&gt;&gt;1234567885</a><br>\
+    <a class=\"quoteLink\" href=\"/v/res/1000.html#1001\">&gt;&gt;1001</a>";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 12, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567880 }) },
+      Spannable { start: 13, len: 19, spannable_data: SpannableData::Link(PostLink::Dead { post_no: 1234567885 }) },
+      Spannable { start: 33, len: 8, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "v".to_string(), thread_no: 1000, post_no: 1001 }) },
+    ];
+
+    let site_profile = LynxChanSiteProfile::new("endchan");
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(1234567880));
+
+    run_test_with_profile(
+      Option::Some(&site_profile),
+      "endchan",
+      "v",
+      &post_parser_context,
+      post_comment_raw,
+      ">>1234567880\n>>1234567885 (DEAD)\n>>1001 →",
+      &expected_spannables
+    );
+  }
+
+  #[test]
+  fn site_profile_test_lynx_chan_styles() {
+    let cases = vec![
+      ("<span class=\"greenText\">&gt;greentext</span>", ">greentext", SpannableData::GreenText),
+      ("<span class=\"redText\">==redtext==</span>", "==redtext==", SpannableData::RedText),
+      ("<span class=\"orangeText\">&lt;orangetext</span>", "<orangetext", SpannableData::OrangeText),
+      ("<span class=\"spoiler\">spoiler</span>", "spoiler", SpannableData::Spoiler),
+      ("<span class=\"aa\">（ ´∀｀）</span>", "（ ´∀｀）", SpannableData::AsciiArt),
+      ("<s>strikethrough</s>", "strikethrough", SpannableData::StrikethroughText),
+    ];
+
+    let site_profile = LynxChanSiteProfile::new("endchan");
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+
+    for (post_comment_raw, expected_parsed_text, spannable_data) in cases {
+      let expected_spannables = vec![
+        Spannable { start: 0, len: expected_parsed_text.chars().count(), spannable_data },
+      ];
+
+      run_test_with_profile(
+        Option::Some(&site_profile),
+        "endchan",
+        "v",
+        &post_parser_context,
+        post_comment_raw,
+        expected_parsed_text,
+        &expected_spannables
+      );
+    }
+
+    // The text before the element is not marked
+    let expected_spannables = vec![
+      Spannable { start: 8, len: 3, spannable_data: SpannableData::RedText },
+      Spannable { start: 12, len: 6, spannable_data: SpannableData::StrikethroughText },
+    ];
+
+    run_test_with_profile(
+      Option::Some(&site_profile),
+      "endchan",
+      "v",
+      &post_parser_context,
+      "foo bar <span class=\"redText\">red</span> <s>strike</s>",
+      "foo bar red strike",
+      &expected_spannables
+    );
+  }
+
+  #[test]
+  fn site_profile_test_vichan_quotes() {
+    let post_comment_raw = "<a onclick=\"highlightReply('1234567880', event);\" href=\"/b/res/1234567890.html#1234567880\">&gt;&gt;1234567880</a><br>\
+    <a onclick=\"highlightReply('5', event);\" href=\"/pol/res/4.html#5\">&gt;&gt;&gt;/pol/5</a><br>\
+    <a href=\"/tech/index.html\">&gt;&gt;&gt;/tech/</a>";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 12, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567880 }) },
+      Spannable { start: 13, len: 11, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "pol".to_string(), thread_no: 4, post_no: 5 }) },
+      Spannable { start: 25, len: 9, spannable_data: SpannableData::Link(PostLink::BoardLink { board_code: "tech".to_string() }) },
+    ];
+
+    let site_profile = VichanSiteProfile::new("8kun");
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(1234567880));
+
+    run_test_with_profile(
+      Option::Some(&site_profile),
+      "8kun",
+      "b",
+      &post_parser_context,
+      post_comment_raw,
+      ">>1234567880\n>>>/pol/5 →\n>>>/tech/",
+      &expected_spannables
+    );
+  }
+
+  #[test]
+  fn site_profile_test_vichan_styles() {
+    let cases = vec![
+      ("<span class=\"quote\">&gt;greentext</span>", ">greentext", SpannableData::GreenText),
+      ("<span class=\"heading\">heading</span>", "heading", SpannableData::Heading { level: 2 }),
+      ("<span class=\"spoiler\">spoiler</span>", "spoiler", SpannableData::Spoiler),
+      ("<em>italic</em>", "italic", SpannableData::ItalicText),
+      ("<u>underline</u>", "underline", SpannableData::UnderlineText),
+      ("<strong>bold</strong>", "bold", SpannableData::BoldText),
+    ];
+
+    let site_profile = VichanSiteProfile::new("8kun");
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+
+    for (post_comment_raw, expected_parsed_text, spannable_data) in cases {
+      let expected_spannables = vec![
+        Spannable { start: 0, len: expected_parsed_text.chars().count(), spannable_data },
+      ];
+
+      run_test_with_profile(
+        Option::Some(&site_profile),
+        "8kun",
+        "b",
+        &post_parser_context,
+        post_comment_raw,
+        expected_parsed_text,
+        &expected_spannables
+      );
+    }
+
+    // The text before the element is not marked
+    let expected_spannables = vec![
+      Spannable { start: 8, len: 7, spannable_data: SpannableData::Heading { level: 2 } },
+      Spannable { start: 16, len: 2, spannable_data: SpannableData::ItalicText },
+    ];
+
+    run_test_with_profile(
+      Option::Some(&site_profile),
+      "8kun",
+      "b",
+      &post_parser_context,
+      "foo bar <span class=\"heading\">heading</span> <em>it</em>",
+      "foo bar heading it",
+      &expected_spannables
+    );
+  }
+
+  #[test]
+  fn site_profile_test_foolfuuka() {
+    let post_comment_raw = "<span class=\"greentext\">&gt;archived</span><br />\
+    <a href=\"https://desuarchive.org/g/thread/1234567890/#1234567880\" class=\"backlink\" data-function=\"highlight\" data-backlink=\"true\" data-board=\"g\" data-post=\"1234567880\">&gt;&gt;1234567880</a><br />\
+    <a href=\"https://desuarchive.org/g/thread/1234567890/#1234567885\" class=\"backlink\" data-board=\"g\" data-post=\"1234567885\">&gt;&gt;1234567885</a><br />\
+    <a href=\"https://desuarchive.org/a/thread/7/#8\" class=\"backlink\" data-board=\"a\" data-post=\"8\">&gt;&gt;&gt;/a/8</a>";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 9, spannable_data: SpannableData::GreenText },
+      Spannable { start: 10, len: 12, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567880 }) },
+      Spannable { start: 23, len: 19, spannable_data: SpannableData::Link(PostLink::Dead { post_no: 1234567885 }) },
+      Spannable { start: 43, len: 9, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "a".to_string(), thread_no: 7, post_no: 8 }) },
+    ];
+
+    let site_profile = FoolFuukaSiteProfile::new("desuarchive", "desuarchive.org");
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(1234567880));
+
+    run_test_with_profile(
+      Option::Some(&site_profile),
+      "desuarchive",
+      "g",
+      &post_parser_context,
+      post_comment_raw,
+      ">archived\n>>1234567880\n>>1234567885 (DEAD)\n>>>/a/8 →",
+      &expected_spannables
+    );
+
+    // Quotes to the posts that were not archived are plain text
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 12, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567880 }) },
+      Spannable { start: 17, len: 9, spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "a".to_string(), thread_no: 7, post_no: 7 }) },
+    ];
+
+    run_test_with_profile(
+      Option::Some(&site_profile),
+      "desuarchive",
+      "g",
+      &post_parser_context,
+      "&gt;&gt;1234567880 and &gt;&gt;&gt;/a/7",
+      ">>1234567880 and >>>/a/7 →",
+      &expected_spannables
+    );
+  }
+
+  #[test]
+  fn site_profile_test_detect_plain_text_quotes_for_all_sites() {
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(1234567880));
+    let mut post_parser = PostParser::new(&post_parser_context);
+    let post_raw = PostRaw::new("4chan", "g", 1234567890, 1234567891, 0, "&gt;&gt;1234567880 draft");
+
+    // 4chan quotes are always <a> tags
+    assert!(post_parser.parse_post(&post_raw).post_comment_parsed.spannables.is_empty());
+
+    post_parser.set_detect_plain_text_quotes(true);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 12, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567880 }) },
+    ];
+
+    let post_comment_parsed = post_parser.parse_post(&post_raw).post_comment_parsed;
+    assert_eq!(">>1234567880 draft", post_comment_parsed.parsed_text.as_str());
+    assert_eq!(&expected_spannables, post_comment_parsed.spannables.as_ref());
+  }
+
+  #[test]
+  fn site_profile_test_absolute_site_urls() {
+    let post_comment_raw = "https://boards.4chan.org/g/thread/1234567890/#p1234567880 https://boards.4channel.org/jp/catalog#s=foo \
+    https://2ch.hk/b/res/5.html#6 https://example.com/ https://2ch.hk/po/";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 57, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567880 }) },
+      Spannable {
+        start: 58,
+        len: 44,
+        spannable_data: SpannableData::Link(PostLink::SearchLink { board_code: "jp".to_string(), search_query: "foo".to_string() })
+      },
+      Spannable {
+        start: 103,
+        len: 31,
+        spannable_data: SpannableData::Link(PostLink::CrossSiteThreadLink {
+          post_descriptor: PostDescriptor::new("2ch.hk", "b", 5, 6, 0)
+        })
+      },
+      Spannable { start: 135, len: 20, spannable_data: SpannableData::Link(PostLink::UrlLink { link: "https://example.com/".to_string(), url_kind: UrlKind::Generic }) },
+      Spannable {
+        start: 156,
+        len: 18,
+        spannable_data: SpannableData::Link(PostLink::CrossSiteBoardLink { board_descriptor: BoardDescriptor::new("2ch.hk", "po") })
+      },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(1234567880));
+    let expected_parsed_text = "https://boards.4chan.org/g/thread/1234567890/#p1234567880 https://boards.4channel.org/jp/catalog#s=foo \
+    https://2ch.hk/b/res/5.html#6 → https://example.com/ https://2ch.hk/po/";
+
+    run_test("4chan", "g", &post_parser_context, post_comment_raw, expected_parsed_text, &expected_spannables);
+  }
+
+  #[test]
+  fn site_profile_test_absolute_site_urls_have_link_suffixes() {
+    let post_comment_raw = "https://boards.4chan.org/g/thread/1234567890#p77 https://boards.4chan.org/g/thread/1234567890#p1234567890 \
+    https://boards.4chan.org/g/thread/1234567890#p1234567880 https://boards.4chan.org/g/thread/1#p2";
+    let expected_parsed_text = "https://boards.4chan.org/g/thread/1234567890#p77 (DEAD) \
+    https://boards.4chan.org/g/thread/1234567890#p1234567890 (OP) \
+    https://boards.4chan.org/g/thread/1234567890#p1234567880 (You) https://boards.4chan.org/g/thread/1#p2 →";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 55, spannable_data: SpannableData::Link(PostLink::Dead { post_no: 77 }) },
+      Spannable { start: 56, len: 61, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567890 }) },
+      Spannable { start: 118, len: 62, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567880 }) },
+      Spannable {
+        start: 181,
+        len: 40,
+        spannable_data: SpannableData::Link(PostLink::ThreadLink { board_code: "g".to_string(), thread_no: 1, post_no: 2 })
+      },
+    ];
+
+    let post_parser_context = PostParserContext::new(set_of!(1234567880), set_of!(1234567880, 1234567890));
+    run_test("4chan", "g", &post_parser_context, post_comment_raw, expected_parsed_text, &expected_spannables);
+
+    // The same urls in <a> tags
+    let post_comment_raw = "<a href=\"https://boards.4chan.org/g/thread/1234567890#p77\">https://boards.4chan.org/g/thread/1234567890#p77</a> \
+    <a href=\"https://boards.4chan.org/g/thread/1234567890#p1234567890\">https://boards.4chan.org/g/thread/1234567890#p1234567890</a> \
+    <a href=\"https://boards.4chan.org/g/thread/1234567890#p1234567880\">https://boards.4chan.org/g/thread/1234567890#p1234567880</a> \
+    <a href=\"https://boards.4chan.org/g/thread/1#p2\">https://boards.4chan.org/g/thread/1#p2</a>";
+
+    run_test("4chan", "g", &post_parser_context, post_comment_raw, expected_parsed_text, &expected_spannables);
+  }
+
+  #[test]
+  fn site_profile_test_duplicate_site_hosts_are_ignored() {
+    let post_comment_raw = "https://boards.4chan.org/g/thread/1234567890/#p1234567880";
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 57, spannable_data: SpannableData::Link(PostLink::Quote { post_no: 1234567880 }) },
+    ];
+
+    // The host of 4chan stays with the 4chan profile
+    let site_profile = FoolFuukaSiteProfile::new("desuarchive", "boards.4chan.org");
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!(1234567880));
+
+    let mut post_parser = PostParser::new(&post_parser_context);
+    assert!(!post_parser.add_site_profile(&site_profile));
+    assert!(post_parser.add_site_profile(&FoolFuukaSiteProfile::new("archived.moe", "archived.moe")));
+
+    run_test_with_profile(
+      Option::Some(&site_profile),
+      "4chan",
+      "g",
+      &post_parser_context,
+      post_comment_raw,
+      post_comment_raw,
+      &expected_spannables
+    );
+  }
+
+}
@@ -0,0 +1,142 @@
+#[cfg(test)]
+mod test_main {
+  use new_post_parser_lib::util::theme_color_mapper::{ThemeColorMapper, DEFAULT_MIN_CONTRAST_RATIO};
+  use new_post_parser_lib::{PostParserContext, PostRaw, PostParser, set_of, Spannable, SpannableData, ChanThemeColorId};
+  use std::collections::HashSet;
+
+  fn parse_spannables(post_parser: &PostParser, post_comment_raw: &str) -> Vec<Spannable> {
+    return parse_site_spannables(post_parser, "4chan", post_comment_raw);
+  }
+
+  fn parse_site_spannables(post_parser: &PostParser, site_name: &str, post_comment_raw: &str) -> Vec<Spannable> {
+    let post_raw = PostRaw::new(site_name, "g", 1234567890, 1234567891, 0, post_comment_raw);
+    return *post_parser.parse_post(&post_raw).post_comment_parsed.spannables;
+  }
+
+  #[test]
+  fn theme_color_mapper_test_default_colors_and_classes() {
+    let post_comment_raw = "<span style=\"color:#789922\">green</span> <span class=\"name\">Anonymous</span>";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    let post_parser = PostParser::new(&post_parser_context);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 5, spannable_data: SpannableData::TextForegroundColorId { color_id: ChanThemeColorId::PostInlineQuoteColor } },
+      Spannable { start: 6, len: 9, spannable_data: SpannableData::TextForegroundColorId { color_id: ChanThemeColorId::PostNameColor } },
+    ];
+
+    assert_eq!(expected_spannables, parse_spannables(&post_parser, post_comment_raw));
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 3, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#FF0000FF".to_string() } },
+    ];
+
+    assert_eq!(expected_spannables, parse_spannables(&post_parser, "<span style=\"color:red\">red</span>"));
+  }
+
+  #[test]
+  fn theme_color_mapper_test_colors_of_other_sites_are_not_mapped() {
+    let post_comment_raw = "<span style=\"color:#789922\">green</span>";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    let post_parser = PostParser::new(&post_parser_context);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 5, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#789922FF".to_string() } },
+    ];
+
+    assert_eq!(expected_spannables, parse_site_spannables(&post_parser, "2ch.hk", post_comment_raw));
+
+    // Black and maroon are colors that posters pick themselves
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 5, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#000000FF".to_string() } },
+    ];
+
+    assert_eq!(expected_spannables, parse_spannables(&post_parser, "<span style=\"color:#000000\">black</span>"));
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 6, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#800000FF".to_string() } },
+    ];
+
+    assert_eq!(expected_spannables, parse_spannables(&post_parser, "<span style=\"color:#800000\">maroon</span>"));
+  }
+
+  #[test]
+  fn theme_color_mapper_test_custom_mapper() {
+    let post_comment_raw = "<span style=\"background: rgb(255, 0, 0)\">red</span> <span class=\"Fortune extra\">fortune</span>";
+
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    let mut post_parser = PostParser::new(&post_parser_context);
+
+    let mut theme_color_mapper = ThemeColorMapper::new();
+    theme_color_mapper.add_color("red", ChanThemeColorId::AccentColor);
+    theme_color_mapper.add_class("fortune", ChanThemeColorId::PostSubjectColor);
+    post_parser.set_theme_color_mapper(Option::Some(theme_color_mapper));
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 3, spannable_data: SpannableData::TextBackgroundColorId { color_id: ChanThemeColorId::AccentColor } },
+      Spannable { start: 4, len: 7, spannable_data: SpannableData::TextForegroundColorId { color_id: ChanThemeColorId::PostSubjectColor } },
+    ];
+
+    assert_eq!(expected_spannables, parse_spannables(&post_parser, post_comment_raw));
+
+    // The default colors are not mapped anymore
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 5, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#789922FF".to_string() } },
+    ];
+
+    assert_eq!(expected_spannables, parse_spannables(&post_parser, "<span style=\"color:#789922\">green</span>"));
+
+    // The mappers of the site profiles are used again
+    post_parser.set_theme_color_mapper(Option::None);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 5, spannable_data: SpannableData::TextForegroundColorId { color_id: ChanThemeColorId::PostInlineQuoteColor } },
+    ];
+
+    assert_eq!(expected_spannables, parse_spannables(&post_parser, "<span style=\"color:#789922\">green</span>"));
+  }
+
+  #[test]
+  fn theme_color_mapper_test_contrast_adjustment() {
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    let mut post_parser = PostParser::new(&post_parser_context);
+
+    let mut theme_color_mapper = ThemeColorMapper::new();
+    theme_color_mapper.set_contrast_background(Option::Some("#1D1F21"), DEFAULT_MIN_CONTRAST_RATIO);
+    post_parser.set_theme_color_mapper(Option::Some(theme_color_mapper));
+
+    // Navy is unreadable on a dark background so it's made lighter
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 4, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#6666B3FF".to_string() } },
+    ];
+
+    assert_eq!(expected_spannables, parse_spannables(&post_parser, "<span style=\"color:#000080\">navy</span>"));
+
+    // Yellow is fine as is
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 6, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#FFFF00FF".to_string() } },
+    ];
+
+    assert_eq!(expected_spannables, parse_spannables(&post_parser, "<span style=\"color:#FFFF00\">yellow</span>"));
+  }
+
+  #[test]
+  fn theme_color_mapper_test_non_finite_contrast_ratio_is_the_default() {
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+    let mut post_parser = PostParser::new(&post_parser_context);
+
+    let expected_spannables = vec![
+      Spannable { start: 0, len: 4, spannable_data: SpannableData::TextForegroundColorRaw { color_hex: "#6666B3FF".to_string() } },
+    ];
+
+    for min_contrast_ratio in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+      let mut theme_color_mapper = ThemeColorMapper::new();
+      theme_color_mapper.set_contrast_background(Option::Some("#1D1F21"), min_contrast_ratio);
+      post_parser.set_theme_color_mapper(Option::Some(theme_color_mapper));
+
+      assert_eq!(expected_spannables, parse_spannables(&post_parser, "<span style=\"color:#000080\">navy</span>"));
+    }
+  }
+
+}
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod test_main {
+  use new_post_parser_lib::util::url_classifier::{UrlClassifierRegistry, UrlClassifier, path_segments};
+  use new_post_parser_lib::{PostParserContext, PostRaw, PostParser, set_of, Spannable, SpannableData, PostLink, UrlKind};
+  use std::collections::HashSet;
+  use std::rc::Rc;
+
+  fn youtube(video_id: &str, start_time_seconds: Option<u64>) -> UrlKind {
+    return UrlKind::YouTube { video_id: video_id.to_string(), start_time_seconds };
+  }
+
+  #[test]
+  fn url_classifier_test_built_in_classifiers() {
+    let test_cases = vec![
+      ("https://www.youtube.com/watch?v=57tu8AtKf9E", youtube("57tu8AtKf9E", Option::None)),
+      ("https://m.youtube.com/watch?feature=share&v=57tu8AtKf9E&t=1m30s", youtube("57tu8AtKf9E", Option::Some(90))),
+      ("https://youtu.be/57tu8AtKf9E?t=42", youtube("57tu8AtKf9E", Option::Some(42))),
+      ("https://www.youtube.com/shorts/57tu8AtKf9E", youtube("57tu8AtKf9E", Option::None)),
+      ("https://www.youtube.com/embed/57tu8AtKf9E?start=10", youtube("57tu8AtKf9E", Option::Some(10))),
+      ("https://www.youtube.com/watch?v=57tu8AtKf9E#t=1h", youtube("57tu8AtKf9E", Option::Some(3600))),
+      ("https://www.youtube.com/watch?v=short", UrlKind::Generic),
+      ("https://www.youtube.com/channel/UC1234567890", UrlKind::Generic),
+      ("https://streamable.com/abc123", UrlKind::Streamable { video_id: "abc123".to_string() }),
+      ("https://streamable.com/e/abc123", UrlKind::Streamable { video_id: "abc123".to_string() }),
+      ("https://twitter.com/user/status/1234567890123", UrlKind::Twitter { tweet_id: 1234567890123 }),
+      ("https://x.com/user/status/1234567890123/photo/1", UrlKind::Twitter { tweet_id: 1234567890123 }),
+      ("https://twitter.com/user", UrlKind::Generic),
+      ("https://soundcloud.com/artist/track", UrlKind::SoundCloud { track_path: "artist/track".to_string() }),
+      ("https://soundcloud.com/artist", UrlKind::Generic),
+      ("https://www.pixiv.net/en/artworks/12345678", UrlKind::Pixiv { artwork_id: 12345678 }),
+      ("https://www.pixiv.net/member_illust.php?mode=medium&illust_id=12345678", UrlKind::Pixiv { artwork_id: 12345678 }),
+      ("https://pastebin.com/YTGdpqZL", UrlKind::Pastebin { paste_id: "YTGdpqZL".to_string() }),
+      ("https://pastebin.com/raw/YTGdpqZL", UrlKind::Pastebin { paste_id: "YTGdpqZL".to_string() }),
+      ("https://pastebin.com/u/someone", UrlKind::Generic),
+      ("https://i.imgur.com/3CDmFQm.JPG", UrlKind::Image { extension: "jpg".to_string() }),
+      ("https://example.com/video.webm?download=1", UrlKind::Video { extension: "webm".to_string() }),
+      ("https://example.com/song.mp3#t=10", UrlKind::Audio { extension: "mp3".to_string() }),
+      ("https://example.com/archive.zip", UrlKind::Generic),
+      ("https://example.com/", UrlKind::Generic),
+      ("magnet:?xt=urn:btih:c12fe1c06bba254a9dc9f519b335aa7c1367a88a", UrlKind::Generic),
+    ];
+
+    let url_classifier_registry = UrlClassifierRegistry::new();
+
+    for (url, expected_url_kind) in test_cases {
+      assert_eq!(expected_url_kind, url_classifier_registry.classify(url), "url: {}", url);
+    }
+  }
+
+  struct GistClassifier {}
+
+  impl UrlClassifier for GistClassifier {
+    fn classify(&self, host: &str, path: &str) -> Option<UrlKind> {
+      if host != "gist.github.com" {
+        return Option::None;
+      }
+
+      let gist_id = path_segments(path).last()?.to_string();
+      return Option::Some(UrlKind::Custom { kind: "gist".to_string(), id: gist_id });
+    }
+  }
+
+  #[test]
+  fn url_classifier_test_custom_classifier_and_anchors() {
+    let post_comment_raw = "https://gist.github.com/user/abc123<br>\
+    <a href=\"https://i.imgur.com/3CDmFQm.png\" target=\"_blank\">pic</a>";
+
+    let post_raw = PostRaw::new("4chan", "g", 1234567890, 1234567891, 0, post_comment_raw);
+    let post_parser_context = PostParserContext::new(set_of!(), set_of!());
+
+    let mut post_parser = PostParser::new(&post_parser_context);
+    post_parser.add_url_classifier(Rc::new(GistClassifier {}));
+
+    let expected_spannables = vec![
+      Spannable {
+        start: 0,
+        len: 35,
+        spannable_data: SpannableData::Link(PostLink::UrlLink {
+          link: "https://gist.github.com/user/abc123".to_string(),
+          url_kind: UrlKind::Custom { kind: "gist".to_string(), id: "abc123".to_string() }
+        })
+      },
+      Spannable {
+        start: 36,
+        len: 3,
+        spannable_data: SpannableData::Link(PostLink::UrlLink {
+          link: "https://i.imgur.com/3CDmFQm.png".to_string(),
+          url_kind: UrlKind::Image { extension: "png".to_string() }
+        })
+      },
+    ];
+
+    assert_eq!(&expected_spannables, post_parser.parse_post(&post_raw).post_comment_parsed.spannables.as_ref());
+  }
+
+}